@@ -0,0 +1,70 @@
+/* Copyright 2023 Alex Jago <abjago.net>
+ * Released under the MIT or Apache-2.0 licenses, at your option.
+ *
+ * C interface to range_split, from its `ffi` feature. Build a library with e.g.
+ *     cargo rustc --release --features ffi --crate-type staticlib
+ * Keep in step with src/ffi.rs: its tests check the declarations and layouts here.
+ */
+
+#ifndef RANGE_SPLIT_H
+#define RANGE_SPLIT_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The half-open range [start, end). */
+typedef struct range_t {
+    uint64_t start;
+    uint64_t end;
+} range_t;
+
+/* The pieces of a split; each is only meaningful if its has_ flag is set. */
+typedef struct range_pieces_t {
+    range_t below;
+    range_t inter;
+    range_t above;
+    bool has_below;
+    bool has_inter;
+    bool has_above;
+} range_pieces_t;
+
+/* A sorted, disjoint, coalesced set of ranges. */
+typedef struct range_set_t range_set_t;
+
+/* Split `a` by `b` into the parts below, inside, and above `b`. */
+void range_split(range_t a, range_t b, range_pieces_t *out);
+
+/* Write the intersection of `a` and `b` to `out`; returns whether it's non-empty. */
+bool range_intersect(range_t a, range_t b, range_t *out);
+
+/* A new, empty set, to be released with range_set_free. */
+range_set_t *range_set_new(void);
+
+/* Release a set from range_set_new. NULL is ignored. */
+void range_set_free(range_set_t *set);
+
+/* Add [start, end) to the set. */
+void range_set_insert(range_set_t *set, uint64_t start, uint64_t end);
+
+/* Remove [start, end) from the set. */
+void range_set_remove(range_set_t *set, uint64_t start, uint64_t end);
+
+/* Whether `t` is in the set. */
+bool range_set_contains(const range_set_t *set, uint64_t t);
+
+/* Number of runs in the set. */
+size_t range_set_len(const range_set_t *set);
+
+/* Write the `i`th run to `out`; returns false if there's no such run. */
+bool range_set_get(const range_set_t *set, size_t i, range_t *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RANGE_SPLIT_H */
@@ -0,0 +1,415 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Carving aligned allocations out of a pool of free space.
+
+use core::ops::{Add, Range, Rem, Sub};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::storage::{RangeSet, RunStorage};
+
+/// Which free run an allocation is carved from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// The lowest-addressed run that fits.
+    FirstFit,
+    /// The shortest run that fits; ties go to the lowest address.
+    BestFit,
+    /// The longest run; ties go to the lowest address.
+    WorstFit,
+}
+
+/** A free-space allocator over a domain of `T`.
+
+Usage:
+```
+# use range_split::allocator::{Allocator, Strategy};
+let mut heap = Allocator::new(0..100u32);
+let a = heap.allocate(10, 8, Strategy::FirstFit).unwrap();
+assert_eq!(a, 0..10);
+let b = heap.allocate(10, 8, Strategy::FirstFit).unwrap();
+assert_eq!(b, 16..26);
+heap.free(a);
+assert_eq!(heap.free_ranges().iter().collect::<Vec<_>>(), vec![0..16, 26..100]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allocator<T> {
+    free: RangeSet<T>,
+}
+
+impl<T> Allocator<T>
+where
+    T: Sized + Ord + Copy + Default + Add<Output = T> + Sub<Output = T> + Rem<Output = T>,
+{
+    /// An allocator whose whole `domain` is free.
+    pub fn new(domain: Range<T>) -> Self {
+        let mut free = RangeSet::new();
+        free.insert(domain);
+        Self { free }
+    }
+
+    /// Carve `size` units aligned to a multiple of `align` out of the free space.
+    ///
+    /// Returns `None` if `size` is zero or no free run can hold the allocation.
+    /// Panics if `align` is zero.
+    pub fn allocate(&mut self, size: T, align: T, strategy: Strategy) -> Option<Range<T>> {
+        let zero = T::default();
+        assert!(align > zero, "alignment must be positive");
+        if size <= zero {
+            return None;
+        }
+
+        // Aligned start of the allocation within run `r`, if it fits
+        let fit = |r: &Range<T>| {
+            let pad = match r.start % align {
+                rem if rem == zero => zero,
+                rem => align - rem,
+            };
+            let room = r.end - r.start;
+            (pad <= room && size <= room - pad).then(|| r.start + pad)
+        };
+        let mut candidates = self
+            .free
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| Some((i, fit(&r)?, r.end - r.start)));
+        let (_, start, _) = match strategy {
+            Strategy::FirstFit => candidates.next(),
+            Strategy::BestFit => candidates.min_by_key(|&(i, _, len)| (len, i)),
+            Strategy::WorstFit => candidates.min_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0))),
+        }?;
+
+        let allocation = start..start + size;
+        self.free.remove(&allocation);
+        Some(allocation)
+    }
+
+    /// Return `range` to the free space.
+    pub fn free(&mut self, range: Range<T>) {
+        self.free.insert(range);
+    }
+
+    /// The free space.
+    pub fn free_ranges(&self) -> &RangeSet<T> {
+        &self.free
+    }
+}
+
+/** A buddy allocator over the power-of-two domain `0..2^order`.
+
+Blocks are split in halves to satisfy an allocation and merged with their buddy when freed,
+so every allocation is a power of two in size (at least `2^min_order`) and aligned to its size.
+
+Usage:
+```
+# use range_split::allocator::BuddyRanges;
+let mut heap = BuddyRanges::new(6, 2); // 64 units, 4-unit minimum blocks
+let a = heap.allocate(3).unwrap();
+assert_eq!(a, 0..4);
+let b = heap.allocate(9).unwrap();
+assert_eq!(b, 16..32);
+assert_eq!(heap.free_ranges().iter().collect::<Vec<_>>(), vec![4..16, 32..64]);
+assert!(heap.free(a));
+assert_eq!(heap.free_ranges().iter().collect::<Vec<_>>(), vec![0..16, 32..64]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuddyRanges {
+    min_order: u32,
+    /// Starts of the free blocks of size `2^(min_order + k)`, indexed by `k`
+    free: Vec<BTreeSet<u64>>,
+    /// Starts of the allocated blocks, with their orders
+    allocated: BTreeMap<u64, u32>,
+}
+
+impl BuddyRanges {
+    /// Panics unless `min_order <= order < 64`.
+    pub fn new(order: u32, min_order: u32) -> Self {
+        assert!(
+            min_order <= order && order < 64,
+            "need min_order <= order < 64"
+        );
+        let mut free = vec![BTreeSet::new(); (order - min_order + 1) as usize];
+        free[(order - min_order) as usize].insert(0);
+        Self {
+            min_order,
+            free,
+            allocated: BTreeMap::new(),
+        }
+    }
+
+    /// Allocate the smallest block that holds `size` units, or `None` if there's no room.
+    pub fn allocate(&mut self, size: u64) -> Option<Range<u64>> {
+        let order = size
+            .max(1)
+            .checked_next_power_of_two()?
+            .trailing_zeros()
+            .max(self.min_order);
+        let k = (order - self.min_order) as usize;
+        // Take the smallest free block big enough, then halve it down to size
+        let mut from = (k..self.free.len()).find(|&j| !self.free[j].is_empty())?;
+        let start = self.free[from].pop_first()?;
+        while from > k {
+            from -= 1;
+            let buddy = start + self.block_len(from);
+            self.free[from].insert(buddy);
+        }
+        self.allocated.insert(start, order);
+        Some(start..start + (1 << order))
+    }
+
+    /// Free a block returned by [`allocate`](BuddyRanges::allocate), merging it with its buddies.
+    /// Returns `false` (and does nothing) if `range` isn't an allocated block.
+    pub fn free(&mut self, range: Range<u64>) -> bool {
+        match self.allocated.get(&range.start) {
+            Some(&order) if range.end.checked_sub(range.start) == Some(1 << order) => {}
+            _ => return false,
+        }
+        let order = self.allocated.remove(&range.start).expect("checked above");
+        let mut k = (order - self.min_order) as usize;
+        let mut start = range.start;
+        while k + 1 < self.free.len() {
+            let len = self.block_len(k);
+            if !self.free[k].remove(&(start ^ len)) {
+                break;
+            }
+            start &= !len;
+            k += 1;
+        }
+        self.free[k].insert(start);
+        true
+    }
+
+    /// The free space.
+    pub fn free_ranges(&self) -> RangeSet<u64> {
+        self.free
+            .iter()
+            .enumerate()
+            .flat_map(|(k, starts)| starts.iter().map(move |&s| s..s + self.block_len(k)))
+            .collect()
+    }
+
+    fn block_len(&self, k: usize) -> u64 {
+        1 << (self.min_order + k as u32)
+    }
+}
+
+/// A summary of how scattered an [`IdAllocator`]'s free IDs are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fragmentation<T> {
+    /// Total number of free IDs.
+    pub free_ids: T,
+    /// Number of disjoint runs of free IDs.
+    pub free_runs: usize,
+    /// Length of the longest free run, the largest block that can be allocated.
+    pub largest_run: T,
+}
+
+/** Hands out integer IDs (ports, inodes, handles) from a fixed range,
+lowest first, tracking the free ones as runs.
+
+Usage:
+```
+# use range_split::allocator::IdAllocator;
+let mut ids = IdAllocator::new(1..100u32);
+assert_eq!(ids.allocate(), Some(1));
+assert_eq!(ids.allocate(), Some(2));
+assert_eq!(ids.allocate_block(10), Some(3..13));
+assert!(ids.release(2));
+assert!(!ids.release(2)); // already free
+assert_eq!(ids.allocate(), Some(2));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdAllocator<T> {
+    ids: Range<T>,
+    free: RangeSet<T>,
+}
+
+impl<T> IdAllocator<T>
+where
+    T: Sized + Ord + Copy + Default + From<u8> + Add<Output = T> + Sub<Output = T>,
+{
+    /// An allocator with every ID in `ids` free.
+    pub fn new(ids: Range<T>) -> Self {
+        let mut free = RangeSet::new();
+        free.insert(ids.clone());
+        Self { ids, free }
+    }
+
+    /// The lowest free ID.
+    pub fn allocate(&mut self) -> Option<T> {
+        self.allocate_block(T::from(1)).map(|r| r.start)
+    }
+
+    /// The lowest block of `n` consecutive free IDs. Returns `None` if `n` is zero.
+    pub fn allocate_block(&mut self, n: T) -> Option<Range<T>> {
+        if n <= T::default() {
+            return None;
+        }
+        let run = self.free.iter().find(|r| n <= r.end - r.start)?;
+        let block = run.start..run.start + n;
+        self.free.remove(&block);
+        Some(block)
+    }
+
+    /// Free `id`. Returns `false` (and does nothing) if it's already free or out of range.
+    pub fn release(&mut self, id: T) -> bool {
+        // Below `ids.end`, so `id + 1` doesn't overflow
+        self.ids.contains(&id) && self.release_block(id..id + T::from(1))
+    }
+
+    /// Free all of `block`. Returns `false` (and does nothing)
+    /// if it's empty, or any of it is already free or out of range.
+    pub fn release_block(&mut self, block: Range<T>) -> bool {
+        if block.start >= block.end {
+            return false;
+        }
+        let clear = self.free.storage().overlapping(&block).is_empty();
+        if !clear || block.start < self.ids.start || self.ids.end < block.end {
+            return false;
+        }
+        self.free.insert(block);
+        true
+    }
+
+    /// Whether `id` is currently allocated.
+    pub fn is_allocated(&self, id: T) -> bool {
+        self.ids.contains(&id) && !self.free.contains(id)
+    }
+
+    pub fn fragmentation(&self) -> Fragmentation<T> {
+        let lengths = self.free.iter().map(|r| r.end - r.start);
+        Fragmentation {
+            free_ids: lengths.clone().fold(T::default(), |a, b| a + b),
+            free_runs: self.free.len(),
+            largest_run: lengths.max().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> Allocator<u32> {
+        Allocator {
+            free: [10..16, 20..30, 70..100].into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn best_fit() {
+        assert_eq!(pool().allocate(5, 1, Strategy::BestFit), Some(10..15))
+    }
+
+    #[test]
+    fn worst_fit() {
+        assert_eq!(pool().allocate(5, 1, Strategy::WorstFit), Some(70..75))
+    }
+
+    #[test]
+    fn alignment_padding_counts() {
+        // 10..16 has no room left after aligning to 16, nor does 20..30
+        assert_eq!(pool().allocate(6, 16, Strategy::FirstFit), Some(80..86))
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut heap = Allocator::new(0..10u8);
+        assert_eq!(heap.allocate(11, 1, Strategy::FirstFit), None);
+        assert_eq!(heap.allocate(0, 1, Strategy::FirstFit), None);
+        assert_eq!(heap.allocate(10, 1, Strategy::FirstFit), Some(0..10));
+        assert!(heap.free_ranges().is_empty());
+    }
+
+    #[test]
+    fn buddy_merges_fully() {
+        let mut heap = BuddyRanges::new(4, 0);
+        let blocks: Vec<_> = (0..16).map(|_| heap.allocate(1).unwrap()).collect();
+        assert!(heap.allocate(1).is_none());
+        for b in blocks.into_iter().rev() {
+            assert!(heap.free(b));
+        }
+        assert_eq!(heap, BuddyRanges::new(4, 0));
+        assert_eq!(heap.free_ranges().containing(7), Some(0..16));
+    }
+
+    #[test]
+    fn buddy_rejects_bad_free() {
+        let mut heap = BuddyRanges::new(4, 0);
+        let a = heap.allocate(4).unwrap();
+        assert!(!heap.free(a.start..a.end - 1));
+        let b = heap.allocate(4).unwrap();
+        assert!(!heap.free(b.start..a.start));
+        assert!(heap.free(b));
+        assert!(heap.free(a.clone()));
+        assert!(!heap.free(a));
+    }
+
+    #[test]
+    fn buddy_too_big() {
+        let mut heap = BuddyRanges::new(4, 0);
+        assert_eq!(heap.allocate(17), None);
+        assert_eq!(heap.allocate(u64::MAX), None);
+        assert_eq!(heap.allocate(16), Some(0..16));
+    }
+
+    #[test]
+    fn id_blocks_fill_gaps_first_fit() {
+        let mut ids = IdAllocator::new(0..20u16);
+        let a = ids.allocate_block(5).unwrap();
+        ids.allocate_block(5);
+        assert!(ids.release_block(a));
+        assert_eq!(ids.allocate_block(6), Some(10..16));
+        assert_eq!(ids.allocate_block(5), Some(0..5));
+        assert_eq!(ids.allocate_block(5), None);
+        assert_eq!(ids.allocate_block(0), None);
+    }
+
+    #[test]
+    fn id_release_rejects_partial_double_free() {
+        let mut ids = IdAllocator::new(0..10u8);
+        ids.allocate_block(4);
+        assert!(ids.release(1));
+        assert!(!ids.release_block(0..3));
+        assert!(!ids.release(10));
+        assert!(ids.is_allocated(0) && !ids.is_allocated(1));
+    }
+
+    #[test]
+    fn id_release_rejects_empty_block() {
+        let mut ids = IdAllocator::new(0u32..10);
+        ids.allocate_block(10);
+        let (p, q) = (8, 3);
+        assert!(!ids.release_block(5..5));
+        assert!(!ids.release_block(p..q));
+        assert_eq!(ids.fragmentation().free_runs, 0)
+    }
+
+    #[test]
+    fn id_release_at_the_top() {
+        let mut ids = IdAllocator::new(0..255u8);
+        assert!(!ids.release(255));
+        ids.allocate_block(255);
+        assert!(ids.release(254));
+        assert!(!ids.is_allocated(254));
+    }
+
+    #[test]
+    fn id_fragmentation() {
+        let mut ids = IdAllocator::new(0..10u32);
+        ids.allocate_block(10);
+        ids.release(2);
+        ids.release_block(5..8);
+        assert_eq!(
+            ids.fragmentation(),
+            Fragmentation {
+                free_ids: 4,
+                free_runs: 2,
+                largest_run: 3
+            }
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Turning any range expression (`..`, `3..`, `..=7`, or a pair of `Bound`s)
+//! into a concrete `Range`.
+
+use core::fmt;
+use core::ops::{Bound, Range, RangeBounds};
+
+/// Why [`resolve_strict`] rejected some bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveError {
+    /// The start is `Excluded(usize::MAX)`, so nothing can follow it.
+    StartOverflow,
+    /// The end is `Included(usize::MAX)`, so can't be expressed as an exclusive end.
+    EndOverflow,
+    /// The start is after the end.
+    Inverted { start: usize, end: usize },
+    /// The end is after the length.
+    OutOfBounds { end: usize, len: usize },
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::StartOverflow => write!(f, "range start overflows usize"),
+            ResolveError::EndOverflow => write!(f, "range end overflows usize"),
+            ResolveError::Inverted { start, end } => {
+                write!(f, "range starts at {start} but ends at {end}")
+            }
+            ResolveError::OutOfBounds { end, len } => {
+                write!(f, "range end {end} is out of bounds for length {len}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/** The indices of a sequence of length `len` within `bounds`, clamped to `0..len`.
+
+Bounds past the end, including those that couldn't be expressed as a `Range`
+(`Excluded(usize::MAX)` as a start, `Included(usize::MAX)` as an end), are taken as `len`.
+The only error is a start after the end, as with `5..2`.
+
+```
+# use range_split::bounds::{resolve, ResolveError};
+assert_eq!(resolve(.., 10), Ok(0..10));
+assert_eq!(resolve(3..=4, 10), Ok(3..5));
+assert_eq!(resolve(8..20, 10), Ok(8..10));
+assert_eq!(resolve(20.., 10), Ok(10..10));
+assert_eq!(resolve(..=usize::MAX, 10), Ok(0..10));
+let (lo, hi) = (5, 2);
+assert_eq!(resolve(lo..hi, 10), Err(ResolveError::Inverted { start: 5, end: 2 }));
+```
+**/
+pub fn resolve(bounds: impl RangeBounds<usize>, len: usize) -> Result<Range<usize>, ResolveError> {
+    let start = match bounds.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    // An open end is past any start, even one past `len`
+    let end = match bounds.end_bound() {
+        Bound::Included(&e) => e.saturating_add(1),
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => usize::MAX,
+    };
+    if start > end {
+        return Err(ResolveError::Inverted { start, end });
+    }
+    Ok(start.min(len)..end.min(len))
+}
+
+/** The indices of a sequence of length `len` within `bounds`, which must be in `0..=len`,
+as slice indexing requires.
+
+```
+# use range_split::bounds::{resolve_strict, ResolveError};
+use std::ops::Bound::{Excluded, Unbounded};
+assert_eq!(resolve_strict((Excluded(0), Unbounded), 10), Ok(1..10));
+assert_eq!(resolve_strict(8..20, 10), Err(ResolveError::OutOfBounds { end: 20, len: 10 }));
+assert_eq!(resolve_strict(..=usize::MAX, 10), Err(ResolveError::EndOverflow));
+```
+**/
+pub fn resolve_strict(
+    bounds: impl RangeBounds<usize>,
+    len: usize,
+) -> Result<Range<usize>, ResolveError> {
+    let start = match bounds.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s.checked_add(1).ok_or(ResolveError::StartOverflow)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match bounds.end_bound() {
+        Bound::Included(&e) => e.checked_add(1).ok_or(ResolveError::EndOverflow)?,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    if start > end {
+        return Err(ResolveError::Inverted { start, end });
+    }
+    if end > len {
+        return Err(ResolveError::OutOfBounds { end, len });
+    }
+    Ok(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ops::Bound::{Excluded, Included, Unbounded};
+
+    #[test]
+    fn excluded_edges() {
+        assert_eq!(resolve((Excluded(0), Excluded(3)), 10), Ok(1..3));
+        assert_eq!(resolve((Excluded(usize::MAX), Unbounded), 10), Ok(10..10));
+        assert_eq!(
+            resolve_strict((Excluded(usize::MAX), Unbounded), 10),
+            Err(ResolveError::StartOverflow)
+        );
+        // An excluded start equal to an included end is empty, not inverted
+        assert_eq!(resolve_strict((Excluded(4), Included(4)), 10), Ok(5..5))
+    }
+
+    #[test]
+    fn strict_matches_slicing() {
+        let v = [0u8; 6];
+        for (start, end) in [(0, 6), (2, 4), (6, 6), (3, 3)] {
+            let r = resolve_strict(start..end, v.len()).unwrap();
+            assert_eq!(v[r].len(), end - start);
+        }
+        assert_eq!(
+            resolve_strict(7..7, 6),
+            Err(ResolveError::OutOfBounds { end: 7, len: 6 })
+        );
+        assert_eq!(resolve(7..7, 6), Ok(6..6))
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Histogram buckets over ranges of values.
+
+use core::ops::Range;
+
+/// Counts of values by bucket, from [`Buckets::tally`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tally {
+    /// The count for each bucket, by index.
+    pub counts: Vec<usize>,
+    /// Values below the first bucket.
+    pub underflow: usize,
+    /// Values at or above the end of the last bucket.
+    pub overflow: usize,
+    /// Values falling in gaps between buckets.
+    pub unmatched: usize,
+}
+
+/** Sorted, disjoint buckets for classifying values.
+
+Usage:
+```
+# use range_split::buckets::Buckets;
+let latency = Buckets::from_boundaries(vec![0, 10, 50, 100]).unwrap();
+assert_eq!(latency.bucket_of(&25), Some(1));
+assert_eq!(latency.bucket_of(&100), None);
+
+let tally = latency.tally([3, 7, 12, 99, 250, 400]);
+assert_eq!(tally.counts, vec![2, 1, 1]);
+assert_eq!(tally.overflow, 2);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buckets<T> {
+    buckets: Vec<Range<T>>,
+}
+
+impl<T> Buckets<T>
+where
+    T: Sized + Ord + Clone,
+{
+    /// Contiguous buckets between consecutive boundaries.
+    /// Returns `None` unless the boundaries are strictly increasing.
+    pub fn from_boundaries(boundaries: Vec<T>) -> Option<Self> {
+        if boundaries.windows(2).any(|w| w[0] >= w[1]) {
+            return None;
+        }
+        let buckets = boundaries
+            .windows(2)
+            .map(|w| w[0].clone()..w[1].clone())
+            .collect();
+        Some(Self { buckets })
+    }
+
+    /// Buckets given explicitly, which may leave gaps.
+    /// Returns `None` unless they're non-empty, sorted and disjoint.
+    pub fn from_ranges(ranges: Vec<Range<T>>) -> Option<Self> {
+        let ordered = ranges.iter().all(|r| r.start < r.end)
+            && ranges.windows(2).all(|w| w[0].end <= w[1].start);
+        ordered.then_some(Self { buckets: ranges })
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// The range of bucket `index`.
+    pub fn get(&self, index: usize) -> Option<&Range<T>> {
+        self.buckets.get(index)
+    }
+
+    /// The bucket containing `value`, if any.
+    pub fn bucket_of(&self, value: &T) -> Option<usize> {
+        let i = self.buckets.partition_point(|r| r.end <= *value);
+        self.buckets
+            .get(i)
+            .is_some_and(|r| r.start <= *value)
+            .then_some(i)
+    }
+
+    /// Count `values` by bucket.
+    pub fn tally(&self, values: impl IntoIterator<Item = T>) -> Tally {
+        let mut tally = Tally {
+            counts: vec![0; self.buckets.len()],
+            underflow: 0,
+            overflow: 0,
+            unmatched: 0,
+        };
+        for v in values {
+            match self.bucket_of(&v) {
+                Some(i) => tally.counts[i] += 1,
+                None if self.buckets.first().is_none_or(|r| v < r.start) => tally.underflow += 1,
+                None if self.buckets.last().is_some_and(|r| r.end <= v) => tally.overflow += 1,
+                None => tally.unmatched += 1,
+            }
+        }
+        tally
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unsorted() {
+        assert_eq!(Buckets::from_boundaries(vec![0, 5, 5]), None);
+        assert_eq!(Buckets::from_ranges(vec![5..10, 0..5]), None);
+        assert!(Buckets::from_ranges(vec![0..5, 5..10]).is_some());
+    }
+
+    #[test]
+    fn gaps() {
+        let b = Buckets::from_ranges(vec![0..5, 10..20]).unwrap();
+        assert_eq!(b.bucket_of(&7), None);
+        assert_eq!(b.bucket_of(&10), Some(1));
+        let t = b.tally([-1, 4, 7, 8, 19, 20]);
+        assert_eq!(
+            t,
+            Tally {
+                counts: vec![1, 1],
+                underflow: 1,
+                overflow: 1,
+                unmatched: 2
+            }
+        );
+    }
+
+    #[test]
+    fn no_buckets() {
+        let b = Buckets::from_boundaries(vec![0]).unwrap();
+        assert!(b.is_empty());
+        assert_eq!(b.tally([1, 2]).underflow, 2);
+    }
+}
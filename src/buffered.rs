@@ -0,0 +1,195 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Media buffering state, modeled on the MediaSource `TimeRanges` interface.
+
+use core::ops::{Range, Sub};
+
+use crate::storage::{RangeSet, RunStorage};
+use crate::view::RangeSetView;
+
+/** The buffered regions of a media timeline, kept as a [`RangeSet`].
+
+```
+# use range_split::buffered::Buffered;
+let mut buf = Buffered::new();
+buf.add(0..10);
+buf.add(20..30);
+buf.add(10..15);
+assert_eq!(buf.len(), 2);
+assert_eq!((buf.start(0), buf.end(0)), (Some(0), Some(15)));
+assert_eq!(buf.next_gap(5), Some(15));
+assert_eq!(buf.buffered_ahead(5), 10);
+assert_eq!(buf.buffered_ahead(17), 0);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buffered<T> {
+    ranges: RangeSet<T>,
+}
+
+impl<T> Default for Buffered<T> {
+    fn default() -> Self {
+        Self {
+            ranges: RangeSet::default(),
+        }
+    }
+}
+
+impl<T> Buffered<T>
+where
+    T: Sized + Ord + Copy,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `range` as buffered, merging it with any ranges it overlaps or touches.
+    pub fn add(&mut self, range: impl Into<Range<T>>) {
+        self.ranges.insert(range);
+    }
+
+    /// Mark `range` as no longer buffered (e.g. after eviction).
+    pub fn remove(&mut self, range: &Range<T>) {
+        self.ranges.remove(range);
+    }
+
+    /// Keep only what's buffered within `ranges`.
+    pub fn intersect_assign<S: RunStorage<T>>(&mut self, ranges: &RangeSet<T, S>) {
+        self.ranges.intersect_assign(ranges);
+    }
+
+    /// Mark everything in `ranges` as no longer buffered.
+    pub fn subtract_assign<S: RunStorage<T>>(&mut self, ranges: &RangeSet<T, S>) {
+        self.ranges.subtract_assign(ranges);
+    }
+
+    /// Number of disjoint buffered ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Start of the `i`th buffered range.
+    pub fn start(&self, i: usize) -> Option<T> {
+        self.as_slice().get(i).map(|r| r.start)
+    }
+
+    /// End of the `i`th buffered range.
+    pub fn end(&self, i: usize) -> Option<T> {
+        self.as_slice().get(i).map(|r| r.end)
+    }
+
+    /// Whether the position `t` is buffered.
+    pub fn contains(&self, t: T) -> bool {
+        self.ranges.contains(t)
+    }
+
+    /// Where playback from `t` would run out of data, or `None` if `t` isn't buffered.
+    pub fn next_gap(&self, t: T) -> Option<T> {
+        self.ranges.containing(t).map(|r| r.end)
+    }
+
+    /// How much is buffered contiguously from `t` onward.
+    pub fn buffered_ahead<D>(&self, t: T) -> D
+    where
+        T: Sub<Output = D>,
+        D: Default,
+    {
+        self.ranges
+            .containing(t)
+            .map(|r| r.end - t)
+            .unwrap_or_default()
+    }
+
+    /// The buffered ranges, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.as_slice().iter()
+    }
+
+    /// The buffered ranges, in order, as a slice.
+    pub fn as_slice(&self) -> &[Range<T>] {
+        self.ranges.storage()
+    }
+
+    /// The buffered ranges as a set.
+    pub fn ranges(&self) -> &RangeSet<T> {
+        &self.ranges
+    }
+
+    /// The buffered ranges within `window`, without copying them.
+    pub fn view(&self, window: Range<T>) -> RangeSetView<'_, T> {
+        self.ranges.view(window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffered(ranges: &[Range<u32>]) -> Buffered<u32> {
+        let mut buf = Buffered::new();
+        for r in ranges {
+            buf.add(r.clone());
+        }
+        buf
+    }
+
+    #[test]
+    fn add_coalesces() {
+        let buf = buffered(&[10..20, 30..40, 0..5, 15..32]);
+        assert_eq!(buf.iter().cloned().collect::<Vec<_>>(), vec![0..5, 10..40]);
+    }
+
+    #[test]
+    fn add_adjacent() {
+        let buf = buffered(&[0..5, 5..10]);
+        assert_eq!(buf.len(), 1);
+        assert_eq!(buf.end(0), Some(10));
+    }
+
+    #[test]
+    fn remove_splits() {
+        let mut buf = buffered(&[0..10, 20..30]);
+        buf.remove(&(5..25));
+        assert_eq!(buf.iter().cloned().collect::<Vec<_>>(), vec![0..5, 25..30]);
+    }
+
+    #[test]
+    fn contains_endpoints() {
+        let buf = buffered(&[10..20, 40..50]);
+        assert!(!buf.contains(9));
+        assert!(buf.contains(10));
+        assert!(!buf.contains(20));
+    }
+
+    #[test]
+    fn unbuffered_position() {
+        let buf = buffered(&[10..20, 40..50]);
+        assert_eq!(buf.next_gap(25), None);
+        assert_eq!(buf.buffered_ahead(5), 0);
+        assert_eq!(buf.start(2), None);
+    }
+
+    #[test]
+    fn view_window() {
+        let buf = buffered(&[10..20, 40..50]);
+        assert_eq!(buf.view(15..45).to_vec(), vec![15..20, 40..45]);
+    }
+
+    #[test]
+    fn assign_ops() {
+        let mut buf = buffered(&[0..10, 20..30, 40..50]);
+        buf.intersect_assign(&[5..8, 9..25, 45..60].into_iter().collect::<RangeSet<_>>());
+        assert_eq!(buf.as_slice(), &[5..8, 9..10, 20..25, 45..50]);
+        buf.subtract_assign(&[6..7, 9..22, 24..46].into_iter().collect::<RangeSet<_>>());
+        assert_eq!(buf.as_slice(), &[5..6, 7..8, 22..24, 46..50]);
+
+        let other = buffered(&[0..50, 50..100]);
+        buf.subtract_assign(other.ranges());
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,492 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Ranges of `char` that skip the surrogate gap `U+D800..U+E000`.
+
+use core::fmt::{self, Write};
+use core::ops::{Range, RangeInclusive};
+
+use crate::Split;
+
+const GAP_START: u32 = 0xD800;
+const GAP_LEN: u32 = 0x800;
+
+/// Position of `c` among all Unicode scalar values.
+fn index(c: char) -> u32 {
+    let c = c as u32;
+    if c < GAP_START {
+        c
+    } else {
+        c - GAP_LEN
+    }
+}
+
+fn from_index(i: u32) -> char {
+    let c = if i < GAP_START { i } else { i + GAP_LEN };
+    char::from_u32(c).expect("index is a Unicode scalar value")
+}
+
+/** A half-open range of Unicode scalar values.
+
+Unlike `Range<char>`, length and iteration don't count the surrogate code points,
+and `end` can lie past `char::MAX`, so every set of chars is representable.
+
+```
+# use range_split::{chars::CharRange, Split};
+let r = CharRange::inclusive('\u{D7FE}'..='\u{E001}');
+assert_eq!(r.len(), 4);
+assert_eq!(r.iter().collect::<String>(), "\u{D7FE}\u{D7FF}\u{E000}\u{E001}");
+
+let (below, inter, above) = r.split(&CharRange::new('\u{D7FF}'..'\u{E001}'));
+assert_eq!(below.unwrap().len(), 1);
+assert_eq!(inter.unwrap().len(), 2);
+assert_eq!(above.unwrap().first(), Some('\u{E001}'));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharRange {
+    // Half-open range of scalar value indices
+    start: u32,
+    end: u32,
+}
+
+impl CharRange {
+    /// Every Unicode scalar value.
+    pub const ALL: Self = Self {
+        start: 0,
+        end: char::MAX as u32 + 1 - GAP_LEN,
+    };
+
+    pub fn new(range: Range<char>) -> Self {
+        Self {
+            start: index(range.start),
+            end: index(range.end),
+        }
+    }
+
+    pub fn inclusive(range: RangeInclusive<char>) -> Self {
+        Self {
+            start: index(*range.start()),
+            end: index(*range.end()) + 1,
+        }
+    }
+
+    pub fn first(&self) -> Option<char> {
+        (!self.is_empty()).then(|| from_index(self.start))
+    }
+
+    pub fn last(&self) -> Option<char> {
+        (!self.is_empty()).then(|| from_index(self.end - 1))
+    }
+
+    /// Number of scalar values in the range.
+    pub fn len(&self) -> u32 {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        (self.start..self.end).contains(&index(c))
+    }
+
+    /// The scalar values in the range, in order.
+    pub fn iter(&self) -> impl Iterator<Item = char> {
+        (self.start..self.end).map(from_index)
+    }
+
+    /** Split into the pieces whose chars encode to 1, 2, 3, and 4 bytes of UTF-8,
+    yielding `(encoded length, piece)` for each non-empty piece in order.
+
+    ```
+    # use range_split::chars::CharRange;
+    let pieces: Vec<_> = CharRange::inclusive('~'..='\u{800}').split_utf8_len().collect();
+    assert_eq!(pieces, vec![
+        (1, CharRange::inclusive('~'..='\u{7F}')),
+        (2, CharRange::inclusive('\u{80}'..='\u{7FF}')),
+        (3, CharRange::inclusive('\u{800}'..='\u{800}')),
+    ]);
+    ```
+    **/
+    pub fn split_utf8_len(&self) -> impl Iterator<Item = (usize, CharRange)> + '_ {
+        const BY_LEN: [(usize, Range<u32>); 4] = [
+            (1, 0..0x80),
+            (2, 0x80..0x800),
+            (3, 0x800..0x10000 - GAP_LEN),
+            (4, 0x10000 - GAP_LEN..char::MAX as u32 + 1 - GAP_LEN),
+        ];
+        BY_LEN.into_iter().filter_map(|(len, r)| {
+            let inter = (self.start..self.end)
+                .split(&r)
+                .1
+                .filter(|r| r.start < r.end)?;
+            Some((
+                len,
+                Self {
+                    start: inter.start,
+                    end: inter.end,
+                },
+            ))
+        })
+    }
+
+    /// As a `RangeInclusive`, or `None` if empty.
+    pub fn to_inclusive(&self) -> Option<RangeInclusive<char>> {
+        Some(self.first()?..=self.last()?)
+    }
+}
+
+impl From<Range<char>> for CharRange {
+    fn from(range: Range<char>) -> Self {
+        Self::new(range)
+    }
+}
+
+impl From<RangeInclusive<char>> for CharRange {
+    fn from(range: RangeInclusive<char>) -> Self {
+        Self::inclusive(range)
+    }
+}
+
+impl Split for CharRange {
+    fn split(&self, other: &Self) -> (Option<Self>, Option<Self>, Option<Self>) {
+        let wrap = |r: Option<Range<u32>>| {
+            r.map(|r| Self {
+                start: r.start,
+                end: r.end,
+            })
+        };
+        let (below, inter, above) = (self.start..self.end).split(&(other.start..other.end));
+        (wrap(below), wrap(inter), wrap(above))
+    }
+}
+
+/** Render a set of chars as a regex character class, such as `[_a-z\u{1F600}-\u{1F64F}]`.
+
+`ranges` may be unsorted and overlapping. Printable ASCII is written as-is,
+escaped if it has a meaning inside a class; everything else is written as `\u{…}`.
+The empty set is written as a negated class of every char.
+
+```
+# use range_split::chars::{to_char_class, CharRange};
+let ranges = [CharRange::inclusive('a'..='z'), CharRange::inclusive('-'..='-')];
+assert_eq!(to_char_class(&ranges), r"[\-a-z]");
+```
+**/
+pub fn to_char_class(ranges: &[CharRange]) -> String {
+    let ranges = normalize(ranges.iter().cloned());
+    if ranges.is_empty() {
+        return r"[^\u{0}-\u{10FFFF}]".into();
+    }
+    let mut out = String::from("[");
+    for r in &ranges {
+        let (first, last) = (from_index(r.start), from_index(r.end - 1));
+        push_class_char(&mut out, first);
+        if last != first {
+            out.push('-');
+            push_class_char(&mut out, last);
+        }
+    }
+    out.push(']');
+    out
+}
+
+fn push_class_char(out: &mut String, c: char) {
+    match c {
+        '\\' | ']' | '[' | '^' | '-' | '&' | '~' => {
+            out.push('\\');
+            out.push(c);
+        }
+        ' '..='~' => out.push(c),
+        _ => write!(out, r"\u{{{:X}}}", c as u32).expect("writing to a String"),
+    }
+}
+
+/// Why [`parse_char_class`] rejected its input, with the byte offset of the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCharClassError {
+    /// The class isn't enclosed in `[` and `]`.
+    Unbracketed,
+    /// An escape sequence is malformed or unsupported (e.g. `\d`).
+    BadEscape(usize),
+    /// A range's start is after its end.
+    InvertedRange(usize),
+}
+
+impl fmt::Display for ParseCharClassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unbracketed => write!(f, "character class must be enclosed in brackets"),
+            Self::BadEscape(at) => write!(f, "bad escape sequence at byte {at}"),
+            Self::InvertedRange(at) => write!(f, "inverted range at byte {at}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseCharClassError {}
+
+/** Parse a character class back into sorted, disjoint, coalesced ranges.
+
+Accepts the syntax produced by [`to_char_class`], plus negation with a leading `^`,
+the escapes `\n`, `\r`, `\t`, `\xHH` and `\x{…}`, and any escaped punctuation.
+Nested classes, class escapes like `\d`, and set operations are not supported.
+
+```
+# use range_split::chars::{parse_char_class, CharRange};
+let ranges = parse_char_class(r"[a-c\u{1F600}]").unwrap();
+assert_eq!(ranges, vec![CharRange::inclusive('a'..='c'), CharRange::inclusive('\u{1F600}'..='\u{1F600}')]);
+```
+**/
+pub fn parse_char_class(class: &str) -> Result<Vec<CharRange>, ParseCharClassError> {
+    let body = class
+        .strip_prefix('[')
+        .and_then(|c| c.strip_suffix(']'))
+        .ok_or(ParseCharClassError::Unbracketed)?;
+    let (negated, body, offset) = match body.strip_prefix('^') {
+        Some(rest) => (true, rest, 2),
+        None => (false, body, 1),
+    };
+
+    let mut chars = body.char_indices().peekable();
+    let mut ranges = Vec::new();
+    while let Some(first) = parse_class_char(&mut chars, offset)? {
+        let at = first.0;
+        let mut last = first;
+        if let Some(dash) = chars.next_if(|&(_, c)| c == '-') {
+            match parse_class_char(&mut chars, offset)? {
+                Some(end) => last = end,
+                // A trailing `-` is literal
+                None => ranges.push(CharRange::inclusive(dash.1..=dash.1)),
+            }
+        }
+        if last.1 < first.1 {
+            return Err(ParseCharClassError::InvertedRange(at + offset));
+        }
+        ranges.push(CharRange::inclusive(first.1..=last.1));
+    }
+
+    let ranges = normalize(ranges);
+    Ok(if negated { complement(&ranges) } else { ranges })
+}
+
+/// Read one possibly-escaped char, with its byte offset.
+fn parse_class_char(
+    chars: &mut core::iter::Peekable<core::str::CharIndices<'_>>,
+    offset: usize,
+) -> Result<Option<(usize, char)>, ParseCharClassError> {
+    let Some((at, c)) = chars.next() else {
+        return Ok(None);
+    };
+    let bad = ParseCharClassError::BadEscape(at + offset);
+    match c {
+        '\\' => {}
+        '[' | ']' => return Err(bad),
+        c => return Ok(Some((at, c))),
+    }
+    let c = match chars.next().ok_or(bad.clone())?.1 {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        e @ ('u' | 'x') => {
+            let braced = chars.next_if(|&(_, c)| c == '{').is_some();
+            let mut hex = String::new();
+            while let Some((_, h)) = chars.next_if(|&(_, c)| c.is_ascii_hexdigit()) {
+                hex.push(h);
+                if !braced && hex.len() == 2 {
+                    break;
+                }
+            }
+            let closed = !braced || chars.next_if(|&(_, c)| c == '}').is_some();
+            let valid_len = if braced {
+                !hex.is_empty()
+            } else {
+                e == 'x' && hex.len() == 2
+            };
+            if !closed || !valid_len {
+                return Err(bad);
+            }
+            u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or(bad)?
+        }
+        c if c.is_ascii_punctuation() => c,
+        _ => return Err(bad),
+    };
+    Ok(Some((at, c)))
+}
+
+/// Sort and coalesce `ranges`, dropping empty ones.
+fn normalize(ranges: impl IntoIterator<Item = CharRange>) -> Vec<CharRange> {
+    let mut ranges: Vec<CharRange> = ranges.into_iter().filter(|r| !r.is_empty()).collect();
+    ranges.sort_unstable_by_key(|r| r.start);
+    let mut out: Vec<CharRange> = Vec::with_capacity(ranges.len());
+    for r in ranges {
+        match out.last_mut() {
+            Some(prev) if r.start <= prev.end => prev.end = prev.end.max(r.end),
+            _ => out.push(r),
+        }
+    }
+    out
+}
+
+/// The chars not in `ranges`, which must be normalized.
+fn complement(ranges: &[CharRange]) -> Vec<CharRange> {
+    let mut out = Vec::new();
+    let mut start = CharRange::ALL.start;
+    for r in ranges {
+        if start < r.start {
+            out.push(CharRange {
+                start,
+                end: r.start,
+            });
+        }
+        start = r.end;
+    }
+    if start < CharRange::ALL.end {
+        out.push(CharRange {
+            start,
+            end: CharRange::ALL.end,
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_len() {
+        assert_eq!(CharRange::inclusive('a'..='z').len(), 26)
+    }
+
+    #[test]
+    fn all_scalar_values() {
+        assert_eq!(CharRange::ALL.len(), 0x10F800);
+        assert_eq!(CharRange::ALL.last(), Some(char::MAX));
+        assert_eq!(CharRange::ALL, CharRange::inclusive('\0'..=char::MAX));
+    }
+
+    #[test]
+    fn across_gap() {
+        let r = CharRange::new('\u{D7FF}'..'\u{E000}');
+        assert_eq!(r.len(), 1);
+        assert!(r.contains('\u{D7FF}'));
+        assert!(!r.contains('\u{E000}'));
+    }
+
+    #[test]
+    fn empty() {
+        let r = CharRange::new('x'..'x');
+        assert!(r.is_empty());
+        assert_eq!(r.first(), None);
+        assert_eq!(r.to_inclusive(), None);
+    }
+
+    #[test]
+    fn split_disjoint() {
+        let a = CharRange::new('a'..'f');
+        let b = CharRange::new('x'..'z');
+        assert_eq!(a.split(&b), (Some(a.clone()), None, None))
+    }
+
+    #[test]
+    fn utf8_len_each_class() {
+        let lens: Vec<_> = CharRange::ALL
+            .split_utf8_len()
+            .map(|(len, r)| {
+                (
+                    len,
+                    r.first().unwrap().len_utf8(),
+                    r.last().unwrap().len_utf8(),
+                )
+            })
+            .collect();
+        assert_eq!(lens, vec![(1, 1, 1), (2, 2, 2), (3, 3, 3), (4, 4, 4)]);
+    }
+
+    #[test]
+    fn utf8_len_of_empty_ranges() {
+        let (b, z, a) = ('b', 'z', 'a');
+        assert_eq!(CharRange::new(b..b).split_utf8_len().count(), 0);
+        assert_eq!(CharRange::new(z..a).split_utf8_len().count(), 0);
+        assert_eq!(CharRange::new(a..z).split_utf8_len().count(), 1);
+    }
+
+    #[test]
+    fn utf8_len_across_gap() {
+        let pieces: Vec<_> = CharRange::inclusive('\u{D7FF}'..='\u{10000}')
+            .split_utf8_len()
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                (3, CharRange::inclusive('\u{D7FF}'..='\u{FFFF}')),
+                (4, CharRange::inclusive('\u{10000}'..='\u{10000}')),
+            ]
+        )
+    }
+
+    #[test]
+    fn class_coalesces() {
+        let ranges = [
+            CharRange::inclusive('0'..='9'),
+            CharRange::inclusive('a'..='f'),
+            CharRange::inclusive('d'..='z'),
+            CharRange::inclusive('_'..='_'),
+        ];
+        assert_eq!(to_char_class(&ranges), "[0-9_a-z]")
+    }
+
+    #[test]
+    fn class_escapes() {
+        let ranges = [
+            CharRange::inclusive('\n'..='\n'),
+            CharRange::inclusive('['..=']'),
+            CharRange::inclusive('\u{1F600}'..='\u{1F64F}'),
+        ];
+        let class = to_char_class(&ranges);
+        assert_eq!(class, r"[\u{A}\[-\]\u{1F600}-\u{1F64F}]");
+        assert_eq!(parse_char_class(&class), Ok(normalize(ranges)));
+    }
+
+    #[test]
+    fn parse_trailing_dash() {
+        let ranges = parse_char_class("[a-]").unwrap();
+        assert_eq!(to_char_class(&ranges), r"[\-a]")
+    }
+
+    #[test]
+    fn empty_class_round_trip() {
+        assert_eq!(parse_char_class(&to_char_class(&[])), Ok(vec![]))
+    }
+
+    #[test]
+    fn parse_negated() {
+        let ranges = parse_char_class(r"[^\x00-\x{60}{-\u{10FFFF}]").unwrap();
+        assert_eq!(ranges, vec![CharRange::inclusive('a'..='z')])
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_char_class("a-z"),
+            Err(ParseCharClassError::Unbracketed)
+        );
+        assert_eq!(
+            parse_char_class(r"[\d]"),
+            Err(ParseCharClassError::BadEscape(1))
+        );
+        assert_eq!(
+            parse_char_class("[z-a]"),
+            Err(ParseCharClassError::InvertedRange(1))
+        );
+        assert_eq!(
+            parse_char_class(r"[\u{D800}]"),
+            Err(ParseCharClassError::BadEscape(1))
+        );
+    }
+}
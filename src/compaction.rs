@@ -0,0 +1,192 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Choosing the input files of a compaction in a log-structured merge tree.
+//!
+//! Each file covers an inclusive range of keys, as its smallest and largest keys.
+//! The files of level 0 may overlap and come in any order. The files of every other level
+//! must be sorted and disjoint, and are searched by bisection.
+
+use core::ops::RangeInclusive;
+
+/// The files to compact together, from [`plan_compaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compaction<K> {
+    /// The level being compacted.
+    pub level: usize,
+    /// Indices of the files of `level` to compact, in order.
+    pub inputs: Vec<usize>,
+    /// Indices of the files of `level + 1` they overlap, in order.
+    pub next_inputs: Vec<usize>,
+    /// The keys covered by all of the inputs.
+    pub keys: RangeInclusive<K>,
+}
+
+/// Indices of the files in `files` that overlap `keys`, in order.
+fn overlapping<K: Ord>(
+    files: &[RangeInclusive<K>],
+    keys: &RangeInclusive<K>,
+    sorted: bool,
+) -> Vec<usize> {
+    let overlaps = |f: &RangeInclusive<K>| f.start() <= keys.end() && keys.start() <= f.end();
+    if !sorted {
+        return (0..files.len()).filter(|&i| overlaps(&files[i])).collect();
+    }
+    let first = files.partition_point(|f| f.end() < keys.start());
+    (first..files.len())
+        .take_while(|&i| files[i].start() <= keys.end())
+        .collect()
+}
+
+/// The smallest range covering `keys` and all of the given files.
+fn cover<K: Ord + Clone>(
+    keys: &RangeInclusive<K>,
+    files: &[RangeInclusive<K>],
+    chosen: &[usize],
+) -> RangeInclusive<K> {
+    let mut start = keys.start();
+    let mut end = keys.end();
+    for f in chosen.iter().map(|&i| &files[i]) {
+        start = start.min(f.start());
+        end = end.max(f.end());
+    }
+    start.clone()..=end.clone()
+}
+
+/// The files of a level overlapping `keys`, and the keys they cover along with `keys`.
+/// On level 0, the files overlapping those are taken in too, until there are no more:
+/// overlapping files there may hold versions of the same key, so must be compacted together.
+fn level_inputs<K: Ord + Clone>(
+    files: &[RangeInclusive<K>],
+    keys: &RangeInclusive<K>,
+    sorted: bool,
+) -> (Vec<usize>, RangeInclusive<K>) {
+    let mut keys = keys.clone();
+    loop {
+        let inputs = overlapping(files, &keys, sorted);
+        let grown = cover(&keys, files, &inputs);
+        if sorted || grown == keys {
+            return (inputs, grown);
+        }
+        keys = grown;
+    }
+}
+
+/** The files to compact with file `file` of level `level`, chosen as LevelDB does.
+
+On levels other than 0 the inputs start as just `file`; on level 0, they are all the files
+connected to it by overlapping key ranges. The files of `level + 1` that overlap the inputs
+are added, so that compacting leaves `level + 1` without overlapping files.
+More files of `level` are then taken in if they fit within the keys covered so far,
+but only if that doesn't add files of `level + 1`.
+`None` if there's no such file.
+
+```
+# use range_split::compaction::plan_compaction;
+let levels = vec![
+    vec![],
+    vec![10..=20, 30..=40, 50..=60],
+    vec![0..=12, 15..=32, 35..=45, 58..=70],
+];
+let plan = plan_compaction(&levels, 1, 0).unwrap();
+// Taking in 30..=40 as well would also need 35..=45
+assert_eq!(plan.inputs, vec![0]);
+assert_eq!(plan.next_inputs, vec![0, 1]);
+assert_eq!(plan.keys, 0..=32);
+```
+**/
+pub fn plan_compaction<K>(
+    levels: &[Vec<RangeInclusive<K>>],
+    level: usize,
+    file: usize,
+) -> Option<Compaction<K>>
+where
+    K: Ord + Clone,
+{
+    let files = levels.get(level)?;
+    let next: &[RangeInclusive<K>] = levels.get(level + 1).map_or(&[], Vec::as_slice);
+    let sorted = level > 0;
+    let (mut inputs, mut keys) = level_inputs(files, files.get(file)?, sorted);
+    let next_inputs = overlapping(next, &keys, true);
+
+    let all = cover(&keys, next, &next_inputs);
+    let (expanded, expanded_keys) = level_inputs(files, &all, sorted);
+    if expanded.len() > inputs.len() && overlapping(next, &expanded_keys, true) == next_inputs {
+        (inputs, keys) = (expanded, expanded_keys);
+    }
+    Some(Compaction {
+        level,
+        inputs,
+        keys: cover(&keys, next, &next_inputs),
+        next_inputs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_zero_overlaps_chain() {
+        let levels = vec![
+            vec![50..=60, 0..=10, 8..=20, 19..=25],
+            vec![0..=5, 22..=30, 40..=45],
+        ];
+        let plan = plan_compaction(&levels, 0, 1).unwrap();
+        assert_eq!(plan.inputs, vec![1, 2, 3]);
+        assert_eq!(plan.next_inputs, vec![0, 1]);
+        assert_eq!(plan.keys, 0..=30)
+    }
+
+    #[test]
+    fn expands_within_next_inputs() {
+        let levels = vec![
+            vec![],
+            vec![10..=20, 25..=28, 31..=40],
+            vec![0..=30, 35..=50],
+        ];
+        let plan = plan_compaction(&levels, 1, 0).unwrap();
+        assert_eq!(plan.inputs, vec![0, 1]);
+        assert_eq!(plan.next_inputs, vec![0]);
+        assert_eq!(plan.keys, 0..=30)
+    }
+
+    #[test]
+    fn level_zero_expansion_stays_closed() {
+        // 4..=5 and 5..=7 overlap 0..=5, but 5..=7 would drag in 7..=9, and with it 8..=9
+        let levels = vec![vec![0..=1, 4..=5, 5..=7, 7..=9], vec![0..=5, 8..=9]];
+        let plan = plan_compaction(&levels, 0, 0).unwrap();
+        assert_eq!(plan.inputs, vec![0]);
+        assert_eq!(plan.next_inputs, vec![0]);
+        assert_eq!(plan.keys, 0..=5)
+    }
+
+    #[test]
+    fn no_next_level_overlap() {
+        let levels = vec![vec![], vec![10..=20, 30..=40], vec![0..=5, 50..=55]];
+        let plan = plan_compaction(&levels, 1, 1).unwrap();
+        assert_eq!(plan.inputs, vec![1]);
+        assert!(plan.next_inputs.is_empty());
+        assert_eq!(plan.keys, 30..=40)
+    }
+
+    #[test]
+    fn shared_boundary_key() {
+        // Inclusive ranges sharing the key 20 overlap
+        let levels = vec![vec![], vec![10..=20], vec![20..=30, 31..=35]];
+        let plan = plan_compaction(&levels, 1, 0).unwrap();
+        assert_eq!(plan.next_inputs, vec![0]);
+        assert_eq!(plan.keys, 10..=30)
+    }
+
+    #[test]
+    fn missing_file() {
+        let levels: Vec<Vec<RangeInclusive<u32>>> = vec![vec![0..=1]];
+        assert_eq!(plan_compaction(&levels, 0, 1), None);
+        assert_eq!(plan_compaction(&levels, 3, 0), None);
+        assert!(plan_compaction(&levels, 0, 0)
+            .unwrap()
+            .next_inputs
+            .is_empty())
+    }
+}
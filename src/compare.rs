@@ -0,0 +1,136 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Ranges ordered by a comparator chosen at runtime, rather than by `Ord`.
+
+use core::cmp::Ordering;
+use core::ops::Range;
+
+use crate::{split_with, Split};
+
+/// A total order over `T`.
+pub trait Compare<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+impl<T: ?Sized, F> Compare<T> for F
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+/** A half-open range whose endpoints are ordered by the comparator `C`.
+
+Usage:
+```
+# use range_split::{compare::RangeBy, Split};
+let nocase = |a: &&str, b: &&str| a.to_lowercase().cmp(&b.to_lowercase());
+let r = RangeBy::new("apple", "Mango", nocase);
+assert!(r.contains(&"BANANA"));
+
+let (below, inter, above) = r.split(&RangeBy::new("Cherry", "zucchini", nocase));
+assert_eq!(below.map(|b| b.range), Some("apple".."Cherry"));
+assert_eq!(inter.map(|i| i.range), Some("Cherry".."Mango"));
+assert!(above.is_none());
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeBy<T, C> {
+    pub range: Range<T>,
+    pub cmp: C,
+}
+
+impl<T, C> RangeBy<T, C>
+where
+    C: Compare<T>,
+{
+    pub fn new(start: T, end: T, cmp: C) -> Self {
+        Self {
+            range: start..end,
+            cmp,
+        }
+    }
+
+    fn lt(&self, a: &T, b: &T) -> bool {
+        self.cmp.compare(a, b) == Ordering::Less
+    }
+
+    /// Whether `x` lies within the range, under `C`.
+    pub fn contains(&self, x: &T) -> bool {
+        !self.lt(x, &self.range.start) && self.lt(x, &self.range.end)
+    }
+
+    /// Whether the range contains nothing, under `C`.
+    pub fn is_empty(&self) -> bool {
+        !self.lt(&self.range.start, &self.range.end)
+    }
+
+    /// Whether the two ranges share any elements, under `self`'s comparator.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.lt(&self.range.start, &other.range.end)
+            && self.lt(&other.range.start, &self.range.end)
+    }
+}
+
+/// Splits using `self`'s comparator.
+impl<T, C> Split for RangeBy<T, C>
+where
+    T: Clone,
+    C: Compare<T> + Clone,
+{
+    fn split(&self, other: &Self) -> (Option<Self>, Option<Self>, Option<Self>) {
+        let wrap = |r: Option<Range<T>>| {
+            r.map(|range| Self {
+                range,
+                cmp: self.cmp.clone(),
+            })
+        };
+        let (below, inter, above) = split_with(&self.range, &other.range, |a, b| self.lt(a, b));
+        (wrap(below), wrap(inter), wrap(above))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Orders numbers by their value modulo a runtime-chosen base.
+    #[derive(Clone)]
+    struct Modulo(u32);
+
+    impl Compare<u32> for Modulo {
+        fn compare(&self, a: &u32, b: &u32) -> Ordering {
+            (a % self.0).cmp(&(b % self.0))
+        }
+    }
+
+    #[test]
+    fn runtime_comparator() {
+        let r = RangeBy::new(12, 18, Modulo(10));
+        assert!(r.contains(&3));
+        assert!(!r.contains(&8));
+    }
+
+    #[test]
+    fn split_matches_ord() {
+        let cmp = |a: &u32, b: &u32| a.cmp(b);
+        let (below, inter, above) = RangeBy::new(0, 10, cmp).split(&RangeBy::new(3, 6, cmp));
+        let plain = (0..10).split(&(3..6));
+        assert_eq!(below.map(|b| b.range), plain.0);
+        assert_eq!(inter.map(|i| i.range), plain.1);
+        assert_eq!(above.map(|a| a.range), plain.2);
+    }
+
+    #[test]
+    fn overlaps_and_empty() {
+        let a = RangeBy::new(21, 5, Modulo(10));
+        let b = RangeBy::new(4, 9, Modulo(10));
+        assert!(a.overlaps(&b));
+        assert!(RangeBy::new(7, 17, Modulo(10)).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! A range set that many threads can claim ranges from at once.
+
+use core::ops::Range;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::{runs, Split};
+
+/** A thread-safe set of reserved ranges of a fixed domain, for claiming work
+(such as byte ranges of a download) without a global lock.
+
+The domain is divided into equal shards, each with its own lock; an operation
+locks only the shards its range touches, always in ascending order.
+
+Usage:
+```
+# use range_split::concurrent::ConcurrentRangeSet;
+let set = ConcurrentRangeSet::new(0..1000, 10);
+assert!(set.try_reserve(0..150));
+assert!(!set.try_reserve(100..200));
+set.release(&(100..150));
+assert!(set.try_reserve(100..200));
+assert_eq!(set.snapshot(), vec![0..200]);
+```
+**/
+#[derive(Debug)]
+pub struct ConcurrentRangeSet {
+    domain: Range<u64>,
+    shard_size: u64,
+    /// Each shard holds only the parts of reservations within its bounds.
+    shards: Vec<Mutex<Vec<Range<u64>>>>,
+}
+
+fn lock(shard: &Mutex<Vec<Range<u64>>>) -> MutexGuard<'_, Vec<Range<u64>>> {
+    // Shards are never left half-updated, so a panic elsewhere doesn't invalidate them
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl ConcurrentRangeSet {
+    /// An empty set over `domain`, split into at most `shards` shards.
+    /// Panics if `shards` is zero.
+    pub fn new(domain: Range<u64>, shards: usize) -> Self {
+        assert!(shards > 0, "need at least one shard");
+        let len = domain.end.saturating_sub(domain.start);
+        let shard_size = len.div_ceil(shards as u64).max(1);
+        let count = len.div_ceil(shard_size) as usize;
+        Self {
+            domain,
+            shard_size,
+            shards: (0..count).map(|_| Mutex::new(Vec::new())).collect(),
+        }
+    }
+
+    /// The shards that `range` (within the domain) touches, and their bounds.
+    fn shards_of(&self, range: &Range<u64>) -> impl Iterator<Item = (usize, Range<u64>)> + '_ {
+        let offset = |t: u64| ((t - self.domain.start) / self.shard_size) as usize;
+        let (first, last) = (offset(range.start), offset(range.end - 1));
+        (first..=last).map(move |i| {
+            let start = self.domain.start + i as u64 * self.shard_size;
+            (
+                i,
+                start..start.saturating_add(self.shard_size).min(self.domain.end),
+            )
+        })
+    }
+
+    /// Reserve `range` as one atomic step. Returns `false` (and reserves nothing)
+    /// if any part of it is already reserved or it's outside the domain.
+    /// Reserving an empty range always succeeds.
+    pub fn try_reserve(&self, range: Range<u64>) -> bool {
+        if range.start >= range.end {
+            return true;
+        }
+        if range.start < self.domain.start || self.domain.end < range.end {
+            return false;
+        }
+        let mut held = Vec::new();
+        for (i, bounds) in self.shards_of(&range) {
+            let guard = lock(&self.shards[i]);
+            let piece = range.split(&bounds).1.expect("range overlaps its shards");
+            let j = guard.partition_point(|r| r.end <= piece.start);
+            if guard.get(j).is_some_and(|r| r.start < piece.end) {
+                return false;
+            }
+            held.push((guard, piece));
+        }
+        for (mut guard, piece) in held {
+            runs::insert(&mut guard, piece);
+        }
+        true
+    }
+
+    /// Release any reserved parts of `range`.
+    pub fn release(&self, range: &Range<u64>) {
+        let Some(range) = range.split(&self.domain).1.filter(|r| r.start < r.end) else {
+            return;
+        };
+        for (i, _) in self.shards_of(&range) {
+            runs::remove(&mut lock(&self.shards[i]), &range);
+        }
+    }
+
+    pub fn contains(&self, t: u64) -> bool {
+        if !self.domain.contains(&t) {
+            return false;
+        }
+        self.shards_of(&(t..t + 1))
+            .any(|(i, _)| runs::containing(&lock(&self.shards[i]), t).is_some())
+    }
+
+    /// The reserved ranges at one instant, sorted and coalesced.
+    pub fn snapshot(&self) -> Vec<Range<u64>> {
+        let guards: Vec<_> = self.shards.iter().map(lock).collect();
+        let mut out = Vec::new();
+        for piece in guards.iter().flat_map(|g| g.iter()) {
+            runs::insert(&mut out, piece.clone());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflict_in_later_shard_reserves_nothing() {
+        let set = ConcurrentRangeSet::new(0..100, 10);
+        assert!(set.try_reserve(55..56));
+        assert!(!set.try_reserve(0..60));
+        assert!(!set.contains(0));
+        assert_eq!(set.snapshot(), core::slice::from_ref(&(55..56)));
+    }
+
+    #[test]
+    fn outside_domain() {
+        let set = ConcurrentRangeSet::new(10..20, 3);
+        assert!(!set.try_reserve(5..15));
+        assert!(!set.try_reserve(15..21));
+        assert!(set.try_reserve(10..20));
+        assert!(!set.contains(20));
+        set.release(&(0..15));
+        assert_eq!(set.snapshot(), core::slice::from_ref(&(15..20)));
+    }
+
+    #[test]
+    fn more_shards_than_elements() {
+        let set = ConcurrentRangeSet::new(0..3, 10);
+        assert!(set.try_reserve(0..3));
+        assert!(set.contains(2));
+    }
+
+    #[test]
+    fn full_domain() {
+        let set = ConcurrentRangeSet::new(0..u64::MAX, 2);
+        assert!(set.try_reserve(u64::MAX - 10..u64::MAX));
+        assert!(set.try_reserve((1 << 63) - 5..(1 << 63) + 5));
+        assert!(!set.try_reserve(0..u64::MAX));
+        assert!(set.contains(u64::MAX - 1));
+        set.release(&(0..u64::MAX));
+        assert!(set.try_reserve(0..u64::MAX));
+        assert_eq!(set.snapshot(), core::slice::from_ref(&(0..u64::MAX)));
+    }
+
+    #[test]
+    fn threads_claim_disjointly() {
+        let set = ConcurrentRangeSet::new(0..1000, 8);
+        let claimed: Vec<Vec<Range<u64>>> = std::thread::scope(|s| {
+            let workers: Vec<_> = (0..4)
+                .map(|w| {
+                    let set = &set;
+                    s.spawn(move || {
+                        // Every worker tries every block, offset so that they collide
+                        (0..100)
+                            .map(|b| (b * 10 + w * 3) % 990)
+                            .map(|start| start..start + 10)
+                            .filter(|r| set.try_reserve(r.clone()))
+                            .collect()
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        let mut all: Vec<_> = claimed.into_iter().flatten().collect();
+        all.sort_by_key(|r| r.start);
+        assert!(all.windows(2).all(|w| w[0].end <= w[1].start));
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Tracking the damaged (dirty) region of a surface between frames, as compositors do.
+
+use std::collections::VecDeque;
+
+use crate::rect::Rect;
+use crate::region::Region2D;
+
+/** The region damaged since the last frame was presented, and that of recent frames.
+
+Once the damage takes more than `max_rects` rectangles, it's replaced by its bounding box:
+repainting a little extra is cheaper than tracking many small pieces.
+
+The damage of the last `max_age` presented frames is kept, to answer what needs repainting
+in a buffer whose contents are some number of frames old (its "buffer age").
+
+Usage:
+```
+# use range_split::{damage::DamageTracker, rect::Rect, region::Region2D};
+let mut damage = DamageTracker::new(4, 2);
+damage.add_damage(Rect::new(0..10, 0..10));
+let presented = damage.take();
+assert_eq!(presented, Region2D::from(Rect::new(0..10, 0..10)));
+
+damage.add_damage(Rect::new(20..30, 0..10));
+// A buffer from the last frame only misses the new damage...
+assert_eq!(damage.damage_for_age(1).unwrap().area::<i32>(), 100);
+// ...one from the frame before misses that frame's too
+assert_eq!(damage.damage_for_age(2).unwrap().area::<i32>(), 200);
+// Anything older, or of unknown age, must be repainted in full
+assert_eq!(damage.damage_for_age(4), None);
+assert_eq!(damage.damage_for_age(0), None);
+```
+**/
+#[derive(Debug, Clone)]
+pub struct DamageTracker<T> {
+    current: Region2D<T>,
+    /// Damage of presented frames, most recent first.
+    history: VecDeque<Region2D<T>>,
+    max_rects: usize,
+    max_age: usize,
+}
+
+impl<T> DamageTracker<T>
+where
+    T: Sized + Ord + Copy,
+{
+    /// A tracker simplifying past `max_rects` rectangles, and remembering `max_age` frames.
+    pub fn new(max_rects: usize, max_age: usize) -> Self {
+        Self {
+            current: Region2D::new(),
+            history: VecDeque::with_capacity(max_age),
+            max_rects,
+            max_age,
+        }
+    }
+
+    /// `region`, or its bounding box if it takes more than `max_rects` rectangles.
+    fn simplify(&self, region: Region2D<T>) -> Region2D<T> {
+        match region.bounds() {
+            Some(bounds) if region.len() > self.max_rects => bounds.into(),
+            _ => region,
+        }
+    }
+
+    /// Mark `rect` as damaged.
+    pub fn add_damage(&mut self, rect: Rect<T>) {
+        let mut damage = core::mem::take(&mut self.current);
+        damage.insert(rect);
+        self.current = self.simplify(damage);
+    }
+
+    /// The damage since the last frame was presented.
+    pub fn damage(&self) -> &Region2D<T> {
+        &self.current
+    }
+
+    /// Present a frame: return the damage to repaint and start afresh,
+    /// remembering the damage for later buffer ages.
+    pub fn take(&mut self) -> Region2D<T> {
+        let damage = core::mem::take(&mut self.current);
+        if self.max_age > 0 {
+            self.history.truncate(self.max_age - 1);
+            self.history.push_front(damage.clone());
+        }
+        damage
+    }
+
+    /// The damage to repaint in a buffer last painted `age` frames ago:
+    /// that since the last frame, and that of the `age - 1` frames before.
+    /// `None` if the buffer must be repainted in full, as its contents are
+    /// unknown (`age` is 0) or older than the history kept.
+    pub fn damage_for_age(&self, age: usize) -> Option<Region2D<T>> {
+        if age == 0 || age - 1 > self.history.len() {
+            return None;
+        }
+        let damage = self
+            .history
+            .iter()
+            .take(age - 1)
+            .fold(self.current.clone(), |all, frame| all.union(frame));
+        Some(self.simplify(damage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragmented_damage_becomes_bounds() {
+        let mut damage = DamageTracker::new(2, 0);
+        damage.add_damage(Rect::new(0..1, 0..1));
+        damage.add_damage(Rect::new(5..6, 0..1));
+        assert_eq!(damage.damage().len(), 2);
+        damage.add_damage(Rect::new(2..3, 8..9));
+        assert_eq!(damage.damage(), &Region2D::from(Rect::new(0..6, 0..9)));
+        damage.take();
+        assert!(damage.damage().is_empty());
+        assert_eq!(damage.damage_for_age(2), None)
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut damage = DamageTracker::new(8, 2);
+        for i in 0..5 {
+            damage.add_damage(Rect::new(i * 10..i * 10 + 1, 0..1));
+            damage.take();
+        }
+        assert_eq!(damage.damage_for_age(3).unwrap().len(), 2);
+        assert_eq!(damage.damage_for_age(4), None);
+        assert_eq!(damage.damage_for_age(1), Some(Region2D::new()))
+    }
+}
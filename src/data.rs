@@ -0,0 +1,383 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! The data-level counterparts of [`Split`](crate::Split): splitting slices and collections
+//! into the parts below, within, and above a range.
+
+use core::fmt;
+use core::ops::Range;
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+
+/// Clamp `range` to `0..len`, treating an inverted range as empty at its start.
+fn clamp(range: &Range<usize>, len: usize) -> (usize, usize) {
+    let start = range.start.min(len);
+    (start, range.end.clamp(start, len))
+}
+
+/** Split a slice by an index range into three subslices.
+
+Usage:
+```
+# use range_split::data::SplitSlice;
+let data = [0, 1, 2, 3, 4, 5, 6, 7];
+assert_eq!(data.split_by_range(3..6), (&[0, 1, 2][..], &[3, 4, 5][..], &[6, 7][..]));
+
+let mut data = data;
+let (below, within, _) = data.split_by_range_mut(3..6);
+within[0] = below[0];
+assert_eq!(data[3], 0);
+```
+Indices past the end of the slice are clamped, as though splitting `0..len` by `range`.
+**/
+pub trait SplitSlice<T> {
+    /// Split `self` into the elements before, within, and after `range`.
+    fn split_by_range(&self, range: Range<usize>) -> (&[T], &[T], &[T]);
+
+    /// As [`split_by_range`](SplitSlice::split_by_range), but mutably.
+    fn split_by_range_mut(&mut self, range: Range<usize>) -> (&mut [T], &mut [T], &mut [T]);
+}
+
+impl<T> SplitSlice<T> for [T] {
+    fn split_by_range(&self, range: Range<usize>) -> (&[T], &[T], &[T]) {
+        let (start, end) = clamp(&range, self.len());
+        let (below, rest) = self.split_at(start);
+        let (within, above) = rest.split_at(end - start);
+        (below, within, above)
+    }
+
+    fn split_by_range_mut(&mut self, range: Range<usize>) -> (&mut [T], &mut [T], &mut [T]) {
+        let (start, end) = clamp(&range, self.len());
+        let (below, rest) = self.split_at_mut(start);
+        let (within, above) = rest.split_at_mut(end - start);
+        (below, within, above)
+    }
+}
+
+/** Split a sorted slice into the elements below, within, and above a value range.
+
+Uses two binary searches, so `slice` must be sorted in ascending order.
+
+```
+# use range_split::data::split_sorted_by_value;
+let data = [1, 3, 3, 5, 8, 13];
+assert_eq!(split_sorted_by_value(&data, &(3..8)), (&[1][..], &[3, 3, 5][..], &[8, 13][..]));
+```
+**/
+pub fn split_sorted_by_value<'a, T>(slice: &'a [T], range: &Range<T>) -> (&'a [T], &'a [T], &'a [T])
+where
+    T: Ord,
+{
+    split_sorted_by_key(slice, range, |x| x)
+}
+
+/** As [`split_sorted_by_value`], but comparing a key extracted from each element.
+
+`slice` must be sorted by that key.
+
+```
+# use range_split::data::split_sorted_by_key;
+let events = [(1, 'a'), (4, 'b'), (6, 'c')];
+let (_, within, _) = split_sorted_by_key(&events, &(2..5), |e| &e.0);
+assert_eq!(within, &[(4, 'b')]);
+```
+**/
+pub fn split_sorted_by_key<'a, T, K>(
+    slice: &'a [T],
+    range: &Range<K>,
+    key: impl Fn(&T) -> &K,
+) -> (&'a [T], &'a [T], &'a [T])
+where
+    K: Ord,
+{
+    let start = slice.partition_point(|x| *key(x) < range.start);
+    let end = start + slice[start..].partition_point(|x| *key(x) < range.end);
+    let (below, rest) = slice.split_at(start);
+    let (within, above) = rest.split_at(end - start);
+    (below, within, above)
+}
+
+/// What [`split_str`] does with range endpoints that fall inside a multi-byte char.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Snap {
+    /// Return an error.
+    Strict,
+    /// Move endpoints toward each other, excluding partial chars.
+    Inward,
+    /// Move endpoints away from each other, including partial chars.
+    Outward,
+}
+
+/// A byte index given to [`split_str`] was not on a char boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharBoundaryError {
+    pub index: usize,
+}
+
+impl fmt::Display for CharBoundaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte index {} is not a char boundary", self.index)
+    }
+}
+
+impl std::error::Error for CharBoundaryError {}
+
+/** Split a string by a byte range into the parts before, within, and after it.
+
+Endpoints that aren't on char boundaries are handled according to `snap`.
+Indices past the end of the string are clamped, as with [`SplitSlice`].
+
+```
+# use range_split::data::{split_str, Snap};
+let s = "añb"; // 'ñ' is bytes 1..3
+assert_eq!(split_str(s, 1..3, Snap::Strict), Ok(("a", "ñ", "b")));
+assert!(split_str(s, 2..4, Snap::Strict).is_err());
+assert_eq!(split_str(s, 2..4, Snap::Inward), Ok(("añ", "b", "")));
+assert_eq!(split_str(s, 2..4, Snap::Outward), Ok(("a", "ñb", "")));
+```
+**/
+pub fn split_str(
+    s: &str,
+    range: Range<usize>,
+    snap: Snap,
+) -> Result<(&str, &str, &str), CharBoundaryError> {
+    let (start, end) = clamp(&range, s.len());
+    let floor = |mut i: usize| {
+        while !s.is_char_boundary(i) {
+            i -= 1;
+        }
+        i
+    };
+    let ceil = |mut i: usize| {
+        while !s.is_char_boundary(i) {
+            i += 1;
+        }
+        i
+    };
+    let (start, end) = match snap {
+        Snap::Strict => {
+            if let Some(&index) = [start, end].iter().find(|&&i| !s.is_char_boundary(i)) {
+                return Err(CharBoundaryError { index });
+            }
+            (start, end)
+        }
+        Snap::Inward => {
+            let start = ceil(start);
+            (start, floor(end).max(start))
+        }
+        Snap::Outward => (floor(start), ceil(end)),
+    };
+    Ok((&s[..start], &s[start..end], &s[end..]))
+}
+
+/** Split a `Vec` by an index range into three vectors, moving rather than cloning elements.
+
+```
+# use range_split::data::vec_split_by_range;
+let (below, within, above) = vec_split_by_range(vec![0, 1, 2, 3, 4], 1..3);
+assert_eq!((below, within, above), (vec![0], vec![1, 2], vec![3, 4]));
+```
+Indices past the end of the vector are clamped, as with [`SplitSlice`].
+**/
+pub fn vec_split_by_range<T>(mut vec: Vec<T>, range: Range<usize>) -> (Vec<T>, Vec<T>, Vec<T>) {
+    let (start, end) = clamp(&range, vec.len());
+    let above = vec.split_off(end);
+    let within = vec.split_off(start);
+    (vec, within, above)
+}
+
+/** Remove and return the elements of `vec` within `range`, leaving those below and above it.
+
+```
+# use range_split::data::vec_take_range;
+let mut v = vec![0, 1, 2, 3, 4];
+assert_eq!(vec_take_range(&mut v, 1..3), vec![1, 2]);
+assert_eq!(v, vec![0, 3, 4]);
+```
+**/
+pub fn vec_take_range<T>(vec: &mut Vec<T>, range: Range<usize>) -> Vec<T> {
+    let (start, end) = clamp(&range, vec.len());
+    let mut above = vec.split_off(end);
+    let within = vec.split_off(start);
+    vec.append(&mut above);
+    within
+}
+
+/// The end of `range`, treating an inverted range as empty at its start.
+fn clamped_end<K: Ord>(range: &Range<K>) -> &K {
+    if range.end < range.start {
+        &range.start
+    } else {
+        &range.end
+    }
+}
+
+/** Split a `BTreeMap` into the entries with keys below, within, and above `range`.
+
+```
+# use std::collections::BTreeMap;
+# use range_split::data::btree_map_split;
+let map = BTreeMap::from([(1, 'a'), (5, 'b'), (9, 'c')]);
+let (below, within, above) = btree_map_split(map, &(2..9));
+assert_eq!(within, BTreeMap::from([(5, 'b')]));
+assert_eq!((below.len(), above.len()), (1, 1));
+```
+**/
+pub fn btree_map_split<K, V>(
+    mut map: BTreeMap<K, V>,
+    range: &Range<K>,
+) -> (BTreeMap<K, V>, BTreeMap<K, V>, BTreeMap<K, V>)
+where
+    K: Ord,
+{
+    let above = map.split_off(clamped_end(range));
+    let within = map.split_off(&range.start);
+    (map, within, above)
+}
+
+/// As [`btree_map_split`], for a `BTreeSet`.
+pub fn btree_set_split<T>(
+    mut set: BTreeSet<T>,
+    range: &Range<T>,
+) -> (BTreeSet<T>, BTreeSet<T>, BTreeSet<T>)
+where
+    T: Ord,
+{
+    let above = set.split_off(clamped_end(range));
+    let within = set.split_off(&range.start);
+    (set, within, above)
+}
+
+/** Borrow the entries of a `BTreeMap` with keys below, within, and above `range`,
+without modifying the map.
+
+```
+# use std::collections::BTreeMap;
+# use range_split::data::btree_map_view;
+let map = BTreeMap::from([(1, 'a'), (5, 'b'), (9, 'c')]);
+let (below, within, above) = btree_map_view(&map, &(2..9));
+assert_eq!(within.collect::<Vec<_>>(), vec![(&5, &'b')]);
+assert_eq!((below.count(), above.count()), (1, 1));
+```
+**/
+pub fn btree_map_view<'a, K, V>(
+    map: &'a BTreeMap<K, V>,
+    range: &Range<K>,
+) -> (
+    btree_map::Range<'a, K, V>,
+    btree_map::Range<'a, K, V>,
+    btree_map::Range<'a, K, V>,
+)
+where
+    K: Ord,
+{
+    let end = clamped_end(range);
+    (
+        map.range(..&range.start),
+        map.range(&range.start..end),
+        map.range(end..),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: [u8; 5] = [0, 1, 2, 3, 4];
+
+    #[test]
+    fn past_end() {
+        assert_eq!(DATA.split_by_range(3..9), (&DATA[..3], &DATA[3..], &[][..]))
+    }
+
+    #[test]
+    fn entirely_past_end() {
+        assert_eq!(DATA.split_by_range(7..9), (&DATA[..], &[][..], &[][..]))
+    }
+
+    #[test]
+    fn inverted() {
+        let (start, end) = (4, 2);
+        assert_eq!(
+            DATA.split_by_range(start..end),
+            (&DATA[..4], &[][..], &DATA[4..])
+        )
+    }
+
+    #[test]
+    fn mut_disjoint() {
+        let mut data = DATA;
+        let (below, within, above) = data.split_by_range_mut(1..4);
+        within.swap_with_slice(&mut [below[0], above[0], 9]);
+        assert_eq!(data, [0, 0, 4, 9, 4])
+    }
+
+    #[test]
+    fn sorted_value_outside() {
+        assert_eq!(
+            split_sorted_by_value(&DATA, &(7..9)),
+            (&DATA[..], &[][..], &[][..])
+        )
+    }
+
+    #[test]
+    fn sorted_value_bounds() {
+        assert_eq!(
+            split_sorted_by_value(&DATA, &(1..4)),
+            (&DATA[..1], &DATA[1..4], &DATA[4..])
+        )
+    }
+
+    #[test]
+    fn str_inward_within_char() {
+        assert_eq!(split_str("日本", 1..2, Snap::Inward), Ok(("日", "", "本")))
+    }
+
+    #[test]
+    fn str_strict_end() {
+        assert_eq!(
+            split_str("日本", 0..4, Snap::Strict),
+            Err(CharBoundaryError { index: 4 })
+        )
+    }
+
+    #[test]
+    fn str_outward_past_end() {
+        assert_eq!(
+            split_str("日本", 4..10, Snap::Outward),
+            Ok(("日", "本", ""))
+        )
+    }
+
+    #[test]
+    fn vec_past_end() {
+        assert_eq!(
+            vec_split_by_range(DATA.to_vec(), 3..9),
+            (vec![0, 1, 2], vec![3, 4], vec![])
+        )
+    }
+
+    #[test]
+    fn vec_take_everything() {
+        let mut v = DATA.to_vec();
+        assert_eq!(vec_take_range(&mut v, 0..5), DATA.to_vec());
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn btree_set_inverted() {
+        let set = BTreeSet::from(DATA);
+        let (start, end) = (3, 1);
+        let (below, within, above) = btree_set_split(set, &(start..end));
+        assert_eq!(below, BTreeSet::from([0, 1, 2]));
+        assert!(within.is_empty());
+        assert_eq!(above, BTreeSet::from([3, 4]));
+    }
+
+    #[test]
+    fn btree_view_matches_split() {
+        let map: BTreeMap<u8, u8> = DATA.iter().map(|&x| (x, x * 2)).collect();
+        let (vb, vw, va) = btree_map_view(&map, &(1..3));
+        let (b, w, a) = btree_map_split(map.clone(), &(1..3));
+        assert!(vb.eq(b.iter()) && vw.eq(w.iter()) && va.eq(a.iter()));
+    }
+}
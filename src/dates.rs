@@ -0,0 +1,186 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Whole-day ranges of [`chrono::NaiveDate`]s, inclusive at both ends, as bookings and leave are.
+//!
+//! Arithmetic is done on days counted from the Common Era, as
+//! [`Datelike::num_days_from_ce`] gives them.
+
+use core::fmt;
+use core::ops::Range;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::storage::RangeSet;
+use crate::Split;
+
+/** The days from `first` to `last`, inclusive.
+
+Usage:
+```
+# use range_split::dates::DateRange;
+# use range_split::Split;
+use chrono::NaiveDate;
+
+let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+let trip = DateRange::new(date(2, 26), date(3, 3)).unwrap();
+assert_eq!(trip.num_days(), 7); // 2024 is a leap year
+
+let march = DateRange::new(date(3, 1), date(3, 31)).unwrap();
+let (feb, in_march, _) = trip.split(&march);
+assert_eq!(feb.unwrap().to_string(), "2024-02-26..=2024-02-29");
+assert_eq!(in_march.unwrap().num_days(), 3);
+assert!(feb.unwrap().is_adjacent(&march));
+```
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateRange {
+    first: NaiveDate,
+    last: NaiveDate,
+}
+
+impl DateRange {
+    /// `None` if `last` is before `first`.
+    pub fn new(first: NaiveDate, last: NaiveDate) -> Option<Self> {
+        (first <= last).then_some(Self { first, last })
+    }
+
+    /// The range holding the days in `days`, counted from the Common Era,
+    /// or `None` if it's empty or reaches outside the dates [`NaiveDate`] can hold.
+    pub fn from_days(days: Range<i32>) -> Option<Self> {
+        if days.start >= days.end {
+            return None;
+        }
+        Self::new(
+            NaiveDate::from_num_days_from_ce_opt(days.start)?,
+            NaiveDate::from_num_days_from_ce_opt(days.end - 1)?,
+        )
+    }
+
+    pub fn first(&self) -> NaiveDate {
+        self.first
+    }
+
+    pub fn last(&self) -> NaiveDate {
+        self.last
+    }
+
+    /// The days covered, counted from the Common Era, as a half-open range.
+    pub fn days(&self) -> Range<i32> {
+        // Even `NaiveDate::MAX` is far from `i32::MAX` days in
+        self.first.num_days_from_ce()..self.last.num_days_from_ce() + 1
+    }
+
+    /// Number of days covered; never zero.
+    pub fn num_days(&self) -> u64 {
+        let days = self.days();
+        days.end.abs_diff(days.start).into()
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.first <= date && date <= self.last
+    }
+
+    /// Whether one range ends the day before the other starts.
+    pub fn is_adjacent(&self, other: &Self) -> bool {
+        let (a, b) = (self.days(), other.days());
+        a.end == b.start || b.end == a.start
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..={}", self.first, self.last)
+    }
+}
+
+impl Split for DateRange {
+    fn split(&self, other: &Self) -> (Option<Self>, Option<Self>, Option<Self>) {
+        let (below, inter, above) = self.days().split(&other.days());
+        let back = |r: Option<Range<i32>>| r.and_then(Self::from_days);
+        (back(below), back(inter), back(above))
+    }
+}
+
+/** Merge date ranges that overlap or are adjacent, giving them in order.
+They're coalesced as a [`RangeSet`] of their [`days`](DateRange::days).
+
+```
+# use range_split::dates::{coalesce, DateRange};
+use chrono::NaiveDate;
+
+let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+let leave = [
+    DateRange::new(date(8), date(12)).unwrap(),
+    DateRange::new(date(1), date(5)).unwrap(),
+    DateRange::new(date(6), date(7)).unwrap(),
+];
+assert_eq!(coalesce(&leave), vec![DateRange::new(date(1), date(12)).unwrap()]);
+```
+**/
+pub fn coalesce(ranges: &[DateRange]) -> Vec<DateRange> {
+    let merged: RangeSet<i32> = ranges.iter().map(DateRange::days).collect();
+    merged.iter().filter_map(DateRange::from_days).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn days_round_trip() {
+        let range = DateRange::new(date(1969, 12, 31), date(2000, 3, 1)).unwrap();
+        assert_eq!(DateRange::from_days(range.days()), Some(range));
+        assert_eq!(range.num_days(), 11_019);
+        let (p, q) = (10, 10);
+        assert_eq!(DateRange::from_days(p..q), None);
+    }
+
+    #[test]
+    fn limits_of_naive_date() {
+        let all = DateRange::new(NaiveDate::MIN, NaiveDate::MAX).unwrap();
+        assert_eq!(DateRange::from_days(all.days()), Some(all));
+        let past_end = all.days().end..all.days().end + 1;
+        assert_eq!(DateRange::from_days(past_end), None);
+    }
+
+    #[test]
+    fn single_day() {
+        let day = DateRange::new(date(2024, 5, 1), date(2024, 5, 1)).unwrap();
+        assert_eq!(day.num_days(), 1);
+        assert!(DateRange::new(date(2024, 5, 2), date(2024, 5, 1)).is_none());
+    }
+
+    #[test]
+    fn split_at_one_day() {
+        let month = DateRange::new(date(2024, 5, 1), date(2024, 5, 31)).unwrap();
+        let holiday = DateRange::new(date(2024, 5, 27), date(2024, 5, 27)).unwrap();
+        let (before, on, after) = month.split(&holiday);
+        assert_eq!(before.unwrap().last(), date(2024, 5, 26));
+        assert_eq!(on, Some(holiday));
+        assert_eq!(after.unwrap().first(), date(2024, 5, 28));
+    }
+
+    #[test]
+    fn adjacent_across_year_end() {
+        let a = DateRange::new(date(2023, 12, 20), date(2023, 12, 31)).unwrap();
+        let b = DateRange::new(date(2024, 1, 1), date(2024, 1, 5)).unwrap();
+        assert!(a.is_adjacent(&b) && b.is_adjacent(&a));
+        assert_eq!(
+            coalesce(&[b, a]),
+            vec![DateRange::new(a.first(), b.last()).unwrap()]
+        );
+    }
+
+    #[test]
+    fn gap_of_a_day_is_not_adjacent() {
+        let a = DateRange::new(date(2024, 1, 1), date(2024, 1, 2)).unwrap();
+        let b = DateRange::new(date(2024, 1, 4), date(2024, 1, 5)).unwrap();
+        assert!(!a.is_adjacent(&b));
+        assert_eq!(coalesce(&[a, b]), vec![a, b]);
+    }
+}
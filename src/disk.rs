@@ -0,0 +1,161 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Partition-table arithmetic over ranges of disk sectors.
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::overlap::{check_overlaps, OverlapError};
+use crate::schedule::free_slots;
+
+/// Partitions are aligned to this many bytes, as modern partitioning tools do.
+pub const ALIGNMENT: u64 = 1 << 20;
+
+/** Shrink a proposed partition (in sectors) so that both ends lie on [`ALIGNMENT`] boundaries.
+
+Returns `None` if no aligned sectors remain.
+Sectors larger than the alignment, or that don't divide it, are aligned to whole sectors only.
+
+```
+# use range_split::disk::align_partition;
+// 512-byte sectors: 1 MiB is 2048 sectors
+assert_eq!(align_partition(&(34..1_000_000), 512), Some(2048..999_424));
+assert_eq!(align_partition(&(34..2000), 512), None);
+```
+**/
+pub fn align_partition(range: &Range<u64>, sector_size: u64) -> Option<Range<u64>> {
+    let align = match sector_size {
+        0 => 1,
+        s if ALIGNMENT.is_multiple_of(s) => ALIGNMENT / s,
+        _ => 1,
+    };
+    let start = range.start.checked_next_multiple_of(align)?;
+    let end = range.end - range.end % align;
+    (start < end).then_some(start..end)
+}
+
+/// A problem with a partition table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartitionError<N> {
+    /// A partition extends outside the device.
+    OutOfBounds { name: N, range: Range<u64> },
+    /// A partition is empty or inverted.
+    Empty { name: N },
+    /// Two partitions overlap.
+    Overlap(OverlapError<N, u64>),
+}
+
+impl<N: fmt::Display> fmt::Display for PartitionError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { name, range } => {
+                write!(f, "partition `{name}` at {range:?} is outside the device")
+            }
+            Self::Empty { name } => write!(f, "partition `{name}` is empty"),
+            Self::Overlap(o) => o.fmt(f),
+        }
+    }
+}
+
+impl<N: fmt::Display + fmt::Debug> std::error::Error for PartitionError<N> {}
+
+/** Check a partition table against the `device`'s usable sectors.
+
+Reports, in input order, every partition that is empty or extends outside the device,
+followed by every overlapping pair as in [`check_overlaps`].
+
+```
+# use range_split::disk::{validate_table, PartitionError};
+let device = 2048..100_000;
+assert_eq!(validate_table(&device, &[("efi", 2048..10_000), ("root", 10_000..100_000)]), Ok(()));
+let errors = validate_table(&device, &[("efi", 2048..10_000), ("root", 9_000..200_000)]).unwrap_err();
+assert_eq!(errors.len(), 2);
+```
+**/
+pub fn validate_table<N: Clone>(
+    device: &Range<u64>,
+    partitions: &[(N, Range<u64>)],
+) -> Result<(), Vec<PartitionError<N>>> {
+    let mut errors: Vec<PartitionError<N>> = partitions
+        .iter()
+        .filter_map(|(name, range)| {
+            if range.start >= range.end {
+                Some(PartitionError::Empty { name: name.clone() })
+            } else if range.start < device.start || range.end > device.end {
+                Some(PartitionError::OutOfBounds {
+                    name: name.clone(),
+                    range: range.clone(),
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    if let Err(overlaps) = check_overlaps(partitions) {
+        errors.extend(overlaps.into_iter().map(PartitionError::Overlap));
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/** List the maximal unpartitioned gaps of the `device`, with their sizes, in order.
+
+```
+# use range_split::disk::free_gaps;
+let gaps = free_gaps(&(0..1000), &[500..600, 0..100]);
+assert_eq!(gaps, vec![(100..500, 400), (600..1000, 400)]);
+```
+**/
+pub fn free_gaps(device: &Range<u64>, partitions: &[Range<u64>]) -> Vec<(Range<u64>, u64)> {
+    free_slots(partitions, device, None)
+        .into_iter()
+        .map(|gap| {
+            let len = gap.end - gap.start;
+            (gap, len)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_already_aligned() {
+        assert_eq!(align_partition(&(2048..4096), 512), Some(2048..4096))
+    }
+
+    #[test]
+    fn align_4k_sectors() {
+        assert_eq!(align_partition(&(1..1000), 4096), Some(256..768))
+    }
+
+    #[test]
+    fn align_near_max() {
+        assert_eq!(align_partition(&(u64::MAX - 10..u64::MAX), 512), None)
+    }
+
+    #[test]
+    fn validate_empty_and_out_of_bounds() {
+        let errors = validate_table(&(100..1000), &[("a", 50..150), ("b", 300..300)]).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                PartitionError::OutOfBounds {
+                    name: "a",
+                    range: 50..150
+                },
+                PartitionError::Empty { name: "b" },
+            ]
+        );
+    }
+
+    #[test]
+    fn gaps_of_full_disk() {
+        assert_eq!(free_gaps(&(0..10), &[0..5, 5..10]), vec![])
+    }
+}
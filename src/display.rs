@@ -0,0 +1,160 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Readable formatting for lists of ranges, such as the runs of a set.
+
+use core::fmt;
+use core::ops::Range;
+
+/// How [`DisplayRanges`] and [`DisplayMap`] write each range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// Interval notation: `[0, 3) ∪ [5, 9)`, with `∅` for no ranges.
+    #[default]
+    Math,
+    /// Rust range syntax: `0..3, 5..9`.
+    Rust,
+}
+
+impl Style {
+    fn range<T: fmt::Display>(self, f: &mut fmt::Formatter<'_>, r: &Range<T>) -> fmt::Result {
+        match self {
+            Style::Math => write!(f, "[{}, {})", r.start, r.end),
+            Style::Rust => write!(f, "{}..{}", r.start, r.end),
+        }
+    }
+
+    fn separator(self) -> &'static str {
+        match self {
+            Style::Math => " ∪ ",
+            Style::Rust => ", ",
+        }
+    }
+
+    fn empty(self) -> &'static str {
+        match self {
+            Style::Math => "∅",
+            Style::Rust => "",
+        }
+    }
+}
+
+/** Formats a list of ranges.
+
+```
+# use range_split::display::{DisplayRanges, Style};
+let runs = [0..3, 5..9];
+assert_eq!(DisplayRanges::new(&runs).to_string(), "[0, 3) ∪ [5, 9)");
+assert_eq!(DisplayRanges::new(&runs).style(Style::Rust).to_string(), "0..3, 5..9");
+```
+**/
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayRanges<'a, T> {
+    ranges: &'a [Range<T>],
+    style: Style,
+}
+
+impl<'a, T> DisplayRanges<'a, T> {
+    pub fn new(ranges: &'a [Range<T>]) -> Self {
+        Self {
+            ranges,
+            style: Style::default(),
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for DisplayRanges<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ranges.is_empty() {
+            return f.write_str(self.style.empty());
+        }
+        for (i, r) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.style.separator())?;
+            }
+            self.style.range(f, r)?;
+        }
+        Ok(())
+    }
+}
+
+/** Formats a list of ranges with values. The alternate form (`{:#}`) shows the values too.
+
+```
+# use range_split::display::{DisplayMap, Style};
+let map = [(0..3, "a"), (5..9, "b")];
+assert_eq!(format!("{}", DisplayMap::new(&map)), "[0, 3) ∪ [5, 9)");
+assert_eq!(format!("{:#}", DisplayMap::new(&map)), "[0, 3) → a, [5, 9) → b");
+assert_eq!(format!("{:#}", DisplayMap::new(&map).style(Style::Rust)), "0..3 => a, 5..9 => b");
+```
+**/
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayMap<'a, T, V> {
+    entries: &'a [(Range<T>, V)],
+    style: Style,
+}
+
+impl<'a, T, V> DisplayMap<'a, T, V> {
+    pub fn new(entries: &'a [(Range<T>, V)]) -> Self {
+        Self {
+            entries,
+            style: Style::default(),
+        }
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self { style, ..self }
+    }
+}
+
+impl<T: fmt::Display, V: fmt::Display> fmt::Display for DisplayMap<'_, T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.entries.is_empty() {
+            return f.write_str(self.style.empty());
+        }
+        let (separator, arrow) = match (f.alternate(), self.style) {
+            (false, style) => (style.separator(), None),
+            (true, Style::Math) => (", ", Some(" → ")),
+            (true, Style::Rust) => (", ", Some(" => ")),
+        };
+        for (i, (r, v)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                f.write_str(separator)?;
+            }
+            self.style.range(f, r)?;
+            if let Some(arrow) = arrow {
+                write!(f, "{arrow}{v}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let none: [Range<u8>; 0] = [];
+        assert_eq!(DisplayRanges::new(&none).to_string(), "∅");
+        assert_eq!(DisplayRanges::new(&none).style(Style::Rust).to_string(), "");
+    }
+
+    #[test]
+    fn negative_endpoints() {
+        let runs = [-5..-1, 2..4];
+        assert_eq!(DisplayRanges::new(&runs).to_string(), "[-5, -1) ∪ [2, 4)");
+    }
+
+    #[test]
+    fn map_rust_style() {
+        let map = [(0..1, 10), (1..2, 20)];
+        let shown = DisplayMap::new(&map).style(Style::Rust);
+        assert_eq!(format!("{shown}"), "0..1, 1..2");
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Mapping tracked ranges through insertions and deletions in the underlying sequence,
+//! as an editor does with selections, diagnostics, or highlights after a text change.
+
+use core::ops::{Add, Range, Sub};
+
+use crate::map::RangeMap;
+use crate::storage::{RangeSet, RunStorage};
+use crate::Split;
+
+/// A change to the underlying sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit<T> {
+    /// `len` new elements were inserted before position `at`.
+    Insert { at: T, len: T },
+    /// The elements in the range were removed.
+    Delete(Range<T>),
+}
+
+impl<T> Edit<T> {
+    pub fn insert(at: T, len: T) -> Self {
+        Self::Insert { at, len }
+    }
+
+    pub fn delete(range: Range<T>) -> Self {
+        Self::Delete(range)
+    }
+}
+
+/** Map `range` through `edit`, giving up to two pieces.
+
+* Ranges wholly before the edit are unchanged and ranges after it are shifted.
+  An insertion exactly at either end of `range` does not extend it.
+* An insertion strictly inside `range` splits it around the inserted elements.
+* A deletion removes the overlapping part of `range`, so it may shrink or vanish.
+
+Empty ranges are dropped.
+
+```
+# use range_split::edit::{transform, Edit};
+assert_eq!(transform(&(2..8), &Edit::insert(5, 3)), (Some(2..5), Some(8..11)));
+assert_eq!(transform(&(2..8), &Edit::delete(6..10)), (Some(2..6), None));
+assert_eq!(transform(&(2..8), &Edit::delete(0..4)), (Some(0..4), None));
+assert_eq!(transform(&(2..8), &Edit::delete(1..9)), (None, None));
+```
+**/
+pub fn transform<T>(range: &Range<T>, edit: &Edit<T>) -> (Option<Range<T>>, Option<Range<T>>)
+where
+    T: Sized + Ord + Copy + Add<Output = T> + Sub<Output = T>,
+{
+    if range.start >= range.end {
+        return (None, None);
+    }
+    match edit {
+        Edit::Insert { at, len } => {
+            let (at, len) = (*at, *len);
+            if range.end <= at {
+                (Some(range.clone()), None)
+            } else if range.start >= at {
+                (Some(range.start + len..range.end + len), None)
+            } else {
+                (Some(range.start..at), Some(at + len..range.end + len))
+            }
+        }
+        Edit::Delete(del) if del.start >= del.end => (Some(range.clone()), None),
+        Edit::Delete(del) => {
+            let len = del.end - del.start;
+            let (below, _, above) = range.split(del);
+            // Whatever survives above the deletion moves down by its length
+            let above = above.map(|a| a.start - len..a.end - len);
+            let kept = match (below, above) {
+                (Some(b), Some(a)) => Some(b.start..a.end),
+                (b, a) => b.or(a),
+            };
+            (kept, None)
+        }
+    }
+}
+
+/** Map each run of `set` through `edit`. Runs brought together by a deletion are merged.
+
+```
+# use range_split::edit::{transform_all, Edit};
+# use range_split::storage::RangeSet;
+let set: RangeSet<u32> = [0..3, 5..8].into_iter().collect();
+let moved = transform_all(&set, &Edit::delete(3..5));
+assert_eq!(moved.iter().collect::<Vec<_>>(), vec![0..6]);
+```
+**/
+pub fn transform_all<T, S>(set: &RangeSet<T, S>, edit: &Edit<T>) -> RangeSet<T, S>
+where
+    T: Sized + Ord + Copy + Add<Output = T> + Sub<Output = T>,
+    S: RunStorage<T>,
+{
+    set.iter()
+        .flat_map(|r| {
+            let (a, b) = transform(&r, edit);
+            a.into_iter().chain(b)
+        })
+        .collect()
+}
+
+/// Map each entry of `map` through `edit`.
+/// A value whose range is split is cloned into both pieces,
+/// and touching pieces with equal values are merged.
+pub fn transform_values<T, V>(map: &RangeMap<T, V>, edit: &Edit<T>) -> RangeMap<T, V>
+where
+    T: Sized + Ord + Copy + Add<Output = T> + Sub<Output = T>,
+    V: Clone + PartialEq,
+{
+    map.iter()
+        .flat_map(|(r, v)| {
+            let (a, b) = transform(r, edit);
+            a.into_iter().chain(b).map(|piece| (piece, v.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const R: Range<u32> = 10..20;
+
+    #[test]
+    fn insert_before() {
+        assert_eq!(transform(&R, &Edit::insert(3, 4)), (Some(14..24), None))
+    }
+
+    #[test]
+    fn insert_at_ends() {
+        assert_eq!(transform(&R, &Edit::insert(10, 4)), (Some(14..24), None));
+        assert_eq!(transform(&R, &Edit::insert(20, 4)), (Some(10..20), None));
+    }
+
+    #[test]
+    fn delete_inside() {
+        assert_eq!(transform(&R, &Edit::delete(12..15)), (Some(10..17), None))
+    }
+
+    #[test]
+    fn delete_straddling_start() {
+        assert_eq!(transform(&R, &Edit::delete(5..15)), (Some(5..10), None))
+    }
+
+    #[test]
+    fn delete_exactly() {
+        assert_eq!(transform(&R, &Edit::delete(10..20)), (None, None))
+    }
+
+    #[test]
+    fn delete_after() {
+        assert_eq!(transform(&R, &Edit::delete(25..30)), (Some(10..20), None))
+    }
+
+    #[test]
+    fn batch_values() {
+        let items: RangeMap<u32, char> = [(0..4, 'a'), (6..9, 'b')].into_iter().collect();
+        assert_eq!(
+            transform_values(&items, &Edit::insert(2, 10)).into_vec(),
+            vec![(0..2, 'a'), (12..14, 'a'), (16..19, 'b')]
+        );
+        let moved = transform_all(&items.ranges(), &Edit::delete(3..7));
+        assert_eq!(moved.iter().collect::<Vec<_>>(), vec![0..5]);
+    }
+
+    #[test]
+    fn deletion_joins_neighbours() {
+        let items: RangeMap<u32, char> = [(0..3, 'a'), (5..8, 'a'), (10..12, 'b')]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            transform_values(&items, &Edit::delete(3..5)).into_vec(),
+            vec![(0..6, 'a'), (8..10, 'b')]
+        );
+        assert_eq!(
+            transform_values(&items, &Edit::delete(7..10)).into_vec(),
+            vec![(0..3, 'a'), (5..7, 'a'), (7..9, 'b')]
+        );
+    }
+
+    #[test]
+    fn any_storage() {
+        let set: RangeSet<u32, std::collections::BTreeMap<u32, u32>> =
+            [0..4, 6..9].into_iter().collect();
+        let moved = transform_all(&set, &Edit::insert(2, 10));
+        assert_eq!(moved.iter().collect::<Vec<_>>(), vec![0..2, 12..14, 16..19]);
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Checked arithmetic on range endpoints: shifting, scaling, aligning and expanding.
+//!
+//! Each operation returns an error rather than panicking or wrapping
+//! when an endpoint would leave the range of `T`.
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::interval_arith::Integer;
+
+/// Why an endpoint operation failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeArithError {
+    /// An endpoint would overflow (or underflow) its type.
+    Overflow,
+    /// An alignment wasn't positive.
+    InvalidStep,
+}
+
+impl fmt::Display for RangeArithError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeArithError::Overflow => write!(f, "range endpoint overflows"),
+            RangeArithError::InvalidStep => write!(f, "alignment must be positive"),
+        }
+    }
+}
+
+impl std::error::Error for RangeArithError {}
+
+use RangeArithError::*;
+
+/** Move both endpoints of `range` up by `by`.
+
+```
+# use range_split::endpoint::{shift, RangeArithError};
+assert_eq!(shift(&(10..20u8), 5), Ok(15..25));
+assert_eq!(shift(&(10..20u8), 250), Err(RangeArithError::Overflow));
+```
+**/
+pub fn shift<T: Integer>(range: &Range<T>, by: T) -> Result<Range<T>, RangeArithError> {
+    let start = range.start.checked_add(by).ok_or(Overflow)?;
+    Ok(start..range.end.checked_add(by).ok_or(Overflow)?)
+}
+
+/// Move both endpoints of `range` down by `by`.
+pub fn shift_back<T: Integer>(range: &Range<T>, by: T) -> Result<Range<T>, RangeArithError> {
+    let start = range.start.checked_sub(by).ok_or(Overflow)?;
+    Ok(start..range.end.checked_sub(by).ok_or(Overflow)?)
+}
+
+/** Multiply both endpoints of `range` by `factor`, as when converting sectors to bytes.
+A negative `factor` gives an inverted range.
+
+```
+# use range_split::endpoint::scale;
+assert_eq!(scale(&(2..4u64), 512), Ok(1024..2048));
+```
+**/
+pub fn scale<T: Integer>(range: &Range<T>, factor: T) -> Result<Range<T>, RangeArithError> {
+    let start = range.start.checked_mul(factor).ok_or(Overflow)?;
+    Ok(start..range.end.checked_mul(factor).ok_or(Overflow)?)
+}
+
+/// `t` rounded down to a multiple of `align`.
+fn align_down<T: Integer>(t: T, align: T) -> Result<T, RangeArithError> {
+    if align <= T::ZERO {
+        return Err(InvalidStep);
+    }
+    let rem = t.checked_rem_euclid(align).ok_or(Overflow)?;
+    t.checked_sub(rem).ok_or(Overflow)
+}
+
+/// `t` rounded up to a multiple of `align`.
+fn align_up<T: Integer>(t: T, align: T) -> Result<T, RangeArithError> {
+    let down = align_down(t, align)?;
+    if down == t {
+        return Ok(t);
+    }
+    down.checked_add(align).ok_or(Overflow)
+}
+
+/** Grow `range` to multiples of `align` at both ends.
+
+```
+# use range_split::endpoint::{align_outward, RangeArithError};
+assert_eq!(align_outward(&(-5..5i32), 4), Ok(-8..8));
+assert_eq!(align_outward(&(0..255u8), 16), Err(RangeArithError::Overflow));
+assert_eq!(align_outward(&(0..1u8), 0), Err(RangeArithError::InvalidStep));
+```
+**/
+pub fn align_outward<T: Integer>(range: &Range<T>, align: T) -> Result<Range<T>, RangeArithError> {
+    Ok(align_down(range.start, align)?..align_up(range.end, align)?)
+}
+
+/** Shrink `range` to multiples of `align` at both ends.
+If no multiple-aligned range fits, the result is empty; it's at `range.end`
+when there is no multiple of `align` at or above `range.start`.
+
+```
+# use range_split::endpoint::align_inward;
+assert_eq!(align_inward(&(3..21u32), 8), Ok(8..16));
+assert_eq!(align_inward(&(3..7u32), 8), Ok(8..8));
+assert_eq!(align_inward(&(250..255u8), 16), Ok(255..255));
+```
+**/
+pub fn align_inward<T: Integer>(range: &Range<T>, align: T) -> Result<Range<T>, RangeArithError> {
+    let start = match align_up(range.start, align) {
+        Err(Overflow) => return Ok(range.end..range.end),
+        start => start?,
+    };
+    Ok(start..align_down(range.end, align)?.max(start))
+}
+
+/** Move the start of `range` down by `before` and its end up by `after`.
+
+```
+# use range_split::endpoint::{expand, RangeArithError};
+assert_eq!(expand(&(10..20u32), 5, 1), Ok(5..21));
+assert_eq!(expand(&(3..20u32), 5, 1), Err(RangeArithError::Overflow));
+```
+**/
+pub fn expand<T: Integer>(
+    range: &Range<T>,
+    before: T,
+    after: T,
+) -> Result<Range<T>, RangeArithError> {
+    let start = range.start.checked_sub(before).ok_or(Overflow)?;
+    Ok(start..range.end.checked_add(after).ok_or(Overflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_back_underflow() {
+        assert_eq!(shift_back(&(5..10u32), 5), Ok(0..5));
+        assert_eq!(shift_back(&(5..10u32), 6), Err(Overflow));
+        assert_eq!(shift_back(&(i8::MIN + 1..0), 2), Err(Overflow));
+    }
+
+    #[test]
+    fn scale_overflow() {
+        assert_eq!(scale(&(0..u64::MAX / 2), 2), Ok(0..u64::MAX - 1));
+        assert_eq!(scale(&(0..u64::MAX / 2 + 1), 2), Err(Overflow));
+    }
+
+    #[test]
+    fn align_negative() {
+        assert_eq!(align_inward(&(-7..-1i64), 4), Ok(-4..-4));
+        assert_eq!(align_outward(&(i8::MIN..0), 8), Ok(i8::MIN..0));
+        assert_eq!(align_outward(&(0..1i8), -8), Err(InvalidStep));
+    }
+
+    #[test]
+    fn align_at_the_top() {
+        assert_eq!(align_outward(&(0..240u8), 16), Ok(0..240));
+        assert_eq!(align_inward(&(250..255u8), 16), Ok(255..255));
+        assert_eq!(align_inward(&(i8::MAX..i8::MAX), 2), Ok(i8::MAX..i8::MAX));
+        assert_eq!(align_inward(&(250..255u8), 0), Err(InvalidStep));
+    }
+}
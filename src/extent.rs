@@ -0,0 +1,168 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Maps of the allocated extents of a sparse file or object.
+
+use core::ops::Range;
+
+use crate::map::RangeMap;
+use crate::Split;
+
+/// A piece of the answer to [`ExtentMap::query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment<'a, I> {
+    /// Part of an extent. `extent` is the whole extent, so that the offset of `range`
+    /// within it is `range.start - extent.start`.
+    Data {
+        range: Range<u64>,
+        extent: &'a Range<u64>,
+        info: &'a I,
+    },
+    /// Unallocated space, which reads as zeroes.
+    Hole(Range<u64>),
+}
+
+/** The allocated extents of a sparse file, each with some information `I`
+(such as its physical location), kept in a [`RangeMap`].
+As there, touching extents with equal information are merged.
+
+Usage:
+```
+# use range_split::extent::{ExtentMap, Segment};
+let mut map = ExtentMap::new();
+assert!(map.insert(0..4096, "block 7"));
+assert!(map.insert(8192..12288, "block 3"));
+assert!(!map.insert(4000..5000, "overlaps"));
+
+let segments = map.query(&(1000..9000));
+assert_eq!(segments, vec![
+    Segment::Data { range: 1000..4096, extent: &(0..4096), info: &"block 7" },
+    Segment::Hole(4096..8192),
+    Segment::Data { range: 8192..9000, extent: &(8192..12288), info: &"block 3" },
+]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtentMap<I> {
+    extents: RangeMap<u64, I>,
+}
+
+impl<I> Default for ExtentMap<I> {
+    fn default() -> Self {
+        Self {
+            extents: RangeMap::default(),
+        }
+    }
+}
+
+impl<I: Clone + PartialEq> ExtentMap<I> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an extent. Returns `false` (and does nothing) if it's empty or overlaps an existing extent.
+    pub fn insert(&mut self, range: impl Into<Range<u64>>, info: I) -> bool {
+        let range = range.into();
+        if range.start >= range.end || !self.extents.overlapping(&range).is_empty() {
+            return false;
+        }
+        self.extents.insert(range, info);
+        true
+    }
+
+    /// Decompose `range` into the data and holes that exactly tile it, in order.
+    pub fn query(&self, range: &Range<u64>) -> Vec<Segment<'_, I>> {
+        let mut out = Vec::new();
+        if range.start >= range.end {
+            return out;
+        }
+        let mut cursor = range.start;
+        for (extent, info) in self.extents.overlapping(range) {
+            let data = range.split(extent).1.expect("extent overlaps range");
+            if cursor < data.start {
+                out.push(Segment::Hole(cursor..data.start));
+            }
+            cursor = data.end;
+            out.push(Segment::Data {
+                range: data,
+                extent,
+                info,
+            });
+        }
+        if cursor < range.end {
+            out.push(Segment::Hole(cursor..range.end));
+        }
+        out
+    }
+
+    /// The extents, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &(Range<u64>, I)> {
+        self.extents.iter()
+    }
+
+    /// The underlying map.
+    pub fn map(&self) -> &RangeMap<u64, I> {
+        &self.extents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> ExtentMap<u32> {
+        let mut map = ExtentMap::new();
+        map.insert(10..20, 1);
+        map.insert(20..30, 2);
+        map.insert(50..60, 3);
+        map
+    }
+
+    #[test]
+    fn all_hole() {
+        assert_eq!(map().query(&(30..50)), vec![Segment::Hole(30..50)])
+    }
+
+    #[test]
+    fn adjacent_extents() {
+        let m = map();
+        let ranges: Vec<_> = m
+            .query(&(15..25))
+            .into_iter()
+            .map(|s| match s {
+                Segment::Data { range, .. } => range,
+                Segment::Hole(_) => panic!("unexpected hole"),
+            })
+            .collect();
+        assert_eq!(ranges, vec![15..20, 20..25]);
+    }
+
+    #[test]
+    fn tiles_exactly() {
+        let m = map();
+        let segments = m.query(&(0..100));
+        assert_eq!(segments.len(), 6);
+        let mut cursor = 0;
+        for s in segments {
+            let r = match s {
+                Segment::Data { range, .. } | Segment::Hole(range) => range,
+            };
+            assert_eq!(r.start, cursor);
+            cursor = r.end;
+        }
+        assert_eq!(cursor, 100);
+    }
+
+    #[test]
+    fn equal_neighbours_merge() {
+        let mut m = map();
+        assert!(m.insert(30..40, 2));
+        assert_eq!(m.map().get(35), Some(&2));
+        assert_eq!(m.iter().nth(1), Some(&(20..40, 2)));
+    }
+
+    #[test]
+    fn insert_rejects_empty() {
+        assert!(!map().insert(40..40, 4))
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! A C interface to splitting, intersection, and range sets over `u64`.
+//!
+//! The matching header is `include/range_split.h`, which the tests check against this module.
+//! Build a library to link against with e.g.
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+
+use core::ops::Range;
+
+use crate::storage::RangeSet;
+use crate::{RangeAssign, Split};
+
+/// The half-open range `[start, end)`: `range_t`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RangeT {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl From<RangeT> for Range<u64> {
+    fn from(r: RangeT) -> Self {
+        r.start..r.end
+    }
+}
+
+impl From<Range<u64>> for RangeT {
+    fn from(r: Range<u64>) -> Self {
+        Self {
+            start: r.start,
+            end: r.end,
+        }
+    }
+}
+
+/// The pieces of a split: `range_pieces_t`. Each is only meaningful if its `has_` flag is set.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RangePiecesT {
+    pub below: RangeT,
+    pub inter: RangeT,
+    pub above: RangeT,
+    pub has_below: bool,
+    pub has_inter: bool,
+    pub has_above: bool,
+}
+
+/// An opaque range set: `range_set_t`.
+pub struct RangeSetT(RangeSet<u64>);
+
+/// Split `a` by `b` into the parts below, inside, and above `b`, as [`Split::split`] does.
+///
+/// # Safety
+/// `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn range_split(a: RangeT, b: RangeT, out: *mut RangePiecesT) {
+    let (below, inter, above) = Range::from(a).split(&b.into());
+    let piece =
+        |p: Option<Range<u64>>| (p.clone().map(RangeT::from).unwrap_or_default(), p.is_some());
+    let ((below, has_below), (inter, has_inter), (above, has_above)) =
+        (piece(below), piece(inter), piece(above));
+    out.write(RangePiecesT {
+        below,
+        inter,
+        above,
+        has_below,
+        has_inter,
+        has_above,
+    });
+}
+
+/// Write the intersection of `a` and `b` to `out`. Returns whether it's non-empty.
+///
+/// # Safety
+/// `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn range_intersect(a: RangeT, b: RangeT, out: *mut RangeT) -> bool {
+    let mut r = Range::from(a);
+    let nonempty = r.intersect_assign(&b.into());
+    out.write(r.into());
+    nonempty
+}
+
+/// A new, empty set, to be released with [`range_set_free`].
+#[no_mangle]
+pub extern "C" fn range_set_new() -> *mut RangeSetT {
+    Box::into_raw(Box::new(RangeSetT(RangeSet::new())))
+}
+
+/// Release a set from [`range_set_new`]. Null is ignored.
+///
+/// # Safety
+/// `set` must be null or from [`range_set_new`], and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn range_set_free(set: *mut RangeSetT) {
+    if !set.is_null() {
+        drop(Box::from_raw(set));
+    }
+}
+
+/// Add `[start, end)` to `set`.
+///
+/// # Safety
+/// `set` must be a live set from [`range_set_new`].
+#[no_mangle]
+pub unsafe extern "C" fn range_set_insert(set: *mut RangeSetT, start: u64, end: u64) {
+    (*set).0.insert(start..end);
+}
+
+/// Remove `[start, end)` from `set`.
+///
+/// # Safety
+/// `set` must be a live set from [`range_set_new`].
+#[no_mangle]
+pub unsafe extern "C" fn range_set_remove(set: *mut RangeSetT, start: u64, end: u64) {
+    (*set).0.remove(&(start..end));
+}
+
+/// Whether `t` is in `set`.
+///
+/// # Safety
+/// `set` must be a live set from [`range_set_new`].
+#[no_mangle]
+pub unsafe extern "C" fn range_set_contains(set: *const RangeSetT, t: u64) -> bool {
+    (*set).0.contains(t)
+}
+
+/// Number of runs in `set`.
+///
+/// # Safety
+/// `set` must be a live set from [`range_set_new`].
+#[no_mangle]
+pub unsafe extern "C" fn range_set_len(set: *const RangeSetT) -> usize {
+    (*set).0.len()
+}
+
+/// Write the `i`th run of `set` to `out`. Returns `false`, writing nothing, if there's no such run.
+///
+/// # Safety
+/// `set` must be a live set from [`range_set_new`], and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn range_set_get(set: *const RangeSetT, i: usize, out: *mut RangeT) -> bool {
+    match (*set).0.storage().get(i) {
+        Some(r) => {
+            out.write(r.clone().into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(start: u64, end: u64) -> RangeT {
+        RangeT { start, end }
+    }
+
+    #[test]
+    fn split_through_c_abi() {
+        let mut out = RangePiecesT::default();
+        unsafe { range_split(r(0, 10), r(3, 6), &mut out) };
+        assert_eq!(
+            (out.below, out.inter, out.above),
+            (r(0, 3), r(3, 6), r(6, 10))
+        );
+        assert!(out.has_below && out.has_inter && out.has_above);
+
+        unsafe { range_split(r(0, 10), r(20, 30), &mut out) };
+        assert_eq!(
+            (out.has_below, out.has_inter, out.has_above),
+            (true, false, false)
+        );
+    }
+
+    #[test]
+    fn intersect_through_c_abi() {
+        let mut out = RangeT::default();
+        assert!(unsafe { range_intersect(r(0, 10), r(5, 20), &mut out) });
+        assert_eq!(out, r(5, 10));
+        assert!(!unsafe { range_intersect(r(0, 10), r(15, 20), &mut out) });
+    }
+
+    #[test]
+    fn set_lifecycle() {
+        let set = range_set_new();
+        let mut out = RangeT::default();
+        unsafe {
+            range_set_insert(set, 0, 10);
+            range_set_insert(set, 20, 30);
+            range_set_remove(set, 5, 25);
+            assert_eq!(range_set_len(set), 2);
+            assert!(range_set_contains(set, 4));
+            assert!(!range_set_contains(set, 5));
+            assert!(range_set_get(set, 1, &mut out));
+            assert_eq!(out, r(25, 30));
+            assert!(!range_set_get(set, 2, &mut out));
+            range_set_free(set);
+            range_set_free(core::ptr::null_mut());
+        }
+    }
+
+    /// The C spelling of a Rust type used across the interface.
+    fn c_type(rust: &str) -> String {
+        if let Some(inner) = rust.strip_prefix("*mut ") {
+            return format!("{} *", c_type(inner));
+        }
+        if let Some(inner) = rust.strip_prefix("*const ") {
+            return format!("const {} *", c_type(inner));
+        }
+        match rust {
+            "u64" => "uint64_t",
+            "usize" => "size_t",
+            "bool" => "bool",
+            "RangeT" => "range_t",
+            "RangePiecesT" => "range_pieces_t",
+            "RangeSetT" => "range_set_t",
+            other => panic!("no C type for {other}"),
+        }
+        .to_string()
+    }
+
+    /// `ty name`, or `ty *name` for pointers, as the header writes them.
+    fn c_decl(ty: &str, name: &str) -> String {
+        if ty.ends_with('*') {
+            format!("{ty}{name}")
+        } else {
+            format!("{ty} {name}")
+        }
+    }
+
+    /// The header declarations this module's exports and `#[repr(C)]` types call for,
+    /// generated from the source.
+    fn expected_declarations() -> Vec<String> {
+        let source = include_str!("ffi.rs");
+        let source = &source[..source.find("#[cfg(test)]").unwrap()];
+        let mut lines = source.lines().map(str::trim);
+        let mut out = Vec::new();
+        while let Some(line) = lines.next() {
+            if line == "#[no_mangle]" {
+                let mut sig = String::new();
+                for l in lines.by_ref() {
+                    sig.push_str(l);
+                    if l.ends_with('{') {
+                        break;
+                    }
+                }
+                let sig = sig.split("fn ").nth(1).unwrap().trim_end_matches('{');
+                let (name, rest) = sig.split_once('(').unwrap();
+                let (args, ret) = rest.rsplit_once(')').unwrap();
+                let args: Vec<String> = args
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|a| !a.is_empty())
+                    .map(|a| {
+                        let (n, t) = a.split_once(": ").unwrap();
+                        c_decl(&c_type(t), n)
+                    })
+                    .collect();
+                let ret = ret
+                    .trim()
+                    .strip_prefix("-> ")
+                    .map_or("void".to_string(), |r| c_type(r.trim()));
+                let args = if args.is_empty() {
+                    "void".to_string()
+                } else {
+                    args.join(", ")
+                };
+                out.push(format!("{}({args});", c_decl(&ret, name)));
+            } else if line == "#[repr(C)]" {
+                let def = lines.find(|l| l.starts_with("pub struct")).unwrap();
+                let name = c_type(def.split_whitespace().nth(2).unwrap());
+                let mut body = format!("typedef struct {name} {{\n");
+                for field in lines.by_ref().take_while(|l| *l != "}") {
+                    let (n, t) = field
+                        .trim_start_matches("pub ")
+                        .trim_end_matches(',')
+                        .split_once(": ")
+                        .unwrap();
+                    body.push_str(&format!("    {};\n", c_decl(&c_type(t), n)));
+                }
+                out.push(format!("{body}}} {name};"));
+            } else if let Some(opaque) = line
+                .strip_prefix("pub struct ")
+                .and_then(|l| l.split_once('('))
+            {
+                let name = c_type(opaque.0);
+                out.push(format!("typedef struct {name} {name};"));
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn header_matches_source() {
+        let header = include_str!("../include/range_split.h");
+        let expected = expected_declarations();
+        for decl in &expected {
+            assert!(header.contains(decl.as_str()), "header lacks:\n{decl}");
+        }
+        // Nor does the header declare anything the source doesn't
+        let functions = header
+            .lines()
+            .filter(|l| !l.starts_with(' ') && l.ends_with(");"))
+            .count();
+        let typedefs = header.matches("typedef struct").count();
+        assert_eq!(functions + typedefs, expected.len());
+    }
+
+    #[test]
+    fn header_layout_matches() {
+        use core::mem::{align_of, offset_of, size_of};
+        let checks = [
+            ("sizeof(range_t)", size_of::<RangeT>()),
+            ("_Alignof(range_t)", align_of::<RangeT>()),
+            ("offsetof(range_t, end)", offset_of!(RangeT, end)),
+            ("sizeof(range_pieces_t)", size_of::<RangePiecesT>()),
+            ("_Alignof(range_pieces_t)", align_of::<RangePiecesT>()),
+            (
+                "offsetof(range_pieces_t, inter)",
+                offset_of!(RangePiecesT, inter),
+            ),
+            (
+                "offsetof(range_pieces_t, above)",
+                offset_of!(RangePiecesT, above),
+            ),
+            (
+                "offsetof(range_pieces_t, has_below)",
+                offset_of!(RangePiecesT, has_below),
+            ),
+            (
+                "offsetof(range_pieces_t, has_inter)",
+                offset_of!(RangePiecesT, has_inter),
+            ),
+            (
+                "offsetof(range_pieces_t, has_above)",
+                offset_of!(RangePiecesT, has_above),
+            ),
+        ];
+        let mut c = String::from("#include <stddef.h>\n#include \"range_split.h\"\n");
+        for (expr, value) in checks {
+            c.push_str(&format!("_Static_assert({expr} == {value}, \"{expr}\");\n"));
+        }
+
+        let dir = std::env::temp_dir().join(format!("range_split_header_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("layout.c");
+        std::fs::write(&file, c).unwrap();
+        let include = concat!(env!("CARGO_MANIFEST_DIR"), "/include");
+        let compiled = std::process::Command::new("cc")
+            .args([
+                "-std=c11",
+                "-fsyntax-only",
+                "-Wall",
+                "-Werror",
+                "-I",
+                include,
+            ])
+            .arg(&file)
+            .output();
+        std::fs::remove_dir_all(&dir).unwrap();
+        // Without a C compiler there's nothing to check against
+        let Ok(compiled) = compiled else { return };
+        assert!(
+            compiled.status.success(),
+            "{}",
+            String::from_utf8_lossy(&compiled.stderr)
+        );
+    }
+}
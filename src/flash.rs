@@ -0,0 +1,120 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Planning erases for sector-erased flash memory.
+
+use core::ops::Range;
+
+use crate::grid::split_periodic;
+use crate::storage::{RangeSet, RunStorage};
+use crate::Split;
+
+/// The steps needed to write a range of flash, from [`erase_plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErasePlan {
+    /// The sectors to erase, in order.
+    pub erase: Vec<Range<u64>>,
+    /// Existing contents to read before erasing and write back afterward:
+    /// the preserved parts of the erased sectors that lie outside the write.
+    pub read_modify_write: RangeSet<u64>,
+    /// The range written with new contents.
+    pub write: Range<u64>,
+}
+
+/** Plan writing `write_range` to flash erased in sectors of `sector_size` bytes,
+keeping the contents of `preserve`.
+
+Usage:
+```
+# use range_split::flash::erase_plan;
+# use range_split::storage::RangeSet;
+let preserve: RangeSet<u64> = [0x0..0x1200, 0x2F00..0x3000].into_iter().collect();
+let plan = erase_plan(&(0x1100..0x2100), 0x1000, &preserve);
+assert_eq!(plan.erase, vec![0x1000..0x2000, 0x2000..0x3000]);
+assert_eq!(
+    plan.read_modify_write.iter().collect::<Vec<_>>(),
+    vec![0x1000..0x1100, 0x2F00..0x3000]
+);
+assert_eq!(plan.write, 0x1100..0x2100);
+```
+An empty `write_range` needs no steps. Panics if `sector_size` is zero.
+**/
+pub fn erase_plan<S: RunStorage<u64>>(
+    write_range: &Range<u64>,
+    sector_size: u64,
+    preserve: &RangeSet<u64, S>,
+) -> ErasePlan {
+    assert!(sector_size > 0, "sector size must be positive");
+    if write_range.start >= write_range.end {
+        return ErasePlan {
+            erase: vec![],
+            read_modify_write: RangeSet::new(),
+            write: write_range.clone(),
+        };
+    }
+    let first = write_range.start - write_range.start % sector_size;
+    let last = write_range
+        .end
+        .div_ceil(sector_size)
+        .saturating_mul(sector_size);
+    let erase: Vec<Range<u64>> = split_periodic(&(first..last), 0, sector_size).collect();
+
+    let (below, _, above) = (first..last).split(write_range);
+    let read_modify_write = below
+        .into_iter()
+        .chain(above)
+        .flat_map(|outside| {
+            let kept = preserve.storage().overlapping(&outside);
+            kept.into_iter().filter_map(move |k| k.split(&outside).1)
+        })
+        .collect();
+
+    ErasePlan {
+        erase,
+        read_modify_write,
+        write: write_range.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<u64>]) -> RangeSet<u64> {
+        ranges.iter().cloned().collect()
+    }
+
+    fn rmw(plan: &ErasePlan) -> Vec<Range<u64>> {
+        plan.read_modify_write.iter().collect()
+    }
+
+    #[test]
+    fn aligned_write_needs_no_rmw() {
+        let preserve = set(&[0x0..0x8000, 0x8000..0x10000]);
+        let plan = erase_plan(&(0x1000..0x3000), 0x1000, &preserve);
+        assert_eq!(plan.erase, vec![0x1000..0x2000, 0x2000..0x3000]);
+        assert!(plan.read_modify_write.is_empty());
+    }
+
+    #[test]
+    fn unpreserved_flanks_are_dropped() {
+        let plan = erase_plan(&(0x10..0x20), 0x100, &set(&[]));
+        assert_eq!(plan.erase, vec![0x0..0x100]);
+        assert!(plan.read_modify_write.is_empty());
+        assert_eq!(plan.write, 0x10..0x20);
+    }
+
+    #[test]
+    fn preserved_both_sides_within_one_sector() {
+        let plan = erase_plan(&(0x40..0x80), 0x100, &set(&[0x20..0x50, 0x70..0x200]));
+        assert_eq!(rmw(&plan), vec![0x20..0x40, 0x80..0x100]);
+    }
+
+    #[test]
+    fn empty_write() {
+        assert_eq!(
+            erase_plan(&(5..5), 0x100, &set(&[0..4, 6..10])).erase,
+            vec![]
+        )
+    }
+}
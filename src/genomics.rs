@@ -0,0 +1,641 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Genomic coordinates, with distinct types for the two common conventions
+//! so that off-by-one conversions happen in exactly one place.
+
+use core::fmt;
+use core::ops::Range;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use crate::keyed::KeyedRangeSet;
+use crate::map::RangeMap;
+
+/** An interval in BED convention: 0-based, half-open.
+
+```
+# use range_split::genomics::{Bed, Gff};
+// The first ten bases of a chromosome
+let bed = Bed::new(0, 10).unwrap();
+assert_eq!(Gff::from(bed), Gff::new(1, 10).unwrap());
+assert_eq!(bed.range(), 0..10);
+```
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bed {
+    start: u64,
+    end: u64,
+}
+
+impl Bed {
+    /// `None` if `start > end`, or if `start` is `u64::MAX`,
+    /// which has no 1-based [`Gff`] equivalent.
+    pub fn new(start: u64, end: u64) -> Option<Self> {
+        (start <= end && start < u64::MAX).then_some(Self { start, end })
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// The 0-based half-open range of positions.
+    pub fn range(&self) -> Range<u64> {
+        self.start..self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/** An interval in GFF/VCF convention: 1-based, inclusive.
+
+A zero-length interval (such as an insertion point) has `end == start - 1`,
+so conversion to and from [`Bed`] is lossless.
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Gff {
+    start: u64,
+    end: u64,
+}
+
+impl Gff {
+    /// `None` if `start` is 0 or `end < start - 1`.
+    pub fn new(start: u64, end: u64) -> Option<Self> {
+        (start >= 1 && end >= start - 1).then_some(Self { start, end })
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - (self.start - 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Gff> for Bed {
+    fn from(gff: Gff) -> Self {
+        Self {
+            start: gff.start - 1,
+            end: gff.end,
+        }
+    }
+}
+
+impl From<Bed> for Gff {
+    fn from(bed: Bed) -> Self {
+        Self {
+            start: bed.start + 1,
+            end: bed.end,
+        }
+    }
+}
+
+/// The strand of a [`BedRecord`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strand {
+    Forward,
+    Reverse,
+}
+
+/// One line of a BED3 to BED6 file. Columns past the sixth are not kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BedRecord {
+    pub chrom: String,
+    pub interval: Bed,
+    pub name: Option<String>,
+    pub score: Option<u32>,
+    pub strand: Option<Strand>,
+}
+
+/// A failure reading BED data.
+#[derive(Debug)]
+pub enum BedError {
+    Io(io::Error),
+    /// A malformed line, numbered from 1.
+    Parse {
+        line: usize,
+        message: &'static str,
+    },
+}
+
+impl fmt::Display for BedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for BedError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/** Streams [`BedRecord`]s from tab-separated BED text.
+
+Blank lines, comments, and `track`/`browser` lines are skipped.
+A `.` in the name, score, or strand column means the column is absent.
+
+```
+# use range_split::genomics::{BedReader, ChromSet, Strand};
+let bed = "track name=peaks\nchr1\t100\t200\tpeak1\t500\t-\nchr1\t150\t300\n";
+let records: Vec<_> = BedReader::new(bed.as_bytes()).collect::<Result<_, _>>().unwrap();
+assert_eq!(records[0].name.as_deref(), Some("peak1"));
+assert_eq!(records[0].strand, Some(Strand::Reverse));
+assert_eq!(records[1].score, None);
+
+let set = ChromSet::read_bed(bed.as_bytes()).unwrap();
+assert_eq!(set.get("chr1"), &[100..300]);
+```
+**/
+pub struct BedReader<R> {
+    lines: io::Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> BedReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for BedReader<R> {
+    type Item = Result<BedRecord, BedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let text = match self.lines.next()? {
+                Ok(text) => text,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.line += 1;
+            let trimmed = text.trim_end();
+            let header = matches!(trimmed.split_whitespace().next(), Some("track" | "browser"));
+            if trimmed.is_empty() || trimmed.starts_with('#') || header {
+                continue;
+            }
+            return Some(parse_record(trimmed).map_err(|message| BedError::Parse {
+                line: self.line,
+                message,
+            }));
+        }
+    }
+}
+
+fn parse_record(line: &str) -> Result<BedRecord, &'static str> {
+    let mut cols = line.split('\t');
+    let mut next = |what| cols.next().ok_or(what);
+    let chrom = next("missing chromosome")?.to_owned();
+    let start = next("missing start")?.parse().map_err(|_| "bad start")?;
+    let end = next("missing end")?.parse().map_err(|_| "bad end")?;
+    if start > end {
+        return Err("start is after end");
+    }
+    let interval = Bed::new(start, end).ok_or("start is out of range")?;
+    fn optional(col: Option<&str>) -> Option<&str> {
+        col.filter(|&c| c != ".")
+    }
+    let name = optional(cols.next()).map(str::to_owned);
+    let score = optional(cols.next())
+        .map(|s| s.parse().map_err(|_| "bad score"))
+        .transpose()?;
+    let strand = match optional(cols.next()) {
+        None => None,
+        Some("+") => Some(Strand::Forward),
+        Some("-") => Some(Strand::Reverse),
+        Some(_) => return Err("bad strand"),
+    };
+    Ok(BedRecord {
+        chrom,
+        interval,
+        name,
+        score,
+        strand,
+    })
+}
+
+/// Write one BED line with `cols` columns, writing absent optional columns as `.`.
+fn write_line(
+    w: &mut impl Write,
+    chrom: &str,
+    interval: &Range<u64>,
+    name: Option<&str>,
+    score: Option<u32>,
+    strand: Option<Strand>,
+    cols: usize,
+) -> io::Result<()> {
+    write!(w, "{chrom}\t{}\t{}", interval.start, interval.end)?;
+    if cols >= 4 {
+        write!(w, "\t{}", name.unwrap_or("."))?;
+    }
+    if cols >= 5 {
+        match score {
+            Some(score) => write!(w, "\t{score}")?,
+            None => write!(w, "\t.")?,
+        }
+    }
+    if cols >= 6 {
+        let strand = match strand {
+            Some(Strand::Forward) => "+",
+            Some(Strand::Reverse) => "-",
+            None => ".",
+        };
+        write!(w, "\t{strand}")?;
+    }
+    writeln!(w)
+}
+
+/// Write `records` as BED text with `cols` columns, from BED3 to BED6:
+/// fewer are taken as 3 and more as 6.
+/// Absent optional columns are written as `.`, and columns past `cols` are left out.
+pub fn write_bed<'a, W: Write>(
+    mut w: W,
+    records: impl IntoIterator<Item = &'a BedRecord>,
+    cols: usize,
+) -> io::Result<()> {
+    for r in records {
+        let (name, range) = (r.name.as_deref(), r.interval.range());
+        write_line(&mut w, &r.chrom, &range, name, r.score, r.strand, cols)?;
+    }
+    Ok(())
+}
+
+/** Sets of positions on each chromosome, kept as sorted, coalesced 0-based runs
+in a [`KeyedRangeSet`].
+
+```
+# use range_split::genomics::{Bed, ChromSet};
+let mut set = ChromSet::new();
+set.insert("chr1", Bed::new(100, 200).unwrap());
+set.insert("chr1", Bed::new(150, 300).unwrap());
+set.insert("chr2", Bed::new(0, 50).unwrap());
+assert_eq!(set.get("chr1"), &[100..300]);
+assert!(set.contains("chr2", 49));
+assert!(!set.contains("chrX", 0));
+
+let mut exons = ChromSet::new();
+exons.insert("chr1", Bed::new(250, 400).unwrap());
+assert_eq!(set.intersect(&exons).get("chr1"), &[250..300]);
+assert_eq!(set.subtract(&exons).get("chr1"), &[100..250]);
+assert_eq!(set.union(&exons).get("chr1"), &[100..400]);
+```
+**/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChromSet {
+    sets: KeyedRangeSet<String, u64>,
+}
+
+impl ChromSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the positions of `interval` on `chrom`.
+    pub fn insert(&mut self, chrom: &str, interval: impl Into<Bed>) {
+        let bed = interval.into();
+        if !bed.is_empty() {
+            self.sets.insert(chrom.to_owned(), bed.range());
+        }
+    }
+
+    /// Whether the 0-based position `pos` on `chrom` is in the set.
+    pub fn contains(&self, chrom: &str, pos: u64) -> bool {
+        self.sets.contains(chrom, pos)
+    }
+
+    /// The 0-based runs on `chrom`, in order.
+    pub fn get(&self, chrom: &str) -> &[Range<u64>] {
+        self.sets.get(chrom).map_or(&[], |set| set.storage())
+    }
+
+    /// The positions in either set, chromosome by chromosome.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            sets: self.sets.union(&other.sets),
+        }
+    }
+
+    /// The positions in both sets on the same chromosome.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            sets: self.sets.intersect(&other.sets),
+        }
+    }
+
+    /// The positions in this set that aren't in `other` on the same chromosome.
+    pub fn subtract(&self, other: &Self) -> Self {
+        Self {
+            sets: self.sets.subtract(&other.sets),
+        }
+    }
+
+    /// The underlying sets, keyed by chromosome.
+    pub fn sets(&self) -> &KeyedRangeSet<String, u64> {
+        &self.sets
+    }
+
+    /// Read the intervals of BED data into a set, ignoring names, scores, and strands
+    /// (which [`ChromMap::read_bed`] keeps).
+    pub fn read_bed(reader: impl BufRead) -> Result<Self, BedError> {
+        let mut set = Self::new();
+        for record in BedReader::new(reader) {
+            let record = record?;
+            set.insert(&record.chrom, record.interval);
+        }
+        Ok(set)
+    }
+
+    /// Write the set as BED3, one line per run.
+    pub fn write_bed(&self, mut w: impl Write) -> io::Result<()> {
+        for (chrom, runs) in self.iter() {
+            for r in runs {
+                writeln!(w, "{chrom}\t{}\t{}", r.start, r.end)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The chromosomes with any positions in the set, in lexicographic order, with their runs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[Range<u64>])> {
+        self.sets
+            .iter()
+            .map(|(c, set)| (c.as_str(), set.storage().as_slice()))
+    }
+}
+
+/// The name, score, and strand columns of a [`BedRecord`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BedAnnotation {
+    pub name: Option<String>,
+    pub score: Option<u32>,
+    pub strand: Option<Strand>,
+}
+
+/** Annotated positions on each chromosome, kept as a [`RangeMap`] of 0-based runs,
+each with the [`BedAnnotation`] of the record it came from.
+
+Where records overlap, the later one wins, and what's left of the earlier one is kept around it.
+Touching runs with equal annotations are merged.
+
+```
+# use range_split::genomics::{BedAnnotation, ChromMap, Strand};
+let bed = "chr1\t100\t200\tgeneA\t.\t+\nchr1\t150\t180\texon\t7\t+\n";
+let map = ChromMap::read_bed(bed.as_bytes()).unwrap();
+assert_eq!(map.get("chr1").len(), 3);
+assert_eq!(map.at("chr1", 160).unwrap().name.as_deref(), Some("exon"));
+assert_eq!(map.at("chr1", 190).unwrap().strand, Some(Strand::Forward));
+assert_eq!(map.at("chr1", 200), None);
+
+let mut out = Vec::new();
+map.write_bed(&mut out, 5).unwrap();
+assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "chr1\t100\t150\tgeneA\t.\nchr1\t150\t180\texon\t7\nchr1\t180\t200\tgeneA\t.\n"
+);
+```
+**/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChromMap {
+    by_chrom: BTreeMap<String, RangeMap<u64, BedAnnotation>>,
+}
+
+impl ChromMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Annotate the positions of `interval` on `chrom`, replacing any annotation they had.
+    pub fn insert(&mut self, chrom: &str, interval: impl Into<Bed>, annotation: BedAnnotation) {
+        let range = interval.into().range();
+        if range.is_empty() {
+            return;
+        }
+        let runs = match self.by_chrom.get_mut(chrom) {
+            Some(runs) => runs,
+            None => self.by_chrom.entry(chrom.to_owned()).or_default(),
+        };
+        runs.insert(range, annotation);
+    }
+
+    /// Add a record, as [`insert`](Self::insert) does.
+    pub fn insert_record(&mut self, record: BedRecord) {
+        let annotation = BedAnnotation {
+            name: record.name,
+            score: record.score,
+            strand: record.strand,
+        };
+        self.insert(&record.chrom, record.interval, annotation);
+    }
+
+    /// The annotation of the 0-based position `pos` on `chrom`, if it has one.
+    pub fn at(&self, chrom: &str, pos: u64) -> Option<&BedAnnotation> {
+        self.by_chrom.get(chrom)?.get(pos)
+    }
+
+    /// The annotated 0-based runs on `chrom`, in order.
+    pub fn get(&self, chrom: &str) -> &[(Range<u64>, BedAnnotation)] {
+        self.by_chrom.get(chrom).map_or(&[], RangeMap::as_slice)
+    }
+
+    /// The positions on each chromosome, without their annotations.
+    pub fn to_set(&self) -> ChromSet {
+        let mut set = ChromSet::new();
+        for (chrom, runs) in self.iter() {
+            for (r, _) in runs {
+                set.insert(chrom, Bed::new(r.start, r.end).expect("runs are 0-based"));
+            }
+        }
+        set
+    }
+
+    /// Read BED data into a map, one record at a time.
+    pub fn read_bed(reader: impl BufRead) -> Result<Self, BedError> {
+        let mut map = Self::new();
+        for record in BedReader::new(reader) {
+            map.insert_record(record?);
+        }
+        Ok(map)
+    }
+
+    /// Write the map as BED text with `cols` columns, one line per run, as [`write_bed`] does.
+    pub fn write_bed(&self, mut w: impl Write, cols: usize) -> io::Result<()> {
+        for (chrom, runs) in self.iter() {
+            for (r, a) in runs {
+                write_line(&mut w, chrom, r, a.name.as_deref(), a.score, a.strand, cols)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The chromosomes with any annotated positions, in lexicographic order, with their runs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[(Range<u64>, BedAnnotation)])> {
+        self.by_chrom
+            .iter()
+            .map(|(c, r)| (c.as_str(), r.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for (s, e) in [(0, 0), (0, 1), (5, 5), (9, 20)] {
+            let bed = Bed::new(s, e).unwrap();
+            assert_eq!(Bed::from(Gff::from(bed)), bed);
+            assert_eq!(Gff::from(bed).len(), bed.len());
+        }
+    }
+
+    #[test]
+    fn single_base() {
+        // Position 1 in GFF is position 0 in BED
+        let gff = Gff::new(1, 1).unwrap();
+        assert_eq!(Bed::from(gff).range(), 0..1);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Bed::new(5, 4), None);
+        assert_eq!(Gff::new(0, 4), None);
+        assert_eq!(Gff::new(5, 3), None);
+        assert!(Gff::new(5, 4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn at_the_top() {
+        assert_eq!(Bed::new(u64::MAX, u64::MAX), None);
+        let bed = Bed::new(u64::MAX - 1, u64::MAX).unwrap();
+        assert_eq!(Gff::from(bed), Gff::new(u64::MAX, u64::MAX).unwrap());
+        assert_eq!(Gff::new(1, u64::MAX).unwrap().len(), u64::MAX);
+        assert!(
+            BedReader::new(format!("chr1\t{0}\t{0}\n", u64::MAX).as_bytes())
+                .next()
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn set_from_gff() {
+        let mut set = ChromSet::new();
+        set.insert("chr1", Gff::new(1, 10).unwrap());
+        set.insert("chr1", Bed::new(10, 20).unwrap());
+        set.insert("chr1", Gff::new(30, 29).unwrap());
+        assert_eq!(set.iter().count(), 1);
+        assert_eq!(set.get("chr1"), core::slice::from_ref(&(0..20)));
+        assert!(set.subtract(&set).sets().is_empty());
+        assert!(set.intersect(&ChromSet::new()).get("chr1").is_empty())
+    }
+
+    #[test]
+    fn bed_parse_errors() {
+        let errors: Vec<_> =
+            BedReader::new("chr1\t5\n# ok\nchr1\t9\t3\nchr1\t1\t2\tx\t.\t?\n".as_bytes())
+                .map(|r| r.unwrap_err().to_string())
+                .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "line 1: missing end",
+                "line 3: start is after end",
+                "line 4: bad strand"
+            ]
+        );
+    }
+
+    #[test]
+    fn bed_headers_need_the_whole_word() {
+        let bed = "track\nbrowser position chr1:1-10\ntrackA\t1\t2\nbrowser_contig\t3\t4\n";
+        let chroms: Vec<_> = BedReader::new(bed.as_bytes())
+            .map(|r| r.unwrap().chrom)
+            .collect();
+        assert_eq!(chroms, vec!["trackA", "browser_contig"]);
+    }
+
+    #[test]
+    fn bed_round_trip() {
+        let bed = "chr1\t0\t10\ta\t.\t+\nchr2\t5\t6\t.\t7\t.\n";
+        let records: Vec<_> = BedReader::new(bed.as_bytes()).map(Result::unwrap).collect();
+        let mut out = Vec::new();
+        write_bed(&mut out, &records, 6).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), bed);
+
+        let mut out = Vec::new();
+        write_bed(&mut out, &records, 4).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "chr1\t0\t10\ta\nchr2\t5\t6\t.\n"
+        );
+    }
+
+    #[test]
+    fn map_overrides_overlaps() {
+        let note = |name: &str| BedAnnotation {
+            name: Some(name.to_owned()),
+            ..Default::default()
+        };
+        let mut map = ChromMap::new();
+        map.insert("chr1", Bed::new(10, 20).unwrap(), note("a"));
+        map.insert("chr1", Bed::new(30, 40).unwrap(), note("b"));
+        map.insert("chr1", Bed::new(15, 35).unwrap(), note("c"));
+        map.insert("chr1", Bed::new(0, 0).unwrap(), note("empty"));
+        let runs: Vec<_> = map
+            .get("chr1")
+            .iter()
+            .map(|(r, a)| (r.clone(), a.name.as_deref().unwrap()))
+            .collect();
+        assert_eq!(runs, vec![(10..15, "a"), (15..35, "c"), (35..40, "b")]);
+        assert_eq!(map.to_set().get("chr1"), core::slice::from_ref(&(10..40)));
+        assert_eq!(map.at("chr1", 9), None);
+        assert!(map.get("chr2").is_empty());
+        // Touching runs with the same annotation merge
+        map.insert("chr1", Bed::new(40, 45).unwrap(), note("b"));
+        assert_eq!(map.get("chr1").last(), Some(&(35..45, note("b"))))
+    }
+
+    #[test]
+    fn bed3_set_round_trip() {
+        let bed = "chr1\t0\t10\nchr1\t20\t30\nchr2\t5\t6\n";
+        let mut out = Vec::new();
+        ChromSet::read_bed(bed.as_bytes())
+            .unwrap()
+            .write_bed(&mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), bed);
+    }
+}
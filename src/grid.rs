@@ -0,0 +1,114 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Splitting ranges against a fixed, repeating grid.
+
+use core::ops::Range;
+
+use crate::interval_arith::Integer;
+
+/** Cut `range` at every boundary `origin + k * period` (for any integer `k`) strictly inside it.
+
+The grid is anchored at `origin`, not at `range.start`,
+so the first and last pieces may be shorter than `period`.
+An empty range yields nothing. Nothing overflows, even for ranges spanning all of `T`.
+
+```
+# use range_split::grid::split_periodic;
+let pieces: Vec<_> = split_periodic(&(7..25), 2, 5).collect();
+assert_eq!(pieces, vec![7..12, 12..17, 17..22, 22..25]);
+```
+Panics if `period` is zero.
+**/
+#[doc(alias = "split_on_grid")]
+pub fn split_periodic<T>(range: &Range<T>, origin: T, period: T) -> impl Iterator<Item = Range<T>>
+where
+    T: Integer,
+{
+    assert!(period > T::ZERO, "period must be positive");
+    // Distances to grid lines can't overflow, unlike `start - origin`
+    let phase = move |t: T| t.checked_rem_euclid(period).expect("period is positive");
+    let origin = phase(origin);
+
+    let mut start = range.start;
+    let end = range.end;
+    core::iter::from_fn(move || {
+        if start >= end {
+            return None;
+        }
+        // Distance from `start` to the next grid line strictly above it
+        let at = phase(start);
+        let offset = if at >= origin {
+            at - origin
+        } else {
+            period - (origin - at)
+        };
+        let gap = period - offset;
+        // If `start + gap` overflows, it's past `end` too
+        let cut = start.checked_add(gap).map_or(end, |c| c.min(end));
+        let piece = start..cut;
+        start = cut;
+        Some(piece)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn periodic(range: Range<u32>, origin: u32, period: u32) -> Vec<Range<u32>> {
+        split_periodic(&range, origin, period).collect()
+    }
+
+    #[test]
+    fn aligned_start() {
+        assert_eq!(periodic(10..30, 0, 10), vec![10..20, 20..30])
+    }
+
+    #[test]
+    fn origin_above_range() {
+        assert_eq!(periodic(3..12, 100, 4), vec![3..4, 4..8, 8..12])
+    }
+
+    #[test]
+    fn within_one_period() {
+        assert_eq!(periodic(11..19, 0, 10), vec![11..19])
+    }
+
+    #[test]
+    fn empty_range() {
+        assert_eq!(periodic(5..5, 0, 10), vec![])
+    }
+
+    #[test]
+    fn near_max() {
+        let pieces: Vec<_> = split_periodic(&(u8::MAX - 10..u8::MAX), 0, 100).collect();
+        assert_eq!(pieces, vec![245..255])
+    }
+
+    #[test]
+    fn negative_grid() {
+        let pieces: Vec<_> = split_periodic(&(-7..6i32), -1, 5).collect();
+        assert_eq!(pieces, vec![-7..-6, -6..-1, -1..4, 4..6])
+    }
+
+    #[test]
+    fn full_signed_domain() {
+        let pieces: Vec<_> = split_periodic(&(i8::MIN..i8::MAX), 0, 50).collect();
+        assert_eq!(pieces.first(), Some(&(-128..-100)));
+        assert_eq!(pieces.last(), Some(&(100..127)));
+        assert!(pieces.windows(2).all(|w| w[0].end == w[1].start));
+
+        let pieces: Vec<_> = split_periodic(&(-100i8..100), 100, 7).collect();
+        assert_eq!(pieces.first(), Some(&(-100..-96)));
+        assert_eq!(pieces.last(), Some(&(93..100)));
+        let pieces: Vec<_> = split_periodic(&(i8::MIN..i8::MAX), i8::MAX, 100).collect();
+        assert_eq!(pieces, vec![-128..-73, -73..27, 27..127])
+    }
+
+    #[test]
+    fn period_wider_than_half() {
+        let pieces: Vec<_> = split_periodic(&(0..u8::MAX), 200, 201).collect();
+        assert_eq!(pieces, vec![0..200, 200..255])
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! `bedtools`-style operations over streams of keyed ranges, such as
+//! `(chromosome, interval)` pairs, applied separately for each key.
+//!
+//! Inputs must be sorted by key (the ranges for each key may come in any order).
+//! Results are streamed as iterators: only one key's worth of input is held in memory at a time.
+
+use core::iter::Peekable;
+use core::ops::{Range, Sub};
+
+use crate::{runs, Split};
+
+/// Group consecutive items with equal keys.
+fn groups<K, T>(
+    sorted: impl IntoIterator<Item = (K, Range<T>)>,
+) -> impl Iterator<Item = (K, Vec<Range<T>>)>
+where
+    K: Ord,
+{
+    let mut items = sorted.into_iter().peekable();
+    core::iter::from_fn(move || {
+        let (key, first) = items.next()?;
+        let mut ranges = vec![first];
+        while let Some((_, r)) = items.next_if(|(k, _)| *k == key) {
+            ranges.push(r);
+        }
+        Some((key, ranges))
+    })
+}
+
+/// The merged runs of `b` for `key`, skipping past any earlier keys.
+fn runs_for<K, T, I>(b: &mut Peekable<I>, key: &K) -> Vec<Range<T>>
+where
+    K: Ord,
+    T: Sized + Ord + Copy,
+    I: Iterator<Item = (K, Vec<Range<T>>)>,
+{
+    while b.next_if(|(k, _)| k < key).is_some() {}
+    let mut out = Vec::new();
+    if let Some((_, ranges)) = b.next_if(|(k, _)| k == key) {
+        for r in ranges {
+            runs::insert(&mut out, r);
+        }
+    }
+    out
+}
+
+/// Lazily apply `f` to each key's ranges in `a` and the merged runs of `b` for that key.
+fn per_key<K, T, O>(
+    a: impl IntoIterator<Item = (K, Range<T>)>,
+    b: impl IntoIterator<Item = (K, Range<T>)>,
+    mut f: impl FnMut(&K, Vec<Range<T>>, &[Range<T>]) -> Vec<O>,
+) -> impl Iterator<Item = O>
+where
+    K: Ord,
+    T: Sized + Ord + Copy,
+{
+    let mut b = groups(b).peekable();
+    groups(a).flat_map(move |(key, ranges)| {
+        let covered = runs_for(&mut b, &key);
+        f(&key, ranges, &covered)
+    })
+}
+
+/** Merge overlapping and touching ranges with the same key.
+
+```
+# use range_split::grouped::merge;
+let merged = merge([("chr1", 5..10), ("chr1", 0..6), ("chr2", 0..3), ("chr2", 3..4)]);
+assert_eq!(merged.collect::<Vec<_>>(), vec![("chr1", 0..10), ("chr2", 0..4)]);
+```
+**/
+pub fn merge<K, T>(
+    sorted: impl IntoIterator<Item = (K, Range<T>)>,
+) -> impl Iterator<Item = (K, Range<T>)>
+where
+    K: Ord + Clone,
+    T: Sized + Ord + Copy,
+{
+    groups(sorted).flat_map(|(key, ranges)| {
+        let mut merged = Vec::new();
+        for r in ranges {
+            runs::insert(&mut merged, r);
+        }
+        merged.into_iter().map(move |r| (key.clone(), r))
+    })
+}
+
+/** The parts of each range in `a` that are also covered by `b` under the same key,
+in the order of `a`.
+
+```
+# use range_split::grouped::intersect;
+let a = [("chr1", 0..10), ("chr2", 0..10)];
+let b = [("chr1", 5..20), ("chr1", 8..9)];
+assert_eq!(intersect(a, b).collect::<Vec<_>>(), vec![("chr1", 5..10)]);
+```
+**/
+pub fn intersect<K, T>(
+    a: impl IntoIterator<Item = (K, Range<T>)>,
+    b: impl IntoIterator<Item = (K, Range<T>)>,
+) -> impl Iterator<Item = (K, Range<T>)>
+where
+    K: Ord + Clone,
+    T: Sized + Ord + Copy,
+{
+    per_key(a, b, |key, ranges, covered| {
+        let mut out = Vec::new();
+        for r in ranges {
+            let i = covered.partition_point(|c| c.end <= r.start);
+            for c in covered[i..].iter().take_while(|c| c.start < r.end) {
+                out.extend(r.split(c).1.map(|x| (key.clone(), x)));
+            }
+        }
+        out
+    })
+}
+
+/** The parts of each range in `a` not covered by `b` under the same key, in the order of `a`.
+
+```
+# use range_split::grouped::subtract;
+let a = [("chr1", 0..10), ("chr2", 0..10)];
+let b = [("chr1", 3..5)];
+assert_eq!(
+    subtract(a, b).collect::<Vec<_>>(),
+    vec![("chr1", 0..3), ("chr1", 5..10), ("chr2", 0..10)]
+);
+```
+**/
+pub fn subtract<K, T>(
+    a: impl IntoIterator<Item = (K, Range<T>)>,
+    b: impl IntoIterator<Item = (K, Range<T>)>,
+) -> impl Iterator<Item = (K, Range<T>)>
+where
+    K: Ord + Clone,
+    T: Sized + Ord + Copy,
+{
+    per_key(a, b, |key, ranges, covered| {
+        let mut out = Vec::new();
+        for r in ranges.into_iter().filter(|r| r.start < r.end) {
+            let mut rest = vec![r.clone()];
+            let i = covered.partition_point(|c| c.end <= r.start);
+            for c in covered[i..].iter().take_while(|c| c.start < r.end) {
+                runs::remove(&mut rest, c);
+            }
+            out.extend(rest.into_iter().map(|x| (key.clone(), x)));
+        }
+        out
+    })
+}
+
+/// The nearest range to a range of `a`, from [`closest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Closest<K, T> {
+    pub key: K,
+    pub range: Range<T>,
+    /// The nearest merged run of `b` with the same key, if any, and the length of the gap to it
+    /// (zero if they overlap or touch).
+    pub nearest: Option<(Range<T>, T)>,
+}
+
+/** For each range in `a`, find the nearest run of `b` with the same key.
+Ties go to the earlier run.
+
+```
+# use range_split::grouped::closest;
+let a = [("chr1", 10..20)];
+let b = [("chr1", 0..5), ("chr1", 24..30)];
+assert_eq!(closest(a, b).next().unwrap().nearest, Some((24..30, 4)));
+```
+**/
+pub fn closest<K, T>(
+    a: impl IntoIterator<Item = (K, Range<T>)>,
+    b: impl IntoIterator<Item = (K, Range<T>)>,
+) -> impl Iterator<Item = Closest<K, T>>
+where
+    K: Ord + Clone,
+    T: Sized + Ord + Copy + Default + Sub<Output = T>,
+{
+    per_key(a, b, |key, ranges, covered| {
+        let mut out = Vec::new();
+        for r in ranges {
+            let gap = |c: &Range<T>| {
+                if c.end <= r.start {
+                    r.start - c.end
+                } else if r.end <= c.start {
+                    c.start - r.end
+                } else {
+                    T::default()
+                }
+            };
+            // The candidates are the last run starting before `r` ends and the one after it
+            let i = covered.partition_point(|c| c.start < r.end);
+            let nearest = covered[i.saturating_sub(1)..(i + 1).min(covered.len())]
+                .iter()
+                .min_by_key(|c| gap(c))
+                .map(|c| (c.clone(), gap(c)));
+            out.push(Closest {
+                key: key.clone(),
+                range: r,
+                nearest,
+            });
+        }
+        out
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_keeps_keys_apart() {
+        assert_eq!(
+            merge([(1, 0..5), (2, 5..10)]).collect::<Vec<_>>(),
+            vec![(1, 0..5), (2, 5..10)]
+        )
+    }
+
+    #[test]
+    fn intersect_skips_missing_keys() {
+        let a = [(1, 0..10), (3, 0..10)];
+        let b = [(0, 0..10), (2, 0..10), (3, 2..4), (3, 6..8), (4, 0..1)];
+        assert_eq!(
+            intersect(a, b).collect::<Vec<_>>(),
+            vec![(3, 2..4), (3, 6..8)]
+        );
+    }
+
+    #[test]
+    fn intersect_keeps_a_records_separate() {
+        let a = [(1, 0..10), (1, 5..15)];
+        let b = [(1, 8..12)];
+        assert_eq!(
+            intersect(a, b).collect::<Vec<_>>(),
+            vec![(1, 8..10), (1, 8..12)]
+        );
+    }
+
+    #[test]
+    fn subtract_everything() {
+        let a = [(1, 2..4), (1, 3..3)];
+        let b = [(1, 0..10)];
+        assert_eq!(subtract(a, b).next(), None);
+    }
+
+    #[test]
+    fn closest_overlapping_and_missing() {
+        let a = [(1, 10..20), (2, 0..1)];
+        let b = [(1, 0..9), (1, 15..16), (1, 40..50)];
+        let found: Vec<_> = closest(a, b).map(|c| c.nearest).collect();
+        assert_eq!(found, vec![Some((15..16, 0)), None]);
+    }
+
+    #[test]
+    fn closest_tie_goes_earlier() {
+        let a = [(1, 10..20)];
+        let b = [(1, 0..5), (1, 25..30)];
+        assert_eq!(closest(a, b).next().unwrap().nearest, Some((0..5, 5)));
+    }
+
+    #[test]
+    fn streams_lazily() {
+        // An endless, sorted stream of keys: only the keys pulled so far are read
+        let a = (0..).map(|k| (k, 0..10));
+        let b = (0..).map(|k| (k, k..k + 1));
+        let first: Vec<_> = subtract(a, b).take(3).collect();
+        assert_eq!(first, vec![(0, 1..10), (1, 0..1), (1, 2..10)]);
+    }
+}
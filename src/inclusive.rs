@@ -0,0 +1,227 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Inclusive ranges: converting to and from half-open ones, and splitting those of `u64`,
+//! including the whole domain `0..=u64::MAX`, without overflow.
+//!
+//! The whole domain holds `2^64` values, one more than a `u64` can count, so lengths are `u128`.
+//! Pieces are found by their last values, which never pass `u64::MAX`, rather than by
+//! exclusive ends, which would.
+
+use core::ops::{Range, RangeInclusive};
+
+use crate::interval_arith::Integer;
+
+/** The same values as `range`, as an inclusive range; `None` if it's empty,
+which no `RangeInclusive` can represent without picking arbitrary endpoints.
+
+```
+# use range_split::inclusive::to_inclusive;
+assert_eq!(to_inclusive(3..7u8), Some(3..=6));
+assert_eq!(to_inclusive(0..u8::MAX), Some(0..=254));
+let (p, q) = (5u8, 5);
+assert_eq!(to_inclusive(p..q), None);
+```
+**/
+pub fn to_inclusive<T: Integer>(range: Range<T>) -> Option<RangeInclusive<T>> {
+    // `end` is above `start`, so can't underflow
+    (range.start < range.end).then(|| range.start..=range.end - T::ONE)
+}
+
+/** The same values as `range`, as a half-open range; `None` if it ends at the largest `T`,
+whose successor can't be an exclusive end. An empty (or exhausted) range gives `start..start`.
+
+```
+# use range_split::inclusive::to_exclusive;
+assert_eq!(to_exclusive(3..=6u8), Some(3..7));
+assert_eq!(to_exclusive(0..=u8::MAX), None);
+assert_eq!(to_exclusive(5..=4u8), Some(5..5));
+```
+**/
+pub fn to_exclusive<T: Integer>(range: RangeInclusive<T>) -> Option<Range<T>> {
+    let start = *range.start();
+    if range.is_empty() {
+        return Some(start..start);
+    }
+    Some(start..range.end().checked_add(T::ONE)?)
+}
+
+/// Conversion to a [`RangeInclusive`], from either kind of range, as [`to_inclusive`] does.
+pub trait ToInclusive<T> {
+    fn to_inclusive(&self) -> Option<RangeInclusive<T>>;
+}
+
+/// Conversion to a [`Range`], from either kind of range, as [`to_exclusive`] does.
+pub trait ToExclusive<T> {
+    fn to_exclusive(&self) -> Option<Range<T>>;
+}
+
+impl<T: Integer> ToInclusive<T> for Range<T> {
+    fn to_inclusive(&self) -> Option<RangeInclusive<T>> {
+        to_inclusive(self.clone())
+    }
+}
+
+/// Only `None` if empty.
+impl<T: Integer> ToInclusive<T> for RangeInclusive<T> {
+    fn to_inclusive(&self) -> Option<RangeInclusive<T>> {
+        (!self.is_empty()).then(|| self.clone())
+    }
+}
+
+impl<T: Integer> ToExclusive<T> for RangeInclusive<T> {
+    fn to_exclusive(&self) -> Option<Range<T>> {
+        to_exclusive(self.clone())
+    }
+}
+
+/// Never `None`.
+impl<T: Integer> ToExclusive<T> for Range<T> {
+    fn to_exclusive(&self) -> Option<Range<T>> {
+        Some(self.clone())
+    }
+}
+
+/** The number of values in `range`: 0 if it's empty.
+
+```
+# use range_split::inclusive::len;
+assert_eq!(len(&(0..=u64::MAX)), 1 << 64);
+assert_eq!(len(&(5..=5)), 1);
+```
+**/
+pub fn len(range: &RangeInclusive<u64>) -> u128 {
+    if range.is_empty() {
+        return 0;
+    }
+    u128::from(range.end() - range.start()) + 1
+}
+
+/** Cut `range` into chunks of `size` values, counted from its start; the last may be shorter.
+An empty range yields nothing. Panics if `size` is zero.
+
+```
+# use range_split::inclusive::chunks;
+let top: Vec<_> = chunks(&(u64::MAX - 4..=u64::MAX), 2).collect();
+assert_eq!(top, vec![u64::MAX - 4..=u64::MAX - 3, u64::MAX - 2..=u64::MAX - 1, u64::MAX..=u64::MAX]);
+assert_eq!(chunks(&(0..=u64::MAX), 1 << 62).count(), 4);
+```
+**/
+pub fn chunks(range: &RangeInclusive<u64>, size: u64) -> impl Iterator<Item = RangeInclusive<u64>> {
+    assert!(size > 0, "chunk size must be positive");
+    let end = *range.end();
+    let mut next = (!range.is_empty()).then(|| *range.start());
+    core::iter::from_fn(move || {
+        let start = next?;
+        let last = start.checked_add(size - 1).map_or(end, |l| l.min(end));
+        next = (last < end).then(|| last + 1);
+        Some(start..=last)
+    })
+}
+
+/** Cut `range` into `n` pieces as nearly equal as possible, larger pieces first,
+as when dividing a hash ring among nodes. Gives fewer, single-value, pieces
+if `range` has fewer than `n` values. Panics if `n` is zero.
+
+```
+# use range_split::inclusive::split_even;
+let ring: Vec<_> = split_even(&(0..=u64::MAX), 4).collect();
+assert_eq!(ring[0], 0..=(1 << 62) - 1);
+assert_eq!(ring[3], 3 << 62..=u64::MAX);
+
+let uneven: Vec<_> = split_even(&(0..=9), 3).collect();
+assert_eq!(uneven, vec![0..=3, 4..=6, 7..=9]);
+```
+**/
+pub fn split_even(
+    range: &RangeInclusive<u64>,
+    n: u64,
+) -> impl Iterator<Item = RangeInclusive<u64>> {
+    assert!(n > 0, "piece count must be positive");
+    let total = len(range);
+    let (size, extra) = (total / u128::from(n), total % u128::from(n));
+    let mut next = u128::from(*range.start());
+    (0..u128::from(n))
+        .map(move |i| size + u128::from(i < extra))
+        .take_while(|&size| size > 0)
+        .map(move |size| {
+            let start = next;
+            next += size;
+            // Both fit, as they're within `range`
+            start as u64..=(next - 1) as u64
+        })
+}
+
+/** The parts of `range` before `at`, and from `at` on.
+
+```
+# use range_split::inclusive::split_at;
+assert_eq!(split_at(&(0..=u64::MAX), 0), (None, Some(0..=u64::MAX)));
+assert_eq!(split_at(&(0..=9), 4), (Some(0..=3), Some(4..=9)));
+assert_eq!(split_at(&(0..=9), 20), (Some(0..=9), None));
+```
+**/
+pub fn split_at(
+    range: &RangeInclusive<u64>,
+    at: u64,
+) -> (Option<RangeInclusive<u64>>, Option<RangeInclusive<u64>>) {
+    if range.is_empty() {
+        return (None, None);
+    }
+    let (start, end) = (*range.start(), *range.end());
+    let below = (at > start).then(|| start..=end.min(at - 1));
+    let above = (at <= end).then(|| start.max(at)..=end);
+    (below, above)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        for r in [0..1u8, 3..200, 0..u8::MAX, 254..255] {
+            assert_eq!(to_exclusive(to_inclusive(r.clone()).unwrap()), Some(r));
+        }
+        assert_eq!(to_inclusive(i8::MIN..i8::MAX), Some(i8::MIN..=i8::MAX - 1));
+        assert_eq!(to_exclusive(i8::MIN..=i8::MAX), None);
+        // Through the traits, whichever kind the caller has
+        assert_eq!((2..=4u32).to_inclusive(), Some(2..=4));
+        assert_eq!((2..5u32).to_exclusive(), Some(2..5));
+        let mut spent = 1..=1u32;
+        spent.next();
+        assert_eq!(spent.to_inclusive(), None);
+        assert_eq!(spent.to_exclusive(), Some(1..1))
+    }
+
+    #[test]
+    fn whole_domain_chunks() {
+        let pieces: Vec<_> = chunks(&(0..=u64::MAX), u64::MAX).collect();
+        assert_eq!(pieces, vec![0..=u64::MAX - 1, u64::MAX..=u64::MAX]);
+        let mut spent = chunks(&(0..=u64::MAX), 1);
+        assert_eq!(spent.nth(2), Some(2..=2));
+        let (p, q) = (5, 4);
+        assert_eq!(chunks(&(p..=q), 3).count(), 0)
+    }
+
+    #[test]
+    fn split_even_covers_exactly() {
+        for (range, n) in [(0..=u64::MAX, 3), (u64::MAX - 10..=u64::MAX, 4), (7..=9, 5)] {
+            let pieces: Vec<_> = split_even(&range, n).collect();
+            assert_eq!(pieces.first().map(|p| *p.start()), Some(*range.start()));
+            assert_eq!(pieces.last().map(|p| *p.end()), Some(*range.end()));
+            assert!(pieces.windows(2).all(|w| *w[0].end() + 1 == *w[1].start()));
+            assert_eq!(pieces.iter().map(len).sum::<u128>(), len(&range));
+        }
+        assert_eq!(split_even(&(7..=9), 5).count(), 3)
+    }
+
+    #[test]
+    fn exhausted_is_empty() {
+        let mut r = 3..=3u64;
+        r.next();
+        assert_eq!(len(&r), 0);
+        assert_eq!(split_at(&r, 3), (None, None));
+        assert_eq!(split_even(&r, 2).count(), 0)
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Interval arithmetic on integer ranges: the result of an operation on two ranges
+//! is the smallest range holding the result for every pair of their members.
+//!
+//! If either operand is empty, so is the result. The `checked_` functions return
+//! [`RangeArithError::Overflow`] if an endpoint overflows, and the `_panicking` ones panic.
+
+use core::ops::{Add, Mul, Range, Sub};
+
+use crate::endpoint::RangeArithError::{self, Overflow};
+
+/// Integer types that ranges can do arithmetic over.
+pub trait Integer:
+    Sized + Ord + Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_rem_euclid(self, rhs: Self) -> Option<Self>;
+    /// The average of `self` and `rhs`, rounded down, without overflow.
+    fn midpoint_floor(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn checked_rem_euclid(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_rem_euclid(self, rhs)
+            }
+            fn midpoint_floor(self, rhs: Self) -> Self {
+                // The shared bits, plus half the differing ones; `>>` rounds down for signed types too
+                (self & rhs) + ((self ^ rhs) >> 1)
+            }
+        }
+    )*};
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// An empty range, if either operand is empty.
+fn empty<T: Integer>(a: &Range<T>, b: &Range<T>) -> Option<Range<T>> {
+    (a.start >= a.end || b.start >= b.end).then_some(a.start..a.start)
+}
+
+/** Every `x + y` for `x` in `a` and `y` in `b`, or [`Overflow`] if an endpoint overflows.
+
+```
+# use range_split::{endpoint::RangeArithError, interval_arith::checked_add};
+assert_eq!(checked_add(&(1..4), &(10..20)), Ok(11..23));
+assert_eq!(checked_add(&(250..255u8), &(0..2)), Err(RangeArithError::Overflow));
+```
+**/
+pub fn checked_add<T: Integer>(a: &Range<T>, b: &Range<T>) -> Result<Range<T>, RangeArithError> {
+    if let Some(e) = empty(a, b) {
+        return Ok(e);
+    }
+    let start = a.start.checked_add(b.start).ok_or(Overflow)?;
+    Ok(start..a.end.checked_add(b.end - T::ONE).ok_or(Overflow)?)
+}
+
+/** Every `x - y` for `x` in `a` and `y` in `b`, or [`Overflow`] if an endpoint overflows.
+
+```
+# use range_split::interval_arith::checked_sub;
+assert_eq!(checked_sub(&(10..20), &(1..4)), Ok(7..19));
+```
+**/
+pub fn checked_sub<T: Integer>(a: &Range<T>, b: &Range<T>) -> Result<Range<T>, RangeArithError> {
+    if let Some(e) = empty(a, b) {
+        return Ok(e);
+    }
+    let start = a.start.checked_sub(b.end - T::ONE).ok_or(Overflow)?;
+    Ok(start..a.end.checked_sub(b.start).ok_or(Overflow)?)
+}
+
+/** Every `x * y` for `x` in `a` and `y` in `b`, or [`Overflow`] if an endpoint overflows.
+
+```
+# use range_split::interval_arith::checked_mul;
+assert_eq!(checked_mul(&(-2..4), &(-5..1)), Ok(-15..11));
+```
+**/
+pub fn checked_mul<T: Integer>(a: &Range<T>, b: &Range<T>) -> Result<Range<T>, RangeArithError> {
+    if let Some(e) = empty(a, b) {
+        return Ok(e);
+    }
+    let (a_last, b_last) = (a.end - T::ONE, b.end - T::ONE);
+    let corner = |x: T, y: T| x.checked_mul(y).ok_or(Overflow);
+    let corners = [
+        corner(a.start, b.start)?,
+        corner(a.start, b_last)?,
+        corner(a_last, b.start)?,
+        corner(a_last, b_last)?,
+    ];
+    let (lo, hi) = min_max(corners);
+    Ok(lo..hi.checked_add(T::ONE).ok_or(Overflow)?)
+}
+
+/// [`checked_add`], panicking if an endpoint overflows.
+pub fn add_panicking<T: Integer>(a: &Range<T>, b: &Range<T>) -> Range<T> {
+    checked_add(a, b).expect(OVERFLOW)
+}
+
+/// [`checked_sub`], panicking if an endpoint overflows.
+pub fn sub_panicking<T: Integer>(a: &Range<T>, b: &Range<T>) -> Range<T> {
+    checked_sub(a, b).expect(OVERFLOW)
+}
+
+/// [`checked_mul`], panicking if an endpoint overflows.
+pub fn mul_panicking<T: Integer>(a: &Range<T>, b: &Range<T>) -> Range<T> {
+    checked_mul(a, b).expect(OVERFLOW)
+}
+
+const OVERFLOW: &str = "range endpoint overflows";
+
+fn min_max<T: Ord + Copy>(values: [T; 4]) -> (T, T) {
+    let lo = values.into_iter().min().expect("four values");
+    let hi = values.into_iter().max().expect("four values");
+    (lo, hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every result of `op` on members of `a` and `b`, as the smallest range holding them.
+    fn brute(a: Range<i32>, b: Range<i32>, op: fn(i32, i32) -> i32) -> Range<i32> {
+        let all: Vec<i32> = a.flat_map(|x| b.clone().map(move |y| op(x, y))).collect();
+        *all.iter().min().unwrap()..all.iter().max().unwrap() + 1
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for (a, b) in [(-3..2, 4..7), (0..1, -5..-2), (-4..-1, -3..3)] {
+            assert_eq!(
+                add_panicking(&a, &b),
+                brute(a.clone(), b.clone(), |x, y| x + y)
+            );
+            assert_eq!(
+                sub_panicking(&a, &b),
+                brute(a.clone(), b.clone(), |x, y| x - y)
+            );
+            assert_eq!(
+                mul_panicking(&a, &b),
+                brute(a.clone(), b.clone(), |x, y| x * y)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_operand() {
+        let (a, b) = (5, 5);
+        assert!(add_panicking(&(a..b), &(0..10)).is_empty());
+        assert!(mul_panicking(&(0..10), &(a..b)).is_empty());
+        assert_eq!(checked_sub(&(a..b), &(0..10u8)), Ok(5..5));
+    }
+
+    #[test]
+    fn checked_overflow() {
+        assert_eq!(checked_add(&(250..255u8), &(0..1)), Ok(250..255));
+        assert_eq!(checked_add(&(250..255u8), &(0..2)), Err(Overflow));
+        assert_eq!(checked_sub(&(0..10u8), &(1..2)), Err(Overflow));
+        assert_eq!(checked_mul(&(0..16u8), &(0..17)), Ok(0..241));
+        assert_eq!(checked_mul(&(0..17u8), &(0..17)), Err(Overflow));
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows")]
+    fn panicking_overflow() {
+        let _ = add_panicking(&(250..255u8), &(0..2));
+    }
+}
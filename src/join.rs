@@ -0,0 +1,142 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Interval joins of [`RangeMap`]s.
+
+use core::ops::Range;
+
+use crate::map::RangeMap;
+
+impl<T, V1> RangeMap<T, V1>
+where
+    T: Sized + Ord + Copy,
+    V1: Clone + PartialEq,
+{
+    /** Combine two range maps over the segments where either has an entry.
+
+    Each entry of the result covers a piece where neither map's value changes, and holds
+    `f(a, b)` of the values there, or nothing if `f` gives `None`. `f` isn't called
+    where neither map has an entry. Touching pieces with equal results merge.
+
+    ```
+    # use range_split::map::RangeMap;
+    let prices: RangeMap<u32, u32> = [(0..10, 5), (10..20, 7)].into_iter().collect();
+    let discounts: RangeMap<u32, u32> = [(5..15, 2)].into_iter().collect();
+    let net = prices.join(&discounts, |p, d| Some(p? - d.unwrap_or(&0)));
+    assert_eq!(net.as_slice(), &[(0..5, 5), (5..10, 3), (10..15, 5), (15..20, 7)]);
+
+    // An inner join
+    let both = prices.join(&discounts, |p, d| Some((*p?, *d?)));
+    assert_eq!(both.as_slice(), &[(5..10, (5, 2)), (10..15, (7, 2))]);
+    ```
+    **/
+    pub fn join<V2, V3>(
+        &self,
+        other: &RangeMap<T, V2>,
+        f: impl FnMut(Option<&V1>, Option<&V2>) -> Option<V3>,
+    ) -> RangeMap<T, V3>
+    where
+        V3: Clone + PartialEq,
+    {
+        join(self.as_slice(), other.as_slice(), f)
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The pieces of [`RangeMap::join`], for sorted, disjoint `a` and `b`.
+fn join<T, V1, V2, V3>(
+    a: &[(Range<T>, V1)],
+    b: &[(Range<T>, V2)],
+    mut f: impl FnMut(Option<&V1>, Option<&V2>) -> Option<V3>,
+) -> Vec<(Range<T>, V3)>
+where
+    T: Sized + Ord + Copy,
+{
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut cursor = None;
+    loop {
+        // Skip entries wholly behind the cursor
+        while i < a.len() && cursor.is_some_and(|c| a[i].0.end <= c) {
+            i += 1;
+        }
+        while j < b.len() && cursor.is_some_and(|c| b[j].0.end <= c) {
+            j += 1;
+        }
+        let (ra, rb) = (a.get(i).map(|e| &e.0), b.get(j).map(|e| &e.0));
+        let start = match (ra, rb) {
+            (None, None) => break,
+            (Some(r), None) | (None, Some(r)) => r.start,
+            (Some(ra), Some(rb)) => ra.start.min(rb.start),
+        };
+        let start = cursor.map_or(start, |c: T| c.max(start));
+        // The piece ends where either map next starts or stops an entry
+        let end = [ra, rb]
+            .into_iter()
+            .flatten()
+            .map(|r| if r.start > start { r.start } else { r.end })
+            .min()
+            .expect("at least one entry");
+        if start < end {
+            let inside = |r: &Range<T>| r.start <= start;
+            let va = a.get(i).filter(|e| inside(&e.0)).map(|e| &e.1);
+            let vb = b.get(j).filter(|e| inside(&e.0)).map(|e| &e.1);
+            if let Some(v) = f(va, vb) {
+                out.push((start..end, v));
+            }
+        }
+        cursor = Some(end);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map<V: Clone + PartialEq>(entries: &[(Range<i32>, V)]) -> RangeMap<i32, V> {
+        entries.iter().cloned().collect()
+    }
+
+    #[test]
+    fn outer_join() {
+        let a = map(&[(0..4, 'a'), (6..8, 'b')]);
+        let b = map(&[(2..7, 1), (10..12, 2)]);
+        let joined = a.join(&b, |x, y| Some((x.copied(), y.copied())));
+        assert_eq!(
+            joined.into_vec(),
+            vec![
+                (0..2, (Some('a'), None)),
+                (2..4, (Some('a'), Some(1))),
+                (4..6, (None, Some(1))),
+                (6..7, (Some('b'), Some(1))),
+                (7..8, (Some('b'), None)),
+                (10..12, (None, Some(2))),
+            ]
+        )
+    }
+
+    #[test]
+    fn equal_results_merge() {
+        let a = map(&[(0..5, 1), (5..10, 2)]);
+        let joined = a.join(&RangeMap::<i32, ()>::new(), |x, _| x.map(|_| 'x'));
+        assert_eq!(joined.into_vec(), vec![(0..10, 'x')])
+    }
+
+    #[test]
+    fn adjacent_entries_stay_separate() {
+        let a = [(0..5, 1), (5..10, 1)];
+        let b: [(Range<i32>, ()); 0] = [];
+        let joined = join(&a, &b, |x, _| x.copied());
+        assert_eq!(joined, vec![(0..5, 1), (5..10, 1)])
+    }
+
+    #[test]
+    fn empty_entries_skipped() {
+        let (p, q) = (3, 3);
+        let a = [(0..2, 'x'), (p..q, 'y'), (4..6, 'z')];
+        let joined = join(&a, &a, |x, _| x.copied());
+        assert_eq!(joined, vec![(0..2, 'x'), (4..6, 'z')])
+    }
+}
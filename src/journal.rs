@@ -0,0 +1,226 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! A range set with undo, redo, and rollback to checkpoints.
+
+use core::ops::Range;
+
+use crate::storage::{Change, RangeSet, RunStorage};
+
+/// A point in the history of a [`JournaledRangeSet`] to roll back to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    depth: usize,
+    /// Serial number of the edit on top at the time, to tell histories apart.
+    serial: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Edit<T> {
+    serial: u64,
+    /// What the edit changed; undone by applying the opposite of each.
+    changes: Vec<Change<T>>,
+}
+
+/** A [`RangeSet`] that journals each edit so that it can be undone.
+
+Edits that change nothing aren't journaled, so [`undo`](Self::undo)
+always reverts something visible. Making an edit discards anything undone.
+
+Usage:
+```
+# use range_split::journal::JournaledRangeSet;
+let mut set: JournaledRangeSet<u32> = JournaledRangeSet::new();
+set.insert(0..10);
+let saved = set.checkpoint();
+set.insert(5..20);
+set.remove(&(0..3));
+assert_eq!(set.runs(), vec![3..20]);
+
+assert!(set.undo());
+assert_eq!(set.runs(), vec![0..20]);
+assert!(set.redo());
+assert_eq!(set.runs(), vec![3..20]);
+
+assert!(set.rollback_to(saved));
+assert_eq!(set.runs(), vec![0..10]);
+```
+**/
+#[derive(Debug, Clone)]
+pub struct JournaledRangeSet<T, S = Vec<Range<T>>> {
+    set: RangeSet<T, S>,
+    done: Vec<Edit<T>>,
+    undone: Vec<Edit<T>>,
+    next_serial: u64,
+}
+
+impl<T, S: Default> Default for JournaledRangeSet<T, S> {
+    fn default() -> Self {
+        Self {
+            set: RangeSet::default(),
+            done: Vec::new(),
+            undone: Vec::new(),
+            next_serial: 1,
+        }
+    }
+}
+
+impl<T, S> JournaledRangeSet<T, S>
+where
+    T: Sized + Ord + Copy,
+    S: RunStorage<T>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The set as it now stands.
+    pub fn set(&self) -> &RangeSet<T, S> {
+        &self.set
+    }
+
+    /// The runs of the set, in order.
+    pub fn runs(&self) -> Vec<Range<T>> {
+        self.set.iter().collect()
+    }
+
+    fn journal(&mut self, changes: Vec<Change<T>>) {
+        if changes.is_empty() {
+            return;
+        }
+        self.undone.clear();
+        self.done.push(Edit {
+            serial: self.next_serial,
+            changes,
+        });
+        self.next_serial += 1;
+    }
+
+    /// Add `range`, as [`RangeSet::insert`] does.
+    pub fn insert(&mut self, range: impl Into<Range<T>>) {
+        let mut changes = Vec::new();
+        self.set.insert_with(range, |c| changes.push(c));
+        self.journal(changes);
+    }
+
+    /// Remove `range`, as [`RangeSet::remove`] does.
+    pub fn remove(&mut self, range: &Range<T>) {
+        let mut changes = Vec::new();
+        self.set.remove_with(range, |c| changes.push(c));
+        self.journal(changes);
+    }
+
+    /// Apply `changes`, or their opposites if `invert`.
+    fn apply(&mut self, changes: &[Change<T>], invert: bool) {
+        for change in changes {
+            match (change, invert) {
+                (Change::Added(r), false) | (Change::Removed(r), true) => {
+                    self.set.insert(r.clone())
+                }
+                (Change::Removed(r), false) | (Change::Added(r), true) => self.set.remove(r),
+            }
+        }
+    }
+
+    /// Revert the latest edit. Returns `false` if there's nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.done.pop() else {
+            return false;
+        };
+        self.apply(&edit.changes, true);
+        self.undone.push(edit);
+        true
+    }
+
+    /// Reapply the latest undone edit. Returns `false` if there's nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.undone.pop() else {
+            return false;
+        };
+        self.apply(&edit.changes, false);
+        self.done.push(edit);
+        true
+    }
+
+    /// Where the history now stands.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            depth: self.done.len(),
+            serial: self.done.last().map_or(0, |e| e.serial),
+        }
+    }
+
+    /// Undo edits back to `checkpoint`; they can then be redone.
+    /// Returns `false`, and does nothing, if `checkpoint` isn't in the current history
+    /// (because it's since been undone and replaced).
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) -> bool {
+        let serial = match checkpoint.depth {
+            0 => Some(0),
+            d => self.done.get(d - 1).map(|e| e.serial),
+        };
+        if serial != Some(checkpoint.serial) {
+            return false;
+        }
+        while self.done.len() > checkpoint.depth {
+            self.undo();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_restores_merged_runs() {
+        let mut set: JournaledRangeSet<u32> = JournaledRangeSet::new();
+        set.insert(0..5);
+        set.insert(10..15);
+        set.insert(3..12);
+        assert_eq!(set.runs(), vec![0..15]);
+        set.undo();
+        assert_eq!(set.runs(), vec![0..5, 10..15]);
+        set.undo();
+        set.undo();
+        assert!(!set.undo());
+        assert!(set.set().is_empty())
+    }
+
+    #[test]
+    fn noop_not_journaled() {
+        let mut set: JournaledRangeSet<u32> = JournaledRangeSet::new();
+        set.insert(0..10);
+        set.insert(2..4);
+        set.remove(&(20..30));
+        assert!(set.undo());
+        assert!(set.set().is_empty());
+        assert!(!set.undo())
+    }
+
+    #[test]
+    fn edit_discards_redo() {
+        let mut set: JournaledRangeSet<u32> = JournaledRangeSet::new();
+        set.insert(0..10);
+        set.undo();
+        set.insert(20..30);
+        assert!(!set.redo());
+        assert_eq!(set.runs(), vec![20..30])
+    }
+
+    #[test]
+    fn stale_checkpoint() {
+        let mut set: JournaledRangeSet<u32> = JournaledRangeSet::new();
+        set.insert(0..10);
+        let cp = set.checkpoint();
+        set.undo();
+        set.insert(20..30);
+        assert!(!set.rollback_to(cp));
+        assert_eq!(set.runs(), vec![20..30]);
+
+        let start = JournaledRangeSet::<u32>::new().checkpoint();
+        assert!(set.rollback_to(start));
+        assert!(set.set().is_empty());
+        assert!(set.redo())
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Range sets grouped by a key, such as a chromosome, file, or shard.
+
+use core::borrow::Borrow;
+use core::ops::Range;
+use std::collections::BTreeMap;
+
+use crate::storage::{RangeSet, RunStorage};
+
+/** A [`RangeSet`] for each key, all kept in the storage `S`.
+
+Keys with no ranges aren't kept.
+
+Usage:
+```
+# use range_split::keyed::KeyedRangeSet;
+let reads: KeyedRangeSet<&str, u64> =
+    [("chr1", 100..200), ("chr2", 0..50), ("chr1", 150..300)].into_iter().collect();
+let exons: KeyedRangeSet<&str, u64> = [("chr1", 250..400), ("chr3", 0..10)].into_iter().collect();
+
+assert_eq!(reads.runs("chr1").collect::<Vec<_>>(), vec![100..300]);
+assert!(reads.contains("chr2", 49));
+
+let covered = reads.intersect(&exons);
+assert_eq!(covered.iter_flat().collect::<Vec<_>>(), vec![(&"chr1", 250..300)]);
+let all = reads.union(&exons);
+assert_eq!(all.keys().collect::<Vec<_>>(), vec![&"chr1", &"chr2", &"chr3"]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyedRangeSet<K, T, S = Vec<Range<T>>> {
+    /// Never holds an empty set.
+    by_key: BTreeMap<K, RangeSet<T, S>>,
+}
+
+impl<K, T, S> Default for KeyedRangeSet<K, T, S> {
+    fn default() -> Self {
+        Self {
+            by_key: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K, T, S> KeyedRangeSet<K, T, S>
+where
+    K: Ord + Clone,
+    T: Sized + Ord + Copy,
+    S: RunStorage<T> + Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `range` under `key`, merging it with any runs it overlaps or touches.
+    pub fn insert(&mut self, key: K, range: impl Into<Range<T>>) {
+        let range = range.into();
+        if range.start < range.end {
+            self.by_key.entry(key).or_default().insert(range);
+        }
+    }
+
+    /// Remove `range` from under `key`.
+    pub fn remove<Q>(&mut self, key: &Q, range: &Range<T>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if let Some(set) = self.by_key.get_mut(key) {
+            set.remove(range);
+            if set.is_empty() {
+                self.by_key.remove(key);
+            }
+        }
+    }
+
+    /// Whether `t` is in the set under `key`.
+    pub fn contains<Q>(&self, key: &Q, t: T) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some_and(|set| set.contains(t))
+    }
+
+    /// The set under `key`, unless it's empty.
+    pub fn get<Q>(&self, key: &Q) -> Option<&RangeSet<T, S>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.by_key.get(key)
+    }
+
+    /// The runs under `key`, in order.
+    pub fn runs<Q>(&self, key: &Q) -> impl Iterator<Item = Range<T>> + '_
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).into_iter().flat_map(RangeSet::iter)
+    }
+
+    /// The keys with any ranges, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.by_key.keys()
+    }
+
+    /// Each key, in order, with its set.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &RangeSet<T, S>)> {
+        self.by_key.iter()
+    }
+
+    /// Every `(key, run)`, in order.
+    pub fn iter_flat(&self) -> impl Iterator<Item = (&K, Range<T>)> + '_ {
+        self.by_key
+            .iter()
+            .flat_map(|(k, set)| set.iter().map(move |r| (k, r)))
+    }
+
+    /// Number of keys with any ranges.
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_key.is_empty()
+    }
+
+    /// Everything in either set, key by key.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        for (key, r) in other.iter_flat() {
+            out.insert(key.clone(), r);
+        }
+        out
+    }
+
+    /// Everything in both sets under the same key.
+    pub fn intersect(&self, other: &Self) -> Self {
+        let mut out = Self::new();
+        for (key, set) in self.iter() {
+            let Some(theirs) = other.get(key) else {
+                continue;
+            };
+            for r in set.iter() {
+                for o in theirs.storage().overlapping(&r) {
+                    out.insert(key.clone(), r.start.max(o.start)..r.end.min(o.end));
+                }
+            }
+        }
+        out
+    }
+
+    /// Everything in this set that isn't in `other` under the same key.
+    pub fn subtract(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        out.by_key.retain(|key, set| {
+            for r in other.runs(key) {
+                set.remove(&r);
+            }
+            !set.is_empty()
+        });
+        out
+    }
+}
+
+impl<K, T, S> FromIterator<(K, Range<T>)> for KeyedRangeSet<K, T, S>
+where
+    K: Ord + Clone,
+    T: Sized + Ord + Copy,
+    S: RunStorage<T> + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (K, Range<T>)>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K, T, S> Extend<(K, Range<T>)> for KeyedRangeSet<K, T, S>
+where
+    K: Ord + Clone,
+    T: Sized + Ord + Copy,
+    S: RunStorage<T> + Clone,
+{
+    fn extend<I: IntoIterator<Item = (K, Range<T>)>>(&mut self, iter: I) {
+        for (key, range) in iter {
+            self.insert(key, range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soa::SoaRangeSet;
+
+    fn set(items: &[(u8, Range<u32>)]) -> KeyedRangeSet<u8, u32> {
+        items.iter().cloned().collect()
+    }
+
+    #[test]
+    fn remove_drops_empty_keys() {
+        let mut s = set(&[(1, 0..10), (2, 0..10)]);
+        s.remove(&1, &(0..10));
+        assert_eq!(s.keys().collect::<Vec<_>>(), vec![&2]);
+        s.remove(&3, &(0..10));
+        assert_eq!(s.len(), 1)
+    }
+
+    #[test]
+    fn subtract_per_key() {
+        let a = set(&[(1, 0..10), (2, 0..10)]);
+        let b = set(&[(1, 3..5), (2, 0..20), (3, 0..5)]);
+        let diff = a.subtract(&b);
+        assert_eq!(
+            diff.iter_flat().collect::<Vec<_>>(),
+            vec![(&1, 0..3), (&1, 5..10)]
+        )
+    }
+
+    #[test]
+    fn other_storage() {
+        let mut s: KeyedRangeSet<&str, u32, SoaRangeSet<u32>> =
+            [("a", 0..10), ("a", 20..30), ("b", 5..6)]
+                .into_iter()
+                .collect();
+        s.remove("a", &(5..25));
+        assert_eq!(s.get("a").unwrap().validate(), Ok(()));
+        assert_eq!(s.runs("a").collect::<Vec<_>>(), vec![0..5, 25..30]);
+        let both = s.intersect(&[("a", 3..27)].into_iter().collect());
+        assert_eq!(
+            both.iter_flat().collect::<Vec<_>>(),
+            vec![(&"a", 3..5), (&"a", 25..27)]
+        )
+    }
+
+    #[test]
+    fn empty_ranges_ignored() {
+        let (p, q) = (5, 5);
+        let s = set(&[(1, p..q)]);
+        assert!(s.is_empty());
+        assert_eq!(s.get(&1), None);
+        assert_eq!(s.runs(&1).count(), 0)
+    }
+}
@@ -1,13 +1,86 @@
 // Copyright 2023 Alex Jago <abjago.net>
 // Released under the MIT or Apache-2.0 licenses, at your option.
 
-use core::ops::Range;
+use core::any::Any;
+use core::ops::{Bound, Range, RangeBounds};
+
+use crate::interval_arith::Integer;
+
+pub mod allocator;
+pub mod bounds;
+pub mod buckets;
+pub mod buffered;
+pub mod chars;
+pub mod compaction;
+pub mod compare;
+pub mod concurrent;
+pub mod damage;
+pub mod data;
+#[cfg(feature = "chrono")]
+pub mod dates;
+pub mod disk;
+pub mod display;
+pub mod edit;
+pub mod endpoint;
+pub mod extent;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod flash;
+#[cfg(feature = "genomics")]
+pub mod genomics;
+pub mod grid;
+pub mod grouped;
+pub mod inclusive;
+pub mod interval_arith;
+pub mod join;
+pub mod journal;
+pub mod keyed;
+pub mod map;
+pub mod memmap;
+pub mod morton;
+pub mod multipart;
+pub mod overlap;
+pub mod pages;
+pub mod paginate;
+pub mod persistent;
+pub mod pieces;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod quantiles;
+pub mod reconcile;
+pub mod rect;
+pub mod region;
+pub mod rle;
+mod runs;
+#[cfg(feature = "rand")]
+pub mod sample;
+pub mod scanline;
+pub mod schedule;
+pub mod search;
+pub mod select;
+#[cfg(feature = "semver")]
+pub mod semver;
+pub mod soa;
+pub mod spans;
+pub mod stab;
+pub mod steps;
+pub mod storage;
+pub mod stream;
+pub mod tagged;
+pub mod timebase;
+#[cfg(feature = "unicode")]
+pub mod unicode;
+pub mod validated;
+pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod wheel;
 
 /** A trait for splitting [`Range`]s and maybe other things too.
 
 Usage:
 ```
-# use range_partition::Split;
+# use range_split::Split;
 let foo = 0..10;
 let bar = 3..6;
 
@@ -61,6 +134,424 @@ where
     }
 }
 
+/** Split [`Range`]s by comparing a key derived from each endpoint, rather than the endpoints themselves.
+
+Usage:
+```
+# use range_split::SplitByKey;
+#[derive(Debug, Clone, PartialEq)]
+struct Event { at: u32, label: &'static str }
+let ev = |at| Event { at, label: "" };
+
+let (below, inter, above) = (ev(0)..ev(10)).split_by_key(&(ev(3)..ev(6)), |e| e.at);
+assert_eq!(below, Some(ev(0)..ev(3)));
+assert_eq!(inter, Some(ev(3)..ev(6)));
+assert_eq!(above, Some(ev(6)..ev(10)));
+```
+Gives the same pieces as [`Split`] would if the endpoints were ordered by `key`.
+**/
+pub trait SplitByKey<T> {
+    /// Split `self` by `other` into up to three parts, as [`Split::split`] does.
+    fn split_by_key<K: Ord>(
+        &self,
+        other: &Self,
+        key: impl Fn(&T) -> K,
+    ) -> (Option<Self>, Option<Self>, Option<Self>)
+    where
+        Self: Sized;
+}
+
+impl<T> SplitByKey<T> for Range<T>
+where
+    T: Sized + Clone,
+{
+    fn split_by_key<K: Ord>(
+        &self,
+        other: &Self,
+        key: impl Fn(&T) -> K,
+    ) -> (Option<Self>, Option<Self>, Option<Self>) {
+        split_with(self, other, |a, b| key(a) < key(b))
+    }
+}
+
+/** Cut a [`Range`] at a point, keeping one side and returning the other,
+as [`Vec::split_off`] does.
+
+Usage:
+```
+# use range_split::SplitOff;
+// Take work from the front of a queue
+let mut todo = 0..25;
+let mut batches = vec![];
+while let Some(batch) = todo.split_off_below(todo.start + 10) {
+    batches.push(batch);
+}
+assert_eq!(batches, vec![0..10, 10..20, 20..25]);
+assert!(todo.is_empty());
+
+let mut r = 0..10;
+assert_eq!(r.split_off_above(7), Some(7..10));
+assert_eq!(r, 0..7);
+assert_eq!(r.split_off_below_range(&(3..5)), Some(0..3));
+assert_eq!(r, 3..7);
+```
+The point is clamped to `self`. Nothing is removed, and `None` returned,
+if the removed piece would be empty.
+**/
+pub trait SplitOff<T> {
+    /// Remove and return the part of `self` below `at`.
+    fn split_off_below(&mut self, at: T) -> Option<Self>
+    where
+        Self: Sized;
+
+    /// Remove and return the part of `self` from `at` onwards.
+    fn split_off_above(&mut self, at: T) -> Option<Self>
+    where
+        Self: Sized;
+
+    /// Remove and return the part of `self` below `other`.
+    fn split_off_below_range(&mut self, other: &Self) -> Option<Self>
+    where
+        Self: Sized;
+
+    /// Remove and return the part of `self` above `other`.
+    fn split_off_above_range(&mut self, other: &Self) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+impl<T> SplitOff<T> for Range<T>
+where
+    T: Sized + Ord + Copy,
+{
+    fn split_off_below(&mut self, at: T) -> Option<Self> {
+        let at = at.min(self.end);
+        if at <= self.start {
+            return None;
+        }
+        let below = self.start..at;
+        self.start = at;
+        Some(below)
+    }
+
+    fn split_off_above(&mut self, at: T) -> Option<Self> {
+        let at = at.max(self.start);
+        if at >= self.end {
+            return None;
+        }
+        let above = at..self.end;
+        self.end = at;
+        Some(above)
+    }
+
+    fn split_off_below_range(&mut self, other: &Self) -> Option<Self> {
+        self.split_off_below(other.start)
+    }
+
+    fn split_off_above_range(&mut self, other: &Self) -> Option<Self> {
+        self.split_off_above(other.end)
+    }
+}
+
+/** Split a [`Range`] by any range expression, such as `..5`, `3..` or `..=7`,
+rather than only by another `Range`.
+
+Usage:
+```
+# use range_split::SplitBounds;
+let r = 0..10u8;
+assert_eq!(r.split_bounds(..4), (None, Some(0..4), Some(4..10)));
+assert_eq!(r.split_bounds(3..=6), (Some(0..3), Some(3..7), Some(7..10)));
+assert_eq!(r.split_bounds(7..), (Some(0..7), Some(7..10), None));
+// No need for a stand-in like `u8::MIN..x` to split below `x`
+assert_eq!(r.split_bounds(..=u8::MAX), (None, Some(0..10), None));
+```
+The pieces are as for [`Split::split`] by the equivalent `Range`,
+except that empty pieces are always `None`.
+**/
+pub trait SplitBounds<T> {
+    /// Split `self` into the parts before, within, and after `cutter`.
+    /// An `Unbounded` end reaches the matching end of `self`;
+    /// an `Excluded` start (or `Included` end) is moved past its value,
+    /// and one with no successor lies past `self`, leaving nothing beyond it.
+    fn split_bounds(&self, cutter: impl RangeBounds<T>) -> Pieces<Self>
+    where
+        Self: Sized;
+}
+
+impl<T: Integer> SplitBounds<T> for Range<T> {
+    fn split_bounds(&self, cutter: impl RangeBounds<T>) -> Pieces<Self> {
+        // The cutter as `lo..hi`, clamped to `self`; bounds past `T::MAX` are past `self` too
+        let lo = match cutter.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s.checked_add(T::ONE).unwrap_or(self.end),
+            Bound::Unbounded => self.start,
+        };
+        let hi = match cutter.end_bound() {
+            Bound::Included(&e) => e.checked_add(T::ONE).unwrap_or(self.end),
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => self.end,
+        };
+        let lo = lo.max(self.start).min(self.end);
+        // An inverted cutter cuts nothing out, at `lo`
+        let hi = hi.min(self.end).max(lo);
+        let piece = |r: Range<T>| (r.start < r.end).then_some(r);
+        (piece(self.start..lo), piece(lo..hi), piece(hi..self.end))
+    }
+}
+
+/** In-place intersection and subtraction of [`Range`]s.
+
+Usage:
+```
+# use range_split::{RangeAssign, Subtracted};
+let mut r = 0..10;
+assert!(r.intersect_assign(&(5..20)));
+assert_eq!(r, 5..10);
+
+assert_eq!(r.subtract_assign(&(0..7)), Subtracted::InPlace);
+assert_eq!(r, 7..10);
+assert_eq!(r.subtract_assign(&(8..9)), Subtracted::Split(9..10));
+assert_eq!(r, 7..8);
+```
+**/
+pub trait RangeAssign {
+    /// Shrink `self` to its intersection with `other`.
+    /// Returns whether anything is left.
+    fn intersect_assign(&mut self, other: &Self) -> bool;
+
+    /// Remove `other` from `self`. If that would leave two ranges,
+    /// `self` becomes the lower one and the upper one is returned.
+    fn subtract_assign(&mut self, other: &Self) -> Subtracted<Self>
+    where
+        Self: Sized;
+}
+
+/// What's left over from [`RangeAssign::subtract_assign`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Subtracted<R> {
+    /// The difference fit in place (though it may be empty).
+    InPlace,
+    /// `other` fell strictly inside `self`; this is the part above it.
+    Split(R),
+}
+
+impl<T> RangeAssign for Range<T>
+where
+    T: Sized + Ord + Copy,
+{
+    fn intersect_assign(&mut self, other: &Self) -> bool {
+        self.start = self.start.max(other.start);
+        self.end = self.end.min(other.end).max(self.start);
+        self.start < self.end
+    }
+
+    fn subtract_assign(&mut self, other: &Self) -> Subtracted<Self> {
+        if other.start >= other.end {
+            return Subtracted::InPlace;
+        }
+        match self.split(other) {
+            (Some(below), _, Some(above)) => {
+                *self = below;
+                Subtracted::Split(above)
+            }
+            (Some(piece), _, None) | (None, _, Some(piece)) => {
+                *self = piece;
+                Subtracted::InPlace
+            }
+            (None, _, None) => {
+                self.end = self.start;
+                Subtracted::InPlace
+            }
+        }
+    }
+}
+
+/** An object-safe form of [`Split`], so that splittable values of different types
+can be handled together through `dyn`.
+
+Implemented for every `'static` type that implements [`Split`].
+
+Usage:
+```
+# use range_split::DynSplit;
+# use core::ops::Range;
+let items: Vec<Box<dyn DynSplit>> = vec![Box::new(0..10), Box::new(0u8..10)];
+let by = 3..6;
+
+let (below, inter, above) = items[0].dyn_split(&by).unwrap();
+assert_eq!(below.unwrap().as_any().downcast_ref::<Range<i32>>(), Some(&(0..3)));
+assert_eq!(*inter.unwrap().into_any().downcast::<Range<i32>>().unwrap(), 3..6);
+assert!(above.is_some());
+
+// `by` isn't a `Range<u8>`
+assert!(items[1].dyn_split(&by).is_none());
+```
+**/
+pub trait DynSplit: Any {
+    /// Split `self` by `other` as [`Split::split`] does,
+    /// or `None` if `other` isn't the same type as `self`.
+    #[allow(clippy::type_complexity)]
+    fn dyn_split(
+        &self,
+        other: &dyn Any,
+    ) -> Option<(
+        Option<Box<dyn DynSplit>>,
+        Option<Box<dyn DynSplit>>,
+        Option<Box<dyn DynSplit>>,
+    )>;
+
+    /// `self`, for downcasting back to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// `self`, boxed, for downcasting back to its concrete type.
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<S> DynSplit for S
+where
+    S: Split + Any,
+{
+    fn dyn_split(&self, other: &dyn Any) -> Option<Pieces<Box<dyn DynSplit>>> {
+        let other = other.downcast_ref::<S>()?;
+        let (below, inter, above) = self.split(other);
+        let boxed = |p: Option<S>| p.map(|p| Box::new(p) as Box<dyn DynSplit>);
+        Some((boxed(below), boxed(inter), boxed(above)))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/** Conveniences for the three-part result of [`Split::split`].
+
+Usage:
+```
+# use range_split::{Split, SplitPieces};
+let pieces: Vec<_> = (0..10).split(&(3..6)).into_pieces().collect();
+assert_eq!(pieces, vec![0..3, 3..6, 6..10]);
+
+let whole: Vec<_> = [0..4, 6..9]
+    .iter()
+    .flat_map(|r| r.split(&(3..7)).into_pieces())
+    .collect();
+assert_eq!(whole, vec![0..3, 3..4, 6..7, 7..9]);
+
+assert_eq!((0..10).split(&(0..20)).single(), Some(0..10));
+```
+**/
+pub trait SplitPieces<R> {
+    /// The pieces that exist, in order: below, intersection, above.
+    fn into_pieces(self) -> IntoPieces<R>;
+
+    /// How many of the three pieces exist.
+    fn count_pieces(&self) -> usize;
+
+    /// The only piece, if exactly one exists.
+    fn single(self) -> Option<R>;
+}
+
+impl<R> SplitPieces<R> for Pieces<R> {
+    fn into_pieces(self) -> IntoPieces<R> {
+        IntoPieces {
+            pieces: [self.0, self.1, self.2],
+            next: 0,
+        }
+    }
+
+    fn count_pieces(&self) -> usize {
+        [&self.0, &self.1, &self.2]
+            .into_iter()
+            .filter(|p| p.is_some())
+            .count()
+    }
+
+    fn single(self) -> Option<R> {
+        match self {
+            (Some(r), None, None) | (None, Some(r), None) | (None, None, Some(r)) => Some(r),
+            _ => None,
+        }
+    }
+}
+
+/// Iterator over the pieces of a split; see [`SplitPieces::into_pieces`].
+#[derive(Debug, Clone)]
+pub struct IntoPieces<R> {
+    pieces: [Option<R>; 3],
+    next: usize,
+}
+
+impl<R> Iterator for IntoPieces<R> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        while self.next < 3 {
+            self.next += 1;
+            if let Some(r) = self.pieces[self.next - 1].take() {
+                return Some(r);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.pieces[self.next..]
+            .iter()
+            .filter(|p| p.is_some())
+            .count();
+        (n, Some(n))
+    }
+}
+
+impl<R> ExactSizeIterator for IntoPieces<R> {}
+
+/// Below, intersection, and above.
+pub(crate) type Pieces<R> = (Option<R>, Option<R>, Option<R>);
+
+/// The logic of [`Split::split`], under an arbitrary strict ordering `lt`.
+pub(crate) fn split_with<T: Clone>(
+    this: &Range<T>,
+    other: &Range<T>,
+    lt: impl Fn(&T, &T) -> bool,
+) -> Pieces<Range<T>> {
+    // `a.min(b)`, keeping `a` on ties as `Ord::min` does
+    let min = |a: &T, b: &T| if lt(b, a) { b.clone() } else { a.clone() };
+
+    let mut below = None;
+    let mut inter = None;
+    let mut above = None;
+    if lt(&this.start, &other.start) {
+        // Below exists
+        below = Some(this.start.clone()..min(&this.end, &other.start));
+        if lt(&other.start, &this.end) && !lt(&other.end, &this.end) {
+            // Inter, but no above
+            inter = Some(other.start.clone()..this.end.clone());
+        }
+        if lt(&other.end, &this.end) {
+            // Inter and above
+            inter = Some(other.clone());
+            above = Some(other.end.clone()..this.end.clone());
+        }
+    } else if lt(&this.start, &other.end) {
+        // No below
+        inter = Some(this.start.clone()..min(&this.end, &other.end));
+        if lt(&other.end, &this.end) {
+            // Also above
+            above = Some(other.end.clone()..this.end.clone());
+        }
+    } else {
+        // Above only
+        above = Some(this.clone());
+    }
+
+    (below, inter, above)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,4 +594,140 @@ mod tests {
     fn c_gt_a() {
         assert_eq!(C.split(&A), (None, None, Some(6..8)))
     }
+
+    #[test]
+    fn by_key_matches_split() {
+        for r in [A, B, C] {
+            for o in [A, B, C] {
+                assert_eq!(r.split_by_key(&o, |&x| x), r.split(&o))
+            }
+        }
+    }
+
+    #[test]
+    fn by_key_composite() {
+        // Ordered by the first field only; the chars are ignored
+        let r = (0, 'z')..(10, 'a');
+        let o = (5, 'a')..(20, 'a');
+        assert_eq!(
+            r.split_by_key(&o, |p| p.0),
+            (Some((0, 'z')..(5, 'a')), Some((5, 'a')..(10, 'a')), None)
+        )
+    }
+
+    #[test]
+    fn dyn_matches_split() {
+        for r in [A, B, C] {
+            for o in [A, B, C] {
+                let (b, i, a) = r.dyn_split(&o).unwrap();
+                let back = |p: Option<Box<dyn DynSplit>>| {
+                    p.map(|p| p.as_any().downcast_ref::<Range<usize>>().unwrap().clone())
+                };
+                assert_eq!((back(b), back(i), back(a)), r.split(&o))
+            }
+        }
+    }
+
+    #[test]
+    fn dyn_wrong_type() {
+        let r: &dyn DynSplit = &A;
+        assert!(r.dyn_split(&(0u32..5)).is_none());
+        assert!(r.dyn_split(&"0..5").is_none())
+    }
+
+    #[test]
+    fn pieces_count_down() {
+        let mut it = A.split(&C).into_pieces();
+        assert_eq!(it.len(), 1);
+        assert_eq!(it.next(), Some(0..5));
+        assert_eq!(it.len(), 0);
+        assert_eq!(it.next(), None);
+
+        let it = B.split(&C).into_pieces();
+        assert_eq!(it.len(), 3);
+        assert_eq!(B.split(&C).count_pieces(), 3);
+        assert_eq!(B.split(&C).single(), None)
+    }
+
+    #[test]
+    fn split_off_clamps() {
+        let mut r = 5..10;
+        assert_eq!(r.split_off_below(2), None);
+        assert_eq!(r.split_off_above(12), None);
+        assert_eq!(r, 5..10);
+        assert_eq!(r.split_off_above(0), Some(5..10));
+        assert_eq!(r, 5..5);
+        assert_eq!(r.split_off_below(20), None)
+    }
+
+    #[test]
+    fn split_off_matches_split() {
+        for o in [A, B, C] {
+            let (below, _, above) = B.split(&o);
+            let mut r = B;
+            assert_eq!(r.split_off_below_range(&o), below);
+            assert_eq!(r.split_off_above_range(&o), above);
+        }
+    }
+
+    #[test]
+    fn split_bounds_matches_split() {
+        use core::ops::Bound::{Excluded, Included};
+        for r in [A, B, C] {
+            for o in [A, B, C] {
+                let nonempty = |p: Option<Range<usize>>| p.filter(|p| p.start < p.end);
+                let (b, i, a) = r.split(&o);
+                let expected = (nonempty(b), nonempty(i), nonempty(a));
+                assert_eq!(r.split_bounds(o.clone()), expected);
+                assert_eq!(r.split_bounds(o.start..=o.end - 1), expected);
+                if o.start > 0 {
+                    let after = (Excluded(o.start - 1), Included(o.end - 1));
+                    assert_eq!(r.split_bounds(after), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn split_bounds_extremes() {
+        use core::ops::Bound::{Excluded, Unbounded};
+        let r = 250..255u8;
+        assert_eq!(
+            r.split_bounds((Excluded(u8::MAX), Unbounded)),
+            (Some(250..255), None, None)
+        );
+        assert_eq!(r.split_bounds(..), (None, Some(250..255), None));
+        let (p, q) = (253, 251);
+        assert_eq!(r.split_bounds(p..q), (Some(250..253), None, Some(253..255)))
+    }
+
+    #[test]
+    fn assign_matches_split() {
+        for r in [A, B, C] {
+            for o in [A, B, C] {
+                let (below, inter, above) = r.split(&o);
+                let mut i = r.clone();
+                assert_eq!(i.intersect_assign(&o), inter.is_some());
+                assert_eq!(Some(i).filter(|i| !i.is_empty()), inter);
+
+                let mut d = r.clone();
+                let pieces = match d.subtract_assign(&o) {
+                    Subtracted::Split(upper) => vec![d, upper],
+                    Subtracted::InPlace if d.is_empty() => vec![],
+                    Subtracted::InPlace => vec![d],
+                };
+                assert_eq!(pieces, below.into_iter().chain(above).collect::<Vec<_>>())
+            }
+        }
+    }
+
+    #[test]
+    fn assign_disjoint() {
+        let mut r = 0..5;
+        assert!(!r.intersect_assign(&(7..9)));
+        assert!(r.is_empty());
+        let mut r = 0..5;
+        assert_eq!(r.subtract_assign(&(7..9)), Subtracted::InPlace);
+        assert_eq!(r, 0..5)
+    }
 }
@@ -0,0 +1,371 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Maps from ranges to values, kept sorted, disjoint, and coalesced.
+
+use core::ops::Range;
+
+use crate::storage::{InvariantError, RangeSet, RunStorage};
+use crate::Split;
+
+/** A sorted, disjoint set of ranges, each with a value.
+
+Inserting over existing entries replaces them where they overlap,
+and touching entries with equal values merge.
+
+Usage:
+```
+# use range_split::map::RangeMap;
+let mut map = RangeMap::new();
+map.insert(0..30, 'a');
+map.insert(10..20, 'b');
+assert_eq!(map.as_slice(), &[(0..10, 'a'), (10..20, 'b'), (20..30, 'a')]);
+assert_eq!(map.get(15), Some(&'b'));
+
+// Back to 'a' in the middle merges all three
+map.insert(5..25, 'a');
+assert_eq!(map.as_slice(), &[(0..30, 'a')]);
+
+map.remove(&(10..12));
+assert_eq!(map.query(&(8..15)).collect::<Vec<_>>(), vec![(8..10, &'a'), (12..15, &'a')]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeMap<T, V> {
+    /// Non-empty, sorted, disjoint, and with no touching neighbours of equal value.
+    entries: Vec<(Range<T>, V)>,
+}
+
+impl<T, V> Default for RangeMap<T, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<T, V> RangeMap<T, V>
+where
+    T: Sized + Ord + Copy,
+    V: Clone + PartialEq,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Under the `debug-invariants` feature, called after every change to the entries:
+    /// panic naming `op` and the broken invariant.
+    #[cfg(feature = "debug-invariants")]
+    pub(crate) fn check(&self, op: &str) {
+        if let Err(e) = self.validate() {
+            panic!(
+                "RangeMap::{op} broke the entry invariants at entry {}",
+                e.index()
+            );
+        }
+    }
+
+    /// Map `range` to `value`, replacing whatever it overlaps
+    /// and merging with touching entries of the same value.
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        let i = self.entries.partition_point(|(r, _)| r.end < range.start);
+        let j = self.entries.partition_point(|(r, _)| r.start <= range.end);
+        let mut new = (range, value);
+        let (mut before, mut after) = (None, None);
+        for (r, v) in self.entries.drain(i..j) {
+            let (below, _, above) = r.split(&new.0);
+            // Any remainder touches `new`, as every entry here touches or overlaps it
+            match below {
+                Some(b) if v == new.1 => new.0.start = b.start,
+                Some(b) => before = before.or(Some((b, v.clone()))),
+                None => {}
+            }
+            match above {
+                Some(a) if v == new.1 => new.0.end = a.end,
+                Some(a) => after = after.or(Some((a, v))),
+                None => {}
+            }
+        }
+        let pieces = before.into_iter().chain([new]).chain(after);
+        self.entries.splice(i..i, pieces);
+        #[cfg(feature = "debug-invariants")]
+        self.check("insert");
+    }
+
+    /// Unmap `range`, keeping the values of what's left either side.
+    pub fn remove(&mut self, range: &Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        let i = self.entries.partition_point(|(r, _)| r.end <= range.start);
+        let j = self.entries.partition_point(|(r, _)| r.start < range.end);
+        let kept: Vec<(Range<T>, V)> = self.entries[i..j]
+            .iter()
+            .flat_map(|(r, v)| {
+                let (below, _, above) = r.split(range);
+                below.into_iter().chain(above).map(|p| (p, v.clone()))
+            })
+            .collect();
+        self.entries.splice(i..j, kept);
+        #[cfg(feature = "debug-invariants")]
+        self.check("remove");
+    }
+
+    /// The value at `t`, if any.
+    pub fn get(&self, t: T) -> Option<&V> {
+        self.entry_at(t).map(|(_, v)| v)
+    }
+
+    /// The entry containing `t`, if any.
+    pub fn entry_at(&self, t: T) -> Option<(&Range<T>, &V)> {
+        let i = self.entries.partition_point(|(r, _)| r.end <= t);
+        self.entries
+            .get(i)
+            .filter(|(r, _)| r.start <= t)
+            .map(|(r, v)| (r, v))
+    }
+
+    /// The entries overlapping `range`, whole, in order.
+    pub fn overlapping(&self, range: &Range<T>) -> &[(Range<T>, V)] {
+        // An empty or inverted `range` overlaps nothing
+        if range.start >= range.end {
+            return &[];
+        }
+        let i = self.entries.partition_point(|(r, _)| r.end <= range.start);
+        let j = self.entries.partition_point(|(r, _)| r.start < range.end);
+        &self.entries[i..j.max(i)]
+    }
+
+    /// The entries overlapping `range`, clipped to it, in order.
+    pub fn query<'a>(&'a self, range: &Range<T>) -> impl Iterator<Item = (Range<T>, &'a V)> + 'a {
+        let range = range.clone();
+        self.overlapping(&range)
+            .iter()
+            .filter_map(move |(r, v)| r.split(&range).1.map(|p| (p, v)))
+    }
+
+    /** Check that the entries are non-empty, sorted, disjoint, and coalesced.
+
+    Under the `debug-invariants` feature, this is done after every change, panicking on failure.
+    `Uncoalesced` here means touching entries with equal values.
+    ```
+    # use range_split::map::RangeMap;
+    let map: RangeMap<u32, char> = [(0..5, 'a'), (5..9, 'b')].into_iter().collect();
+    assert_eq!(map.validate(), Ok(()));
+    ```
+    **/
+    pub fn validate(&self) -> Result<(), InvariantError<T>> {
+        let mut previous: Option<&(Range<T>, V)> = None;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let run = entry.0.clone();
+            if run.start >= run.end {
+                return Err(InvariantError::Empty { index, run });
+            }
+            if let Some((prev, value)) = previous.replace(entry) {
+                let previous = prev.clone();
+                if run.start < previous.end {
+                    return Err(InvariantError::OutOfOrder {
+                        index,
+                        previous,
+                        run,
+                    });
+                }
+                if run.start == previous.end && *value == entry.1 {
+                    return Err(InvariantError::Uncoalesced {
+                        index,
+                        previous,
+                        run,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /** Keep only the parts of the entries within `set`, in place.
+
+    ```
+    # use range_split::map::RangeMap;
+    # use range_split::storage::RangeSet;
+    let mut map: RangeMap<u32, char> = [(0..10, 'a'), (10..20, 'b')].into_iter().collect();
+    let window: RangeSet<u32> = [5..12, 15..30].into_iter().collect();
+    map.intersect_assign(&window);
+    assert_eq!(map.as_slice(), &[(5..10, 'a'), (10..12, 'b'), (15..20, 'b')]);
+    map.subtract_assign(&[8..16].into_iter().collect::<RangeSet<u32>>());
+    assert_eq!(map.as_slice(), &[(5..8, 'a'), (16..20, 'b')]);
+    ```
+    **/
+    pub fn intersect_assign<S: RunStorage<T>>(&mut self, set: &RangeSet<T, S>) {
+        let mut other = set.iter().peekable();
+        let n = self.entries.len();
+        for i in 0..n {
+            let (r, v) = self.entries[i].clone();
+            while other.next_if(|o| o.end <= r.start).is_some() {}
+            while let Some(o) = other.peek().filter(|o| o.start < r.end) {
+                if let Some(p) = r.split(o).1 {
+                    self.entries.push((p, v.clone()));
+                }
+                // One reaching past `r` may cover the next entry too
+                if o.end > r.end {
+                    break;
+                }
+                other.next();
+            }
+        }
+        self.entries.drain(..n);
+        #[cfg(feature = "debug-invariants")]
+        self.check("intersect_assign");
+    }
+
+    /// Remove the parts of the entries within `set`, in place.
+    pub fn subtract_assign<S: RunStorage<T>>(&mut self, set: &RangeSet<T, S>) {
+        let mut other = set.iter().peekable();
+        let n = self.entries.len();
+        for i in 0..n {
+            let (r, v) = self.entries[i].clone();
+            while other.next_if(|o| o.end <= r.start).is_some() {}
+            let mut rest = Some(r);
+            while let Some(o) = other.peek() {
+                let Some(r) = rest.take_if(|r| o.start < r.end) else {
+                    break;
+                };
+                let (below, _, above) = r.split(o);
+                self.entries.extend(below.map(|b| (b, v.clone())));
+                rest = above;
+                // Otherwise `o` reaches past `r`, and may cover the next entry too
+                if rest.is_some() {
+                    other.next();
+                }
+            }
+            self.entries.extend(rest.map(|r| (r, v)));
+        }
+        self.entries.drain(..n);
+        #[cfg(feature = "debug-invariants")]
+        self.check("subtract_assign");
+    }
+
+    /// The ranges with a value, whatever it is.
+    pub fn ranges(&self) -> RangeSet<T> {
+        self.entries.iter().map(|(r, _)| r.clone()).collect()
+    }
+}
+
+impl<T, V> RangeMap<T, V> {
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries, in order.
+    pub fn iter(&self) -> core::slice::Iter<'_, (Range<T>, V)> {
+        self.entries.iter()
+    }
+
+    pub fn as_slice(&self) -> &[(Range<T>, V)] {
+        &self.entries
+    }
+
+    pub fn into_vec(self) -> Vec<(Range<T>, V)> {
+        self.entries
+    }
+}
+
+impl<T, V> FromIterator<(Range<T>, V)> for RangeMap<T, V>
+where
+    T: Sized + Ord + Copy,
+    V: Clone + PartialEq,
+{
+    fn from_iter<I: IntoIterator<Item = (Range<T>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// Inserts each entry in turn, so later entries win where they overlap.
+impl<T, V> Extend<(Range<T>, V)> for RangeMap<T, V>
+where
+    T: Sized + Ord + Copy,
+    V: Clone + PartialEq,
+{
+    fn extend<I: IntoIterator<Item = (Range<T>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<'a, T, V> IntoIterator for &'a RangeMap<T, V> {
+    type Item = &'a (Range<T>, V);
+    type IntoIter = core::slice::Iter<'a, (Range<T>, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn different_values_stay_apart() {
+        let mut map = RangeMap::new();
+        map.insert(0..10, 'a');
+        map.insert(10..20, 'b');
+        map.insert(20..30, 'a');
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.entry_at(10), Some((&(10..20), &'b')));
+        assert_eq!(map.get(30), None)
+    }
+
+    #[test]
+    fn split_by_insert_and_remove() {
+        let mut map = RangeMap::new();
+        map.insert(0..30, 1);
+        map.insert(10..20, 2);
+        map.remove(&(15..25));
+        assert_eq!(map.as_slice(), &[(0..10, 1), (10..15, 2), (25..30, 1)]);
+        assert_eq!(map.ranges().iter().collect::<Vec<_>>(), vec![0..15, 25..30]);
+        assert_eq!(map.validate(), Ok(()))
+    }
+
+    #[test]
+    fn query_empty_range() {
+        let map: RangeMap<u32, char> = [(0..20, 'a')].into_iter().collect();
+        let (p, q) = (10, 5);
+        assert_eq!(map.query(&(p..q)).count(), 0);
+        assert_eq!(map.query(&(5..5)).count(), 0)
+    }
+
+    #[test]
+    fn assign_ops_across_entries() {
+        let mut map: RangeMap<u32, u8> = [(0..5, 1), (5..10, 2), (12..20, 3)].into_iter().collect();
+        map.subtract_assign(&[3..6, 13..14].into_iter().collect::<RangeSet<u32>>());
+        assert_eq!(
+            map.as_slice(),
+            &[(0..3, 1), (6..10, 2), (12..13, 3), (14..20, 3)]
+        );
+        map.intersect_assign(&core::iter::once(1..30).collect::<RangeSet<u32>>());
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.validate(), Ok(()))
+    }
+
+    #[test]
+    fn later_entries_win() {
+        let map: RangeMap<i32, char> = [(0..10, 'a'), (5..15, 'b'), (8..9, 'a')]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            map.into_vec(),
+            vec![(0..5, 'a'), (5..8, 'b'), (8..9, 'a'), (9..15, 'b')]
+        )
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Building a normalized physical memory map from overlapping firmware-provided regions,
+//! in the style of the BIOS E820 map.
+
+use core::ops::Range;
+
+use crate::map::RangeMap;
+
+/// The type of a memory region, ordered by priority: when firmware regions overlap,
+/// the greater kind wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RegionKind {
+    Usable,
+    AcpiReclaimable,
+    AcpiNvs,
+    Reserved,
+    Unusable,
+}
+
+/** A physical memory map: a [`RangeMap`] of sorted, disjoint regions, each with a [`RegionKind`].
+Adjacent regions of the same kind are merged.
+
+Usage:
+```
+# use range_split::memmap::{RegionKind::*, RegionMap};
+let mut map = RegionMap::new();
+map.overlay(0x0..0xA_0000, Usable);
+map.overlay(0x9_F000..0x10_0000, Reserved);
+map.overlay(0x10_0000..0x800_0000, Usable);
+// The kernel image is no longer free memory
+map.carve_out(0x10_0000..0x20_0000, Reserved);
+assert_eq!(map.regions().as_slice(), &[
+    (0x0..0x9_F000, Usable),
+    (0x9_F000..0x20_0000, Reserved),
+    (0x20_0000..0x800_0000, Usable),
+]);
+```
+**/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegionMap {
+    regions: RangeMap<u64, RegionKind>,
+}
+
+impl RegionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a firmware-reported region; where it overlaps an existing region,
+    /// the higher-priority kind is kept.
+    pub fn overlay(&mut self, range: Range<u64>, kind: RegionKind) {
+        self.assign(&range, |old| old.map_or(kind, |old| old.max(kind)));
+    }
+
+    /// Set `range` to `kind` regardless of priority, as when reserving the kernel or initrd.
+    pub fn carve_out(&mut self, range: Range<u64>, kind: RegionKind) {
+        self.assign(&range, |_| kind);
+    }
+
+    /// The normalized map.
+    pub fn regions(&self) -> &RangeMap<u64, RegionKind> {
+        &self.regions
+    }
+
+    /// The ranges of the given kind, in order.
+    pub fn ranges_of(&self, kind: RegionKind) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.regions
+            .iter()
+            .filter(move |(_, k)| *k == kind)
+            .map(|(r, _)| r.clone())
+    }
+
+    /// Replace the kind of every part of `range` (including holes, which have no kind) with `f` of it.
+    fn assign(&mut self, range: &Range<u64>, f: impl Fn(Option<RegionKind>) -> RegionKind) {
+        if range.start >= range.end {
+            return;
+        }
+        let mut pieces = Vec::new();
+        let mut cursor = range.start;
+        for (r, kind) in self.regions.query(range) {
+            if cursor < r.start {
+                pieces.push((cursor..r.start, f(None)));
+            }
+            cursor = r.end;
+            pieces.push((r, f(Some(*kind))));
+        }
+        if cursor < range.end {
+            pieces.push((cursor..range.end, f(None)));
+        }
+        self.regions.extend(pieces);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegionKind::*;
+    use super::*;
+
+    #[test]
+    fn lower_priority_does_not_override() {
+        let mut map = RegionMap::new();
+        map.overlay(10..20, Reserved);
+        map.overlay(0..30, Usable);
+        assert_eq!(
+            map.regions().as_slice(),
+            &[(0..10, Usable), (10..20, Reserved), (20..30, Usable)]
+        );
+    }
+
+    #[test]
+    fn higher_priority_overrides() {
+        let mut map = RegionMap::new();
+        map.overlay(0..30, AcpiReclaimable);
+        map.overlay(10..20, AcpiNvs);
+        assert_eq!(map.ranges_of(AcpiNvs).collect::<Vec<_>>(), vec![10..20]);
+    }
+
+    #[test]
+    fn holes_preserved() {
+        let mut map = RegionMap::new();
+        map.overlay(20..30, Usable);
+        map.overlay(0..10, Usable);
+        assert_eq!(
+            map.regions().as_slice(),
+            &[(0..10, Usable), (20..30, Usable)]
+        );
+    }
+
+    #[test]
+    fn carve_out_overrides_priority() {
+        let mut map = RegionMap::new();
+        map.overlay(0..30, Unusable);
+        map.carve_out(5..25, Usable);
+        map.carve_out(0..5, Usable);
+        assert_eq!(
+            map.regions().as_slice(),
+            &[(0..25, Usable), (25..30, Unusable)]
+        );
+    }
+}
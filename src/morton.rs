@@ -0,0 +1,294 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Z-order (Morton) codes, which interleave the bits of two `u32` coordinates into a `u64` key,
+//! and the decomposition of rectangles into ranges of those keys.
+//!
+//! The bits of `x` go in the even positions and those of `y` in the odd ones.
+//! A cell of level `l` is an aligned square of side `2^l`, whose codes form the contiguous
+//! range of length `4^l` starting at the code of its lowest corner.
+
+use core::cmp::Reverse;
+use core::ops::Range;
+
+use crate::rect::Rect;
+use crate::runs;
+
+/// Spread the bits of `v` into the even positions.
+fn spread(v: u32) -> u64 {
+    let mut v = u64::from(v);
+    v = (v | v << 16) & 0x0000_ffff_0000_ffff;
+    v = (v | v << 8) & 0x00ff_00ff_00ff_00ff;
+    v = (v | v << 4) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | v << 2) & 0x3333_3333_3333_3333;
+    (v | v << 1) & 0x5555_5555_5555_5555
+}
+
+/// Gather the even bits of `v`.
+fn gather(v: u64) -> u32 {
+    let mut v = v & 0x5555_5555_5555_5555;
+    v = (v | v >> 1) & 0x3333_3333_3333_3333;
+    v = (v | v >> 2) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | v >> 4) & 0x00ff_00ff_00ff_00ff;
+    v = (v | v >> 8) & 0x0000_ffff_0000_ffff;
+    (v | v >> 16) as u32
+}
+
+/// The Morton code of `(x, y)`.
+pub fn encode(x: u32, y: u32) -> u64 {
+    spread(x) | spread(y) << 1
+}
+
+/// The point with Morton code `code`.
+pub fn decode(code: u64) -> (u32, u32) {
+    (gather(code), gather(code >> 1))
+}
+
+/// The codes of the cell at `code` of level `level`. The very last code, of
+/// `(u32::MAX, u32::MAX)`, is left out of the top cells so that the end fits.
+fn cell_codes(code: u64, level: u32) -> Range<u64> {
+    let end = (u128::from(code) + (1 << (2 * level))).min(u128::from(u64::MAX));
+    code..end as u64
+}
+
+/** The rectangle covered by the cell of level `level` whose lowest corner has code `code`.
+
+`None` if `code` isn't aligned to the level, or the cell reaches `u32::MAX`
+(which a half-open `Rect<u32>` can't include).
+
+```
+# use range_split::{morton::{cell_rect, encode}, rect::Rect};
+assert_eq!(cell_rect(encode(4, 8), 2), Some(Rect::new(4..8, 8..12)));
+assert_eq!(cell_rect(encode(4, 9), 2), None);
+```
+**/
+pub fn cell_rect(code: u64, level: u32) -> Option<Rect<u32>> {
+    if level >= 32 || code & ((1 << (2 * level)) - 1) != 0 {
+        return None;
+    }
+    let (x, y) = decode(code);
+    let side = 1 << level;
+    Some(Rect::new(x..x.checked_add(side)?, y..y.checked_add(side)?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cover {
+    Inside,
+    Partial,
+    Outside,
+}
+
+/// How much of the cell the disjoint `rects` cover between them.
+fn cover(rects: &[Rect<u32>], code: u64, level: u32) -> Cover {
+    let (x, y) = decode(code);
+    let side = 1u64 << level;
+    // Length of the overlap of one side of the cell, from `lo`, with `range`
+    let overlap = |lo: u32, range: &Range<u32>| {
+        let (lo, hi) = (u64::from(lo), u64::from(lo) + side);
+        let (start, end) = (u64::from(range.start).max(lo), u64::from(range.end).min(hi));
+        u128::from(end.saturating_sub(start))
+    };
+    let area: u128 = rects
+        .iter()
+        .map(|r| overlap(x, &r.x) * overlap(y, &r.y))
+        .sum();
+    match area {
+        0 => Cover::Outside,
+        a if a == u128::from(side) * u128::from(side) => Cover::Inside,
+        _ => Cover::Partial,
+    }
+}
+
+/// Merge `ranges` into `max` by closing the smallest gaps between them.
+fn close_gaps(ranges: Vec<Range<u64>>, max: usize) -> Vec<Range<u64>> {
+    let mut gaps: Vec<usize> = (1..ranges.len()).collect();
+    gaps.sort_by_key(|&i| Reverse(ranges[i].start - ranges[i - 1].end));
+    let mut kept = gaps[..max - 1].to_vec();
+    kept.sort_unstable();
+
+    let mut merged = Vec::with_capacity(max);
+    let mut start = ranges[0].start;
+    for i in kept {
+        merged.push(start..ranges[i - 1].end);
+        start = ranges[i].start;
+    }
+    merged.push(start..ranges[ranges.len() - 1].end);
+    merged
+}
+
+/** The Morton codes of the points in `rect`, as at most `max_ranges` sorted, disjoint ranges.
+
+The exact cover may need many ranges, roughly in proportion to the perimeter of `rect`.
+When it needs more than `max_ranges`, the smallest gaps between ranges are closed,
+so the ranges also take in some codes outside `rect` which a query must filter out:
+fewer ranges cost more of this slack. A `max_ranges` of zero is taken as one.
+
+```
+# use range_split::{morton::{encode, rect_ranges}, rect::Rect};
+// The aligned 2 × 2 square at (2, 2) is a single cell
+assert_eq!(rect_ranges(&Rect::new(2..4, 2..4), 8), vec![12..16]);
+
+// Two cells side by side aren't contiguous in Z-order
+let rect = Rect::new(0..2, 0..4);
+assert_eq!(rect_ranges(&rect, 8), vec![0..4, 8..12]);
+// ...unless the gap between them is let in
+assert_eq!(rect_ranges(&rect, 1), vec![0..12]);
+```
+**/
+pub fn rect_ranges(rect: &Rect<u32>, max_ranges: usize) -> Vec<Range<u64>> {
+    if rect.is_empty() {
+        return Vec::new();
+    }
+    let max_ranges = max_ranges.max(1);
+    let mut inside = Vec::new();
+    // Cells of the current level that `rect` only partly covers, which are refined level by level
+    let mut partial = vec![0];
+    let mut level = 32;
+    while !partial.is_empty() {
+        level -= 1;
+        let children = partial
+            .iter()
+            .flat_map(|&code| (0..4).map(move |q| code | q << (2 * level)));
+        let mut next = Vec::new();
+        for child in children {
+            match cover(core::slice::from_ref(rect), child, level) {
+                Cover::Inside => runs::insert(&mut inside, cell_codes(child, level)),
+                Cover::Partial => next.push(child),
+                Cover::Outside => {}
+            }
+        }
+        partial = next;
+
+        let mut ranges = inside.clone();
+        for &code in &partial {
+            runs::insert(&mut ranges, cell_codes(code, level));
+        }
+        if ranges.len() > max_ranges {
+            return close_gaps(ranges, max_ranges);
+        }
+    }
+    inside
+}
+
+/** A cell of the quadtree over the `u32 × u32` plane: the root, at depth 0, is the whole plane,
+and each cell at depth `d` has four children at depth `d + 1`, of half the side.
+
+From [`Rect::to_quad_cells`] and [`Region2D::to_quad_cells`](crate::region::Region2D::to_quad_cells).
+
+```
+# use range_split::{morton::{encode, QuadCell}, rect::Rect};
+let cell = QuadCell { depth: 30, code: encode(4, 8) };
+assert_eq!(cell.level(), 2);
+assert_eq!(cell.codes(), 144..160);
+assert_eq!(cell.rect(), Some(Rect::new(4..8, 8..12)));
+```
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QuadCell {
+    pub depth: u32,
+    /// The Morton code of the cell's lowest corner.
+    pub code: u64,
+}
+
+impl QuadCell {
+    /// The level of the cell, whose side is `2^level`: `32 - depth`.
+    pub fn level(&self) -> u32 {
+        32 - self.depth
+    }
+
+    /// The Morton codes of the points in the cell.
+    /// That of `(u32::MAX, u32::MAX)` is left out, as for [`rect_ranges`].
+    pub fn codes(&self) -> Range<u64> {
+        cell_codes(self.code, self.level())
+    }
+
+    /// The rectangle the cell covers, as for [`cell_rect`].
+    pub fn rect(&self) -> Option<Rect<u32>> {
+        cell_rect(self.code, self.level())
+    }
+}
+
+/// Push the cells under the cell at `code` of `level` that the disjoint `rects` cover, in Z-order.
+pub(crate) fn quad_cells(
+    rects: &[Rect<u32>],
+    code: u64,
+    level: u32,
+    min_level: u32,
+    out: &mut Vec<QuadCell>,
+) {
+    let cell = QuadCell {
+        depth: 32 - level,
+        code,
+    };
+    match cover(rects, code, level) {
+        Cover::Outside => {}
+        Cover::Partial if level > min_level => {
+            for q in 0..4 {
+                quad_cells(
+                    rects,
+                    code | q << (2 * (level - 1)),
+                    level - 1,
+                    min_level,
+                    out,
+                );
+            }
+        }
+        _ => out.push(cell),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_round_trip() {
+        assert_eq!(encode(0b11, 0b01), 0b0111);
+        for (x, y) in [(0, 0), (1, 2), (u32::MAX, 0), (12_345, u32::MAX)] {
+            assert_eq!(decode(encode(x, y)), (x, y));
+        }
+    }
+
+    /// The codes in `ranges` that are inside `rect`, and how many aren't.
+    fn tally(rect: &Rect<u32>, ranges: &[Range<u64>]) -> (Vec<u64>, usize) {
+        let codes: Vec<u64> = ranges.iter().flat_map(|r| r.clone()).collect();
+        let (x, y): (Vec<u64>, Vec<u64>) = codes.iter().partition(|&&c| {
+            let (x, y) = decode(c);
+            rect.contains(x, y)
+        });
+        (x, y.len())
+    }
+
+    #[test]
+    fn exact_and_bounded_covers() {
+        let rect = Rect::new(3..13, 5..9);
+        let mut expected: Vec<u64> = (3..13)
+            .flat_map(|x| (5..9).map(move |y| encode(x, y)))
+            .collect();
+        expected.sort_unstable();
+
+        let exact = rect_ranges(&rect, usize::MAX);
+        assert_eq!(tally(&rect, &exact), (expected.clone(), 0));
+
+        let mut slack = 0;
+        for max in (1..exact.len()).rev() {
+            let bounded = rect_ranges(&rect, max);
+            assert!(bounded.len() <= max);
+            assert!(bounded.windows(2).all(|w| w[0].end < w[1].start));
+            let (inside, outside) = tally(&rect, &bounded);
+            assert_eq!(inside, expected);
+            // Fewer ranges never means less slack
+            assert!(outside >= slack);
+            slack = outside;
+        }
+    }
+
+    #[test]
+    fn far_corner() {
+        let rect = Rect::new(u32::MAX - 2..u32::MAX, u32::MAX - 1..u32::MAX);
+        let ranges = rect_ranges(&rect, 4);
+        assert_eq!(tally(&rect, &ranges).0.len(), 2);
+        assert_eq!(cell_rect(encode(u32::MAX - 1, u32::MAX - 1), 1), None);
+        assert!(rect_ranges(&Rect::new(0..0, 0..5), 4).is_empty())
+    }
+}
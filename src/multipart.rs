@@ -0,0 +1,170 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Planning the parts of a multipart upload, as used by S3 and similar object stores.
+
+use core::fmt;
+use core::ops::Range;
+
+/// Limits on the parts of an upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartConstraints {
+    /// The smallest size allowed for any part but the last.
+    pub min_part: u64,
+    /// The largest size allowed for any part.
+    pub max_part: u64,
+    /// The most parts allowed.
+    pub max_parts: u64,
+}
+
+impl PartConstraints {
+    /// The limits for Amazon S3: 5 MiB to 5 GiB per part, and at most 10,000 parts.
+    pub const S3: Self = Self {
+        min_part: 5 << 20,
+        max_part: 5 << 30,
+        max_parts: 10_000,
+    };
+}
+
+/// Why [`plan_parts`] couldn't find a plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    /// `max_part` is zero or less than `min_part`, or `max_parts` is zero.
+    BadConstraints(PartConstraints),
+    /// Even parts of `max_part` bytes would need `needed` parts, more than `max_parts`.
+    TooManyParts { needed: u64, max_parts: u64 },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::BadConstraints(c) => write!(
+                f,
+                "unsatisfiable part constraints: {} to {} bytes, at most {} parts",
+                c.min_part, c.max_part, c.max_parts
+            ),
+            PlanError::TooManyParts { needed, max_parts } => write!(
+                f,
+                "upload needs {needed} parts, but at most {max_parts} are allowed"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/** Split `total_len` bytes into consecutive parts that satisfy `constraints`,
+using as few parts as possible and making them as close to equal in size as possible.
+
+```
+# use range_split::multipart::{plan_parts, PartConstraints};
+let limits = PartConstraints { min_part: 10, max_part: 40, max_parts: 100 };
+let parts = plan_parts(100, limits).unwrap();
+assert_eq!(parts, vec![0..34, 34..67, 67..100]);
+```
+An empty upload has no parts.
+**/
+pub fn plan_parts(
+    total_len: u64,
+    constraints: PartConstraints,
+) -> Result<Vec<Range<u64>>, PlanError> {
+    let PartConstraints {
+        min_part,
+        max_part,
+        max_parts,
+    } = constraints;
+    if max_part == 0 || max_part < min_part || max_parts == 0 {
+        return Err(PlanError::BadConstraints(constraints));
+    }
+    let needed = total_len.div_ceil(max_part);
+    if needed > max_parts {
+        return Err(PlanError::TooManyParts { needed, max_parts });
+    }
+    if needed <= 1 {
+        return Ok((total_len > 0)
+            .then_some(0..total_len)
+            .into_iter()
+            .collect());
+    }
+
+    let (size, extra) = (total_len / needed, total_len % needed);
+    let mut parts = Vec::new();
+    let mut start = 0;
+    if size >= min_part {
+        // The first `extra` parts take one more byte each
+        for i in 0..needed {
+            let end = start + size + u64::from(i < extra);
+            parts.push(start..end);
+            start = end;
+        }
+    } else {
+        // Equal parts would be too small, so fill parts to the maximum and leave a short last part
+        while start < total_len {
+            let end = start + (total_len - start).min(max_part);
+            parts.push(start..end);
+            start = end;
+        }
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: PartConstraints = PartConstraints {
+        min_part: 10,
+        max_part: 40,
+        max_parts: 4,
+    };
+
+    #[test]
+    fn single_part() {
+        assert_eq!(
+            plan_parts(5, LIMITS).unwrap(),
+            core::slice::from_ref(&(0..5))
+        );
+        assert_eq!(
+            plan_parts(40, LIMITS).unwrap(),
+            core::slice::from_ref(&(0..40))
+        );
+        assert_eq!(plan_parts(0, LIMITS), Ok(vec![]));
+    }
+
+    #[test]
+    fn too_many_parts() {
+        assert_eq!(
+            plan_parts(161, LIMITS),
+            Err(PlanError::TooManyParts {
+                needed: 5,
+                max_parts: 4
+            })
+        );
+        assert_eq!(plan_parts(160, LIMITS).map(|p| p.len()), Ok(4));
+    }
+
+    #[test]
+    fn falls_back_to_full_parts() {
+        let tight = PartConstraints {
+            min_part: 38,
+            ..LIMITS
+        };
+        assert_eq!(plan_parts(81, tight), Ok(vec![0..40, 40..80, 80..81]));
+    }
+
+    #[test]
+    fn bad_constraints() {
+        let bad = PartConstraints {
+            min_part: 50,
+            ..LIMITS
+        };
+        assert_eq!(plan_parts(100, bad), Err(PlanError::BadConstraints(bad)));
+    }
+
+    #[test]
+    fn s3_limit() {
+        let parts = plan_parts(1 << 40, PartConstraints::S3).unwrap();
+        assert!(parts.len() <= 10_000);
+        assert_eq!(parts.last().unwrap().end, 1 << 40);
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Detecting overlaps among named ranges, such as linker sections or register blocks.
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::Split;
+
+/// Two named ranges overlap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlapError<N, T> {
+    /// The name of whichever of the pair came first in the input.
+    pub first: N,
+    pub second: N,
+    /// The part of the two ranges that overlaps.
+    pub overlap: Range<T>,
+}
+
+impl<N: fmt::Display, T: fmt::Debug> fmt::Display for OverlapError<N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` overlaps `{}` at {:?}",
+            self.first, self.second, self.overlap
+        )
+    }
+}
+
+impl<N: fmt::Display + fmt::Debug, T: fmt::Debug> std::error::Error for OverlapError<N, T> {}
+
+/** Check that none of the named ranges overlap, reporting every overlapping pair.
+
+Overlaps are reported in order of where they start, then by input order of the pair.
+Empty ranges never overlap anything.
+
+```
+# use range_split::overlap::{check_overlaps, OverlapError};
+let sections = [(".text", 0x1000..0x2000), (".data", 0x3000..0x3800), (".rodata", 0x1F00..0x3100)];
+let errors = check_overlaps(&sections).unwrap_err();
+assert_eq!(errors, vec![
+    OverlapError { first: ".text", second: ".rodata", overlap: 0x1F00..0x2000 },
+    OverlapError { first: ".data", second: ".rodata", overlap: 0x3000..0x3100 },
+]);
+assert_eq!(errors[0].to_string(), "`.text` overlaps `.rodata` at 7936..8192");
+```
+**/
+pub fn check_overlaps<N, T>(items: &[(N, Range<T>)]) -> Result<(), Vec<OverlapError<N, T>>>
+where
+    N: Clone,
+    T: Sized + Ord + Copy,
+{
+    let mut order: Vec<usize> = (0..items.len())
+        .filter(|&i| items[i].1.start < items[i].1.end)
+        .collect();
+    order.sort_by_key(|&i| (items[i].1.start, i));
+
+    // (overlap start, first index, second index, overlap)
+    let mut found = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+    for &i in &order {
+        let range = &items[i].1;
+        active.retain(|&a| items[a].1.end > range.start);
+        for &a in &active {
+            let overlap = range.split(&items[a].1).1.expect("active ranges overlap");
+            let (first, second) = (a.min(i), a.max(i));
+            found.push((overlap.start, first, second, overlap));
+        }
+        active.push(i);
+    }
+    if found.is_empty() {
+        return Ok(());
+    }
+    found.sort_by_key(|&(start, first, second, _)| (start, first, second));
+    Err(found
+        .into_iter()
+        .map(|(_, first, second, overlap)| OverlapError {
+            first: items[first].0.clone(),
+            second: items[second].0.clone(),
+            overlap,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disjoint_and_touching() {
+        assert_eq!(
+            check_overlaps(&[("a", 0..10), ("b", 10..20), ("c", 30..40)]),
+            Ok(())
+        )
+    }
+
+    #[test]
+    fn empty_inside() {
+        assert_eq!(check_overlaps(&[("a", 0..10), ("b", 5..5)]), Ok(()))
+    }
+
+    #[test]
+    fn nested_triple() {
+        let errors =
+            check_overlaps(&[("outer", 0..100), ("mid", 10..50), ("inner", 20..30)]).unwrap_err();
+        let pairs: Vec<_> = errors
+            .iter()
+            .map(|e| (e.first, e.second, e.overlap.clone()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("outer", "mid", 10..50),
+                ("outer", "inner", 20..30),
+                ("mid", "inner", 20..30)
+            ]
+        );
+    }
+
+    #[test]
+    fn identical() {
+        let errors = check_overlaps(&[("x", 4..8), ("y", 4..8)]).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![OverlapError {
+                first: "x",
+                second: "y",
+                overlap: 4..8
+            }]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Page alignment for ranges of memory addresses.
+//!
+//! All arithmetic is checked, so ranges near the top of the address space
+//! give `None` rather than wrapping. Panics if `page_size` is zero.
+
+use core::ops::Range;
+
+use crate::grid::split_periodic;
+
+/// Whether both ends of `range` are multiples of `page_size`.
+pub fn is_page_aligned(range: &Range<usize>, page_size: usize) -> bool {
+    range.start.is_multiple_of(page_size) && range.end.is_multiple_of(page_size)
+}
+
+/** Grow `range` to the whole pages it touches, as `mprotect` would affect.
+
+An empty range stays empty. Returns `None` if the last page extends past `usize::MAX`.
+
+```
+# use range_split::pages::page_align_outer;
+assert_eq!(page_align_outer(&(0x1234..0x2001), 0x1000), Some(0x1000..0x3000));
+assert_eq!(page_align_outer(&(usize::MAX - 5..usize::MAX), 0x1000), None);
+```
+**/
+pub fn page_align_outer(range: &Range<usize>, page_size: usize) -> Option<Range<usize>> {
+    let start = range.start - range.start % page_size;
+    if range.start >= range.end {
+        return Some(start..start);
+    }
+    Some(start..range.end.checked_next_multiple_of(page_size)?)
+}
+
+/** Shrink `range` to the whole pages it contains.
+
+Returns `None` if it contains no whole page.
+
+```
+# use range_split::pages::page_align_inner;
+assert_eq!(page_align_inner(&(0x1234..0x3001), 0x1000), Some(0x2000..0x3000));
+assert_eq!(page_align_inner(&(0x1234..0x2001), 0x1000), None);
+```
+**/
+pub fn page_align_inner(range: &Range<usize>, page_size: usize) -> Option<Range<usize>> {
+    let start = range.start.checked_next_multiple_of(page_size)?;
+    let end = range.end - range.end % page_size;
+    (start < end).then_some(start..end)
+}
+
+/** Cut `range` at every page boundary strictly inside it.
+
+```
+# use range_split::pages::split_at_page_boundaries;
+let pieces: Vec<_> = split_at_page_boundaries(&(0xFF0..0x2010), 0x1000).collect();
+assert_eq!(pieces, vec![0xFF0..0x1000, 0x1000..0x2000, 0x2000..0x2010]);
+```
+**/
+pub fn split_at_page_boundaries(
+    range: &Range<usize>,
+    page_size: usize,
+) -> impl Iterator<Item = Range<usize>> {
+    split_periodic(range, 0, page_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: usize = 4096;
+
+    #[test]
+    fn aligned() {
+        assert!(is_page_aligned(&(PAGE..3 * PAGE), PAGE));
+        assert!(!is_page_aligned(&(PAGE..3 * PAGE + 1), PAGE));
+    }
+
+    #[test]
+    fn already_aligned_unchanged() {
+        let r = PAGE..2 * PAGE;
+        assert_eq!(page_align_outer(&r, PAGE), Some(r.clone()));
+        assert_eq!(page_align_inner(&r, PAGE), Some(r));
+    }
+
+    #[test]
+    fn top_of_address_space() {
+        let top = usize::MAX - PAGE + 1;
+        assert_eq!(page_align_inner(&(top - 1..usize::MAX), PAGE), None);
+        assert_eq!(page_align_outer(&(top..top), PAGE), Some(top..top));
+        assert_eq!(
+            split_at_page_boundaries(&(top - 1..usize::MAX), PAGE).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn empty_outer() {
+        assert_eq!(page_align_outer(&(10..10), PAGE), Some(0..0))
+    }
+}
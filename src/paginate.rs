@@ -0,0 +1,108 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Splitting a range of offsets into pages, as for a paginated API.
+//!
+//! Pages are counted from `total.start`, so every page but the last holds
+//! exactly `page_size` offsets. Panics if `page_size` is zero.
+
+use core::ops::Range;
+
+/// One page of a range, from [`paginate`] or [`page_containing`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// The zero-based page number.
+    pub index: u64,
+    /// The offsets on this page.
+    pub range: Range<u64>,
+    /// Whether no pages follow this one.
+    pub is_last: bool,
+}
+
+/// The number of pages in `total`.
+fn page_count(total: &Range<u64>, page_size: u64) -> u64 {
+    assert!(page_size > 0, "page size must be positive");
+    total.end.saturating_sub(total.start).div_ceil(page_size)
+}
+
+fn page(total: &Range<u64>, page_size: u64, index: u64, count: u64) -> Page {
+    let start = total.start + index * page_size;
+    let end = start + (total.end - start).min(page_size);
+    Page {
+        index,
+        range: start..end,
+        is_last: index + 1 == count,
+    }
+}
+
+/** The pages of `total`, in order. An empty range has no pages.
+
+```
+# use range_split::paginate::{paginate, Page};
+let pages: Vec<_> = paginate(&(100..225), 50).collect();
+assert_eq!(pages.len(), 3);
+assert_eq!(pages[2], Page { index: 2, range: 200..225, is_last: true });
+```
+**/
+pub fn paginate(total: &Range<u64>, page_size: u64) -> impl Iterator<Item = Page> {
+    let count = page_count(total, page_size);
+    let total = total.clone();
+    (0..count).map(move |i| page(&total, page_size, i, count))
+}
+
+/** The page holding `offset`, or `None` if it's outside `total`.
+
+```
+# use range_split::paginate::page_containing;
+let page = page_containing(&(100..225), 50, 199).unwrap();
+assert_eq!((page.index, page.range), (1, 150..200));
+assert_eq!(page_containing(&(100..225), 50, 225), None);
+```
+**/
+pub fn page_containing(total: &Range<u64>, page_size: u64, offset: u64) -> Option<Page> {
+    let count = page_count(total, page_size);
+    total.contains(&offset).then(|| {
+        let index = (offset - total.start) / page_size;
+        page(total, page_size, index, count)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_multiple() {
+        let pages: Vec<_> = paginate(&(0..20), 10).map(|p| p.range).collect();
+        assert_eq!(pages, vec![0..10, 10..20]);
+    }
+
+    #[test]
+    fn only_last_is_last() {
+        let last: Vec<_> = paginate(&(0..21), 10).map(|p| p.is_last).collect();
+        assert_eq!(last, vec![false, false, true]);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(paginate(&(5..5), 10).count(), 0);
+        assert_eq!(page_containing(&(5..5), 10, 5), None);
+    }
+
+    #[test]
+    fn near_the_top() {
+        let total = u64::MAX - 15..u64::MAX;
+        let pages: Vec<_> = paginate(&total, 10).collect();
+        assert_eq!(pages[1].range, u64::MAX - 5..u64::MAX);
+        assert_eq!(
+            page_containing(&total, 10, u64::MAX - 1),
+            Some(pages[1].clone())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_page_size() {
+        let _ = paginate(&(0..10), 0);
+    }
+}
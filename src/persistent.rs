@@ -0,0 +1,447 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! An immutable range set whose versions share storage.
+
+use core::fmt;
+use core::ops::Range;
+use std::sync::Arc;
+
+use crate::runs;
+
+/// The most runs kept in one shared chunk.
+const CHUNK: usize = 32;
+
+/// A node of an AVL tree of chunks, ordered by their runs.
+struct Node<T> {
+    left: Tree<T>,
+    /// Never empty.
+    chunk: Chunk<T>,
+    right: Tree<T>,
+    height: u32,
+    /// Number of runs in the subtree.
+    len: usize,
+}
+
+type Tree<T> = Option<Arc<Node<T>>>;
+
+type Chunk<T> = Arc<[Range<T>]>;
+
+fn height<T>(t: &Tree<T>) -> u32 {
+    t.as_ref().map_or(0, |n| n.height)
+}
+
+fn len<T>(t: &Tree<T>) -> usize {
+    t.as_ref().map_or(0, |n| n.len)
+}
+
+fn node<T>(left: Tree<T>, chunk: Chunk<T>, right: Tree<T>) -> Tree<T> {
+    Some(Arc::new(Node {
+        height: height(&left).max(height(&right)) + 1,
+        len: len(&left) + chunk.len() + len(&right),
+        left,
+        chunk,
+        right,
+    }))
+}
+
+/// The parts of a node, sharing its children.
+fn parts<T>(n: &Node<T>) -> (Tree<T>, Chunk<T>, Tree<T>) {
+    (n.left.clone(), n.chunk.clone(), n.right.clone())
+}
+
+fn rotate_left<T>(t: Tree<T>) -> Tree<T> {
+    let (a, x, r) = parts(t.as_deref().expect("rotating a node"));
+    let (b, y, c) = parts(r.as_deref().expect("rotating left with a right child"));
+    node(node(a, x, b), y, c)
+}
+
+fn rotate_right<T>(t: Tree<T>) -> Tree<T> {
+    let (l, y, c) = parts(t.as_deref().expect("rotating a node"));
+    let (a, x, b) = parts(l.as_deref().expect("rotating right with a left child"));
+    node(a, x, node(b, y, c))
+}
+
+/// `left`, then `chunk`, then `right`, rebalanced, where `left` is the higher.
+fn join_right<T>(left: &Node<T>, chunk: Chunk<T>, right: Tree<T>) -> Tree<T> {
+    let (ll, lc, lr) = parts(left);
+    let inner = match lr.as_deref() {
+        Some(lr) if lr.height > height(&right) + 1 => join_right(lr, chunk, right),
+        _ => node(lr, chunk, right),
+    };
+    if height(&inner) <= height(&ll) + 1 {
+        node(ll, lc, inner)
+    } else if height(&inner.as_ref().expect("joined").right) > height(&ll) {
+        rotate_left(node(ll, lc, inner))
+    } else {
+        rotate_left(node(ll, lc, rotate_right(inner)))
+    }
+}
+
+/// `left`, then `chunk`, then `right`, rebalanced, where `right` is the higher.
+fn join_left<T>(left: Tree<T>, chunk: Chunk<T>, right: &Node<T>) -> Tree<T> {
+    let (rl, rc, rr) = parts(right);
+    let inner = match rl.as_deref() {
+        Some(rl) if rl.height > height(&left) + 1 => join_left(left, chunk, rl),
+        _ => node(left, chunk, rl),
+    };
+    if height(&inner) <= height(&rr) + 1 {
+        node(inner, rc, rr)
+    } else if height(&inner.as_ref().expect("joined").left) > height(&rr) {
+        rotate_right(node(inner, rc, rr))
+    } else {
+        rotate_right(node(rotate_left(inner), rc, rr))
+    }
+}
+
+/// The chunks of `left`, then `chunk`, then those of `right`, as a balanced tree.
+fn join<T>(left: Tree<T>, chunk: Chunk<T>, right: Tree<T>) -> Tree<T> {
+    match (left.as_deref(), right.as_deref()) {
+        (Some(l), _) if l.height > height(&right) + 1 => join_right(l, chunk, right),
+        (_, Some(r)) if r.height > height(&left) + 1 => join_left(left, chunk, r),
+        _ => node(left, chunk, right),
+    }
+}
+
+/// The first chunk of `t`, and the tree of the rest.
+fn split_first<T>(t: &Tree<T>) -> Option<(Chunk<T>, Tree<T>)> {
+    let n = t.as_deref()?;
+    Some(match split_first(&n.left) {
+        None => (n.chunk.clone(), n.right.clone()),
+        Some((first, rest)) => (first, join(rest, n.chunk.clone(), n.right.clone())),
+    })
+}
+
+/// The tree of all but the last chunk of `t`, and the last chunk.
+fn split_last<T>(t: &Tree<T>) -> Option<(Tree<T>, Chunk<T>)> {
+    let n = t.as_deref()?;
+    Some(match split_last(&n.right) {
+        None => (n.left.clone(), n.chunk.clone()),
+        Some((rest, last)) => (join(n.left.clone(), n.chunk.clone(), rest), last),
+    })
+}
+
+/// The chunks of `left` then those of `right`, as a balanced tree.
+fn concat<T>(left: Tree<T>, right: Tree<T>) -> Tree<T> {
+    match split_first(&right) {
+        None => left,
+        Some((first, rest)) => join(left, first, rest),
+    }
+}
+
+/// Split `t` into the chunks for which `before` holds, which must come first, and the rest.
+fn split<T>(t: &Tree<T>, before: &impl Fn(&[Range<T>]) -> bool) -> (Tree<T>, Tree<T>) {
+    let Some(n) = t.as_deref() else {
+        return (None, None);
+    };
+    if before(&n.chunk) {
+        let (a, b) = split(&n.right, before);
+        (join(n.left.clone(), n.chunk.clone(), a), b)
+    } else {
+        let (a, b) = split(&n.left, before);
+        (a, join(b, n.chunk.clone(), n.right.clone()))
+    }
+}
+
+/// A balanced tree of `chunks`, in order.
+fn build<T>(chunks: &[Chunk<T>]) -> Tree<T> {
+    if chunks.is_empty() {
+        return None;
+    }
+    let mid = chunks.len() / 2;
+    node(
+        build(&chunks[..mid]),
+        chunks[mid].clone(),
+        build(&chunks[mid + 1..]),
+    )
+}
+
+/// Push the chunks of `t` onto `out`, in order.
+fn collect_chunks<'a, T>(t: &'a Tree<T>, out: &mut Vec<&'a Chunk<T>>) {
+    if let Some(n) = t.as_deref() {
+        collect_chunks(&n.left, out);
+        out.push(&n.chunk);
+        collect_chunks(&n.right, out);
+    }
+}
+
+/// The runs of a tree, in order.
+struct Iter<'a, T> {
+    /// The nodes whose chunk and right subtree are still to come, innermost last.
+    stack: Vec<&'a Node<T>>,
+    runs: core::slice::Iter<'a, Range<T>>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn new(root: &'a Tree<T>) -> Self {
+        let mut iter = Self {
+            stack: Vec::new(),
+            runs: [].iter(),
+        };
+        iter.push_left(root);
+        iter
+    }
+
+    fn push_left(&mut self, mut t: &'a Tree<T>) {
+        while let Some(n) = t.as_deref() {
+            self.stack.push(n);
+            t = &n.left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(r) = self.runs.next() {
+                return Some(r);
+            }
+            let n = self.stack.pop()?;
+            self.runs = n.chunk.iter();
+            self.push_left(&n.right);
+        }
+    }
+}
+
+/** A sorted, disjoint, coalesced set of ranges where [`insert`](Self::insert) and
+[`remove`](Self::remove) return a new set rather than changing this one.
+
+The runs are kept in fixed-size shared chunks, at the nodes of a balanced tree.
+A new version copies only the chunks an operation touches and `O(log n)` nodes above them,
+so keeping many snapshots costs little more than their differences.
+
+Usage:
+```
+# use range_split::persistent::PersistentRangeSet;
+let v1 = PersistentRangeSet::new().insert(0..10).insert(20..30);
+let v2 = v1.remove(&(5..25));
+assert_eq!(v1.iter().collect::<Vec<_>>(), vec![&(0..10), &(20..30)]);
+assert_eq!(v2.iter().collect::<Vec<_>>(), vec![&(0..5), &(25..30)]);
+```
+**/
+pub struct PersistentRangeSet<T> {
+    root: Tree<T>,
+}
+
+impl<T> Clone for PersistentRangeSet<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T> Default for PersistentRangeSet<T> {
+    fn default() -> Self {
+        Self { root: None }
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentRangeSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Iter::new(&self.root).eq(Iter::new(&other.root))
+    }
+}
+
+impl<T: Eq> Eq for PersistentRangeSet<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentRangeSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(Iter::new(&self.root)).finish()
+    }
+}
+
+impl<T> PersistentRangeSet<T>
+where
+    T: Sized + Ord + Copy,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the chunks of `mid` with the runs they hold, after `edit`, between `left` and `right`.
+    fn rebuild(
+        left: Tree<T>,
+        mid: &Tree<T>,
+        right: Tree<T>,
+        edit: impl FnOnce(&mut Vec<Range<T>>),
+    ) -> Self {
+        let mut chunks = Vec::new();
+        collect_chunks(mid, &mut chunks);
+        let mut affected: Vec<Range<T>> = chunks.iter().flat_map(|c| c.iter().cloned()).collect();
+        edit(&mut affected);
+        let rebuilt: Vec<Chunk<T>> = affected.chunks(CHUNK).map(Arc::from).collect();
+        Self {
+            root: concat(concat(left, build(&rebuilt)), right),
+        }
+    }
+
+    /// A new set that also contains `range`.
+    pub fn insert(&self, range: impl Into<Range<T>>) -> Self {
+        let range = range.into();
+        if range.start >= range.end {
+            return self.clone();
+        }
+        let (mut left, rest) = split(&self.root, &|c| c[c.len() - 1].end < range.start);
+        let (mut mid, mut right) = split(&rest, &|c| c[0].start <= range.end);
+        // With no chunk to merge into, add the run to a neighbouring one
+        if mid.is_none() {
+            if let Some((first, rest)) = split_first(&right) {
+                (mid, right) = (node(None, first, None), rest);
+            } else if let Some((rest, last)) = split_last(&left) {
+                (left, mid) = (rest, node(None, last, None));
+            }
+        }
+        Self::rebuild(left, &mid, right, |runs| runs::insert(runs, range))
+    }
+
+    /// A new set without `range`.
+    pub fn remove(&self, range: &Range<T>) -> Self {
+        if range.start >= range.end {
+            return self.clone();
+        }
+        let (left, rest) = split(&self.root, &|c| c[c.len() - 1].end <= range.start);
+        let (mid, right) = split(&rest, &|c| c[0].start < range.end);
+        if mid.is_none() {
+            return self.clone();
+        }
+        Self::rebuild(left, &mid, right, |runs| runs::remove(runs, range))
+    }
+
+    pub fn contains(&self, t: T) -> bool {
+        let mut at = &self.root;
+        while let Some(n) = at.as_deref() {
+            at = if n.chunk[n.chunk.len() - 1].end <= t {
+                &n.right
+            } else if t < n.chunk[0].start {
+                &n.left
+            } else {
+                return runs::containing(&n.chunk, t).is_some();
+            };
+        }
+        false
+    }
+
+    /// Number of disjoint runs.
+    pub fn len(&self) -> usize {
+        len(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// The runs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        Iter::new(&self.root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn many() -> PersistentRangeSet<u32> {
+        (0..100).fold(PersistentRangeSet::new(), |s, i| {
+            s.insert(i * 10..i * 10 + 5)
+        })
+    }
+
+    #[test]
+    fn coalesces() {
+        let s = PersistentRangeSet::new()
+            .insert(0..5)
+            .insert(10..15)
+            .insert(5..10);
+        assert_eq!(s.len(), 1);
+        assert!(s.contains(14) && !s.contains(15));
+    }
+
+    /// Every node of `t`.
+    fn nodes<T>(t: &Tree<T>, out: &mut Vec<*const Node<T>>) {
+        if let Some(n) = t.as_deref() {
+            out.push(n);
+            nodes(&n.left, out);
+            nodes(&n.right, out);
+        }
+    }
+
+    /// Check the AVL balance and cached lengths of `t`, returning its height.
+    fn check<T>(t: &Tree<T>) -> u32 {
+        let Some(n) = t.as_deref() else {
+            return 0;
+        };
+        let (l, r) = (check(&n.left), check(&n.right));
+        assert!(l.abs_diff(r) <= 1);
+        assert_eq!(n.height, l.max(r) + 1);
+        assert_eq!(n.len, len(&n.left) + n.chunk.len() + len(&n.right));
+        n.height
+    }
+
+    #[test]
+    fn versions_share_untouched_nodes() {
+        let old = (0..10_000).fold(PersistentRangeSet::new(), |s, i| {
+            s.insert(i * 10..i * 10 + 5)
+        });
+        assert_eq!(old.len(), 10_000);
+        let h = check(&old.root);
+        assert!(h <= 14, "height {h}");
+
+        let mut kept = Vec::new();
+        nodes(&old.root, &mut kept);
+        for new in [
+            old.insert(99_995..100_000),
+            old.insert(50_002..50_008),
+            old.remove(&(3..4)),
+        ] {
+            check(&new.root);
+            let mut all = Vec::new();
+            nodes(&new.root, &mut all);
+            let copied = all.iter().filter(|n| !kept.contains(n)).count();
+            assert!(copied <= 3 * h as usize, "{copied} nodes copied");
+        }
+        let new = old.insert(99_995..100_000);
+        assert_eq!(new.iter().last(), Some(&(99_990..100_000)));
+        assert_eq!(old.iter().last(), Some(&(99_990..99_995)));
+    }
+
+    #[test]
+    fn stays_balanced() {
+        let mut s = PersistentRangeSet::new();
+        let mut x: u32 = 7;
+        let mut expected = Vec::new();
+        for step in 0..3000 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let start = (x >> 8) % 20_000;
+            let range = start..start + (x >> 4) % 7;
+            if step % 4 == 0 {
+                s = s.remove(&range);
+                runs::remove(&mut expected, &range);
+            } else {
+                s = s.insert(range.clone());
+                runs::insert(&mut expected, range);
+            }
+            check(&s.root);
+        }
+        assert_eq!(s.iter().cloned().collect::<Vec<_>>(), expected);
+        assert_eq!(s.len(), expected.len());
+        assert!(expected
+            .iter()
+            .all(|r| s.contains(r.start) && !s.contains(r.end)));
+    }
+
+    #[test]
+    fn remove_across_chunks() {
+        let s = many().remove(&(2..993));
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![&(0..2), &(993..995)]);
+        assert!(s.remove(&(0..1000)).is_empty());
+    }
+
+    #[test]
+    fn remove_in_gap_is_a_no_op() {
+        let s = many();
+        assert_eq!(s.remove(&(5..10)), s);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Conversion between BitTorrent-style piece bitfields and byte ranges.
+//!
+//! A bitfield has one bit per piece, most significant bit first,
+//! with any spare bits in the last byte left clear.
+
+use core::ops::Range;
+
+use crate::storage::{RangeSet, RunStorage};
+
+/** How a file of `total_len` bytes divides into pieces of `piece_size` bytes.
+The final piece holds whatever is left, so may be short.
+
+Usage:
+```
+# use range_split::pieces::PieceLayout;
+# use range_split::storage::RangeSet;
+let layout = PieceLayout::new(16, 40);
+assert_eq!(layout.piece_count(), 3);
+
+// Pieces 0 and 2, the last being short
+let have = layout.to_ranges(&[0b1010_0000]);
+assert_eq!(have.iter().collect::<Vec<_>>(), vec![0..16, 32..40]);
+// Only whole pieces count
+let bytes: RangeSet<u64> = [10..40].into_iter().collect();
+assert_eq!(layout.to_bitfield(&bytes), vec![0b0110_0000]);
+```
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PieceLayout {
+    piece_size: u64,
+    total_len: u64,
+}
+
+impl PieceLayout {
+    /// Panics if `piece_size` is zero.
+    pub fn new(piece_size: u64, total_len: u64) -> Self {
+        assert!(piece_size > 0, "piece size must be positive");
+        Self {
+            piece_size,
+            total_len,
+        }
+    }
+
+    pub fn piece_count(&self) -> u64 {
+        self.total_len.div_ceil(self.piece_size)
+    }
+
+    /// The bytes of piece `index`, or `None` if there's no such piece.
+    pub fn piece_range(&self, index: u64) -> Option<Range<u64>> {
+        let start = index
+            .checked_mul(self.piece_size)
+            .filter(|_| index < self.piece_count())?;
+        Some(start..start.saturating_add(self.piece_size).min(self.total_len))
+    }
+
+    /// The bytes of the pieces set in `bitfield`. Bits beyond the last piece are ignored.
+    pub fn to_ranges(&self, bitfield: &[u8]) -> RangeSet<u64> {
+        (0..self.piece_count().min(bitfield.len() as u64 * 8))
+            .filter(|index| bitfield[(index / 8) as usize] & (0x80 >> (index % 8)) != 0)
+            .map(|index| self.piece_range(index).expect("index below piece count"))
+            .collect()
+    }
+
+    /// The bitfield of the pieces wholly covered by `have`.
+    pub fn to_bitfield<S: RunStorage<u64>>(&self, have: &RangeSet<u64, S>) -> Vec<u8> {
+        let mut bitfield = vec![0; self.piece_count().div_ceil(8) as usize];
+        for index in 0..self.piece_count() {
+            let piece = self.piece_range(index).expect("index below piece count");
+            let covered = have
+                .containing(piece.start)
+                .is_some_and(|r| piece.end <= r.end);
+            if covered {
+                bitfield[(index / 8) as usize] |= 0x80 >> (index % 8);
+            }
+        }
+        bitfield
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(set: &RangeSet<u64>) -> Vec<Range<u64>> {
+        set.iter().collect()
+    }
+
+    fn set(ranges: &[Range<u64>]) -> RangeSet<u64> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let layout = PieceLayout::new(10, 95);
+        let bitfield = vec![0b1100_1111, 0b0100_0000];
+        let ranges = layout.to_ranges(&bitfield);
+        assert_eq!(runs(&ranges), vec![0..20, 40..80, 90..95]);
+        assert_eq!(layout.to_bitfield(&ranges), bitfield);
+    }
+
+    #[test]
+    fn spare_bits_ignored() {
+        let layout = PieceLayout::new(10, 30);
+        assert_eq!(runs(&layout.to_ranges(&[0b1011_1111])), vec![0..10, 20..30]);
+    }
+
+    #[test]
+    fn short_bitfield() {
+        let layout = PieceLayout::new(1, 20);
+        assert_eq!(runs(&layout.to_ranges(&[0x81])), vec![0..1, 7..8]);
+    }
+
+    #[test]
+    fn short_last_piece_needs_only_its_bytes() {
+        let layout = PieceLayout::new(10, 25);
+        assert_eq!(
+            layout.to_bitfield(&set(&[20..25, 30..40])),
+            vec![0b0010_0000]
+        );
+        assert_eq!(
+            layout.to_bitfield(&set(&[5..10, 0..5, 21..30])),
+            vec![0b1000_0000]
+        );
+    }
+
+    #[test]
+    fn huge_pieces() {
+        let layout = PieceLayout::new(1 << 63, u64::MAX);
+        assert_eq!(layout.piece_range(1), Some(1 << 63..u64::MAX));
+        assert_eq!(layout.piece_range(2), None);
+        assert_eq!(layout.piece_range(u64::MAX), None);
+        assert_eq!(
+            runs(&layout.to_ranges(&[0b0100_0000])),
+            vec![1 << 63..u64::MAX]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let layout = PieceLayout::new(10, 0);
+        assert_eq!(layout.piece_count(), 0);
+        assert_eq!(layout.to_bitfield(&set(&[])), Vec::<u8>::new());
+        assert_eq!(layout.piece_range(0), None);
+    }
+}
@@ -0,0 +1,351 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Python bindings, as the `range_split` extension module: `RangeSet`, `RangeMap`,
+//! `split` and `intersect` over integers that fit in an `i64`.
+//!
+//! Ranges may be passed as `range` objects with a step of 1 or as `(start, stop)` tuples,
+//! and come back as `range`s. Build the module with e.g.
+//! `PYO3_BUILD_EXTENSION_MODULE=1 cargo rustc --release --features python --crate-type cdylib`
+//! and rename the library to `range_split.so`, or with `maturin`.
+
+use core::ops::Range;
+
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyRange, PyTuple};
+
+use crate::map::RangeMap;
+use crate::storage::RangeSet;
+use crate::{RangeAssign, Split};
+
+/// A range given from Python.
+fn to_range(obj: &Bound<'_, PyAny>) -> PyResult<Range<i64>> {
+    if let Ok(r) = obj.cast::<PyRange>() {
+        if r.getattr("step")?.extract::<i64>()? != 1 {
+            return Err(PyValueError::new_err("ranges must have a step of 1"));
+        }
+        return Ok(r.getattr("start")?.extract()?..r.getattr("stop")?.extract()?);
+    }
+    let (start, stop): (i64, i64) = obj
+        .extract()
+        .map_err(|_| PyTypeError::new_err("expected a range or a (start, stop) tuple"))?;
+    Ok(start..stop)
+}
+
+/// `range` as a Python `range`.
+fn to_py<'py>(py: Python<'py>, range: &Range<i64>) -> PyResult<Bound<'py, PyAny>> {
+    py.get_type::<PyRange>().call1((range.start, range.end))
+}
+
+/// A sorted, disjoint, coalesced set of ranges: `RangeSet`. Its `len` is the number of runs.
+#[pyclass(name = "RangeSet", module = "range_split", eq, skip_from_py_object)]
+#[derive(Clone, PartialEq, Default)]
+pub struct PyRangeSet {
+    set: RangeSet<i64>,
+}
+
+#[pymethods]
+impl PyRangeSet {
+    #[new]
+    #[pyo3(signature = (ranges = None))]
+    fn new(ranges: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut out = Self::default();
+        if let Some(ranges) = ranges {
+            for r in ranges.try_iter()? {
+                out.insert(&r?)?;
+            }
+        }
+        Ok(out)
+    }
+
+    fn insert(&mut self, range: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.set.insert(to_range(range)?);
+        Ok(())
+    }
+
+    fn remove(&mut self, range: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.set.remove(&to_range(range)?);
+        Ok(())
+    }
+
+    fn __contains__(&self, x: i64) -> bool {
+        self.set.contains(x)
+    }
+
+    fn __len__(&self) -> usize {
+        self.set.len()
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.set.is_empty()
+    }
+
+    /// The runs, in order.
+    fn ranges<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let runs = self
+            .set
+            .iter()
+            .map(|r| to_py(py, &r))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, runs)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(self.ranges(py)?.try_iter()?.into_any())
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        for r in other.set.iter() {
+            out.set.insert(r);
+        }
+        out
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        out.set.intersect_assign(&other.set);
+        out
+    }
+
+    fn difference(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        out.set.subtract_assign(&other.set);
+        out
+    }
+
+    fn __or__(&self, other: &Self) -> Self {
+        self.union(other)
+    }
+
+    fn __and__(&self, other: &Self) -> Self {
+        self.intersection(other)
+    }
+
+    fn __sub__(&self, other: &Self) -> Self {
+        self.difference(other)
+    }
+
+    fn __repr__(&self) -> String {
+        let runs: Vec<String> = self
+            .set
+            .iter()
+            .map(|r| format!("range({}, {})", r.start, r.end))
+            .collect();
+        format!("RangeSet([{}])", runs.join(", "))
+    }
+}
+
+/// A value in a [`PyRangeMap`]; values are equal if Python's `==` says so.
+struct Value(Py<PyAny>);
+
+impl Clone for Value {
+    fn clone(&self) -> Self {
+        Python::attach(|py| Self(self.0.clone_ref(py)))
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        // An `__eq__` that raises counts as unequal, so the entries stay apart
+        Python::attach(|py| self.0.bind(py).eq(other.0.bind(py)).unwrap_or(false))
+    }
+}
+
+/// A map from disjoint ranges to Python objects: `RangeMap`.
+/// Later insertions win, and touching ranges with equal values merge.
+#[pyclass(name = "RangeMap", module = "range_split")]
+#[derive(Default)]
+pub struct PyRangeMap {
+    map: RangeMap<i64, Value>,
+}
+
+/// `entries` as a list of `(range, value)` tuples.
+fn items<'py, 'a>(
+    py: Python<'py>,
+    entries: impl IntoIterator<Item = (Range<i64>, &'a Value)>,
+) -> PyResult<Bound<'py, PyList>> {
+    let items = entries
+        .into_iter()
+        .map(|(r, v)| PyTuple::new(py, [to_py(py, &r)?, v.0.bind(py).clone()]))
+        .collect::<PyResult<Vec<_>>>()?;
+    PyList::new(py, items)
+}
+
+#[pymethods]
+impl PyRangeMap {
+    #[new]
+    #[pyo3(signature = (items = None))]
+    fn new(items: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut out = Self::default();
+        if let Some(items) = items {
+            for item in items.try_iter()? {
+                let (range, value): (Bound<'_, PyAny>, Bound<'_, PyAny>) = item?.extract()?;
+                out.insert(&range, value)?;
+            }
+        }
+        Ok(out)
+    }
+
+    fn insert(&mut self, range: &Bound<'_, PyAny>, value: Bound<'_, PyAny>) -> PyResult<()> {
+        self.map.insert(to_range(range)?, Value(value.unbind()));
+        Ok(())
+    }
+
+    fn remove(&mut self, range: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.map.remove(&to_range(range)?);
+        Ok(())
+    }
+
+    /// The value at `x`, or `default` if there's none.
+    #[pyo3(signature = (x, default = None))]
+    fn get(&self, py: Python<'_>, x: i64, default: Option<Py<PyAny>>) -> Option<Py<PyAny>> {
+        match self.map.get(x) {
+            Some(v) => Some(v.0.clone_ref(py)),
+            None => default,
+        }
+    }
+
+    fn __getitem__(&self, py: Python<'_>, x: i64) -> PyResult<Py<PyAny>> {
+        self.get(py, x, None).ok_or_else(|| PyKeyError::new_err(x))
+    }
+
+    fn __contains__(&self, x: i64) -> bool {
+        self.map.get(x).is_some()
+    }
+
+    fn __len__(&self) -> usize {
+        self.map.len()
+    }
+
+    /// The entries, in order, as `(range, value)` tuples.
+    fn items<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        items(py, self.map.iter().map(|(r, v)| (r.clone(), v)))
+    }
+
+    /// The entries overlapping `range`, clipped to it.
+    fn query<'py>(
+        &self,
+        py: Python<'py>,
+        range: &Bound<'_, PyAny>,
+    ) -> PyResult<Bound<'py, PyList>> {
+        items(py, self.map.query(&to_range(range)?))
+    }
+
+    /// The ranges that have values.
+    fn ranges(&self) -> PyRangeSet {
+        PyRangeSet {
+            set: self.map.ranges(),
+        }
+    }
+}
+
+/// Split `a` by `b` into the parts below, inside, and above `b`, each `None` if empty.
+#[pyfunction]
+fn split<'py>(
+    py: Python<'py>,
+    a: &Bound<'py, PyAny>,
+    b: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyTuple>> {
+    let (below, inter, above) = to_range(a)?.split(&to_range(b)?);
+    let piece = |p: Option<Range<i64>>| p.map(|p| to_py(py, &p)).transpose();
+    (piece(below)?, piece(inter)?, piece(above)?).into_pyobject(py)
+}
+
+/// The intersection of `a` and `b`, or `None` if it's empty.
+#[pyfunction]
+fn intersect<'py>(
+    py: Python<'py>,
+    a: &Bound<'py, PyAny>,
+    b: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let mut r = to_range(a)?;
+    if r.intersect_assign(&to_range(b)?) {
+        to_py(py, &r).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+#[pymodule]
+fn range_split(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRangeSet>()?;
+    m.add_class::<PyRangeMap>()?;
+    m.add_function(wrap_pyfunction!(split, m)?)?;
+    m.add_function(wrap_pyfunction!(intersect, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ffi::CStr;
+    use pyo3::types::PyDict;
+
+    /// Run `code` with the module imported as `rs`.
+    fn run(code: &CStr) {
+        Python::initialize();
+        Python::attach(|py| {
+            let locals = PyDict::new(py);
+            let module = pyo3::wrap_pymodule!(range_split)(py);
+            locals.set_item("rs", module).unwrap();
+            if let Err(e) = py.run(code, None, Some(&locals)) {
+                e.print(py);
+                panic!("Python code failed");
+            }
+        })
+    }
+
+    #[test]
+    fn set_from_python() {
+        run(c"
+s = rs.RangeSet([range(0, 5), (3, 10), (20, 30)])
+assert list(s) == [range(0, 10), range(20, 30)]
+assert len(s) == 2 and 4 in s and 15 not in s
+s.remove((5, 25))
+assert s.ranges() == [range(0, 5), range(25, 30)]
+assert repr(s) == 'RangeSet([range(0, 5), range(25, 30)])'
+t = rs.RangeSet([(3, 27)])
+assert list(s & t) == [range(3, 5), range(25, 27)]
+assert list(s - t) == [range(0, 3), range(27, 30)]
+assert s | t == rs.RangeSet([(0, 30)])
+assert not rs.RangeSet()
+");
+    }
+
+    #[test]
+    fn map_from_python() {
+        run(c"
+m = rs.RangeMap([((0, 10), 'a'), (range(10, 20), 'a'), ((5, 8), 'b')])
+assert m.items() == [(range(0, 5), 'a'), (range(5, 8), 'b'), (range(8, 20), 'a')]
+assert m[6] == 'b' and m.get(25) is None and m.get(25, 'z') == 'z'
+assert 19 in m and 20 not in m
+m.insert((5, 8), 'a')
+assert len(m) == 1
+assert m.query((15, 30)) == [(range(15, 20), 'a')]
+assert list(m.ranges()) == [range(0, 20)]
+try:
+    m[-1]
+    assert False
+except KeyError:
+    pass
+");
+    }
+
+    #[test]
+    fn functions_from_python() {
+        run(c"
+assert rs.split(range(0, 10), (3, 6)) == (range(0, 3), range(3, 6), range(6, 10))
+assert rs.split((0, 10), (20, 30)) == (range(0, 10), None, None)
+assert rs.intersect((0, 10), (5, 20)) == range(5, 10)
+assert rs.intersect((0, 10), (15, 20)) is None
+for bad in [range(0, 10, 2), 'x', (1, 2, 3)]:
+    try:
+        rs.split(bad, (0, 1))
+        assert False
+    except (TypeError, ValueError):
+        pass
+");
+    }
+}
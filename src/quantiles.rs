@@ -0,0 +1,79 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Choosing split points from the observed distribution of values.
+
+use core::ops::Range;
+
+/** Split `domain` into at most `k` consecutive ranges holding roughly equal numbers of
+`sorted_samples`, as when choosing shard boundaries from observed keys.
+
+Samples outside `domain` are ignored. Each cut falls at a sample, so heavily repeated
+samples give fewer than `k` ranges rather than empty ones; with no samples in range,
+the whole domain comes back as one piece.
+
+```
+# use range_split::quantiles::split_by_quantiles;
+let samples = [1, 2, 3, 4, 50, 51, 52, 53, 90, 91, 92, 93];
+assert_eq!(split_by_quantiles(&(0..100), &samples, 3), vec![0..50, 50..90, 90..100]);
+```
+Panics if `k` is zero.
+**/
+pub fn split_by_quantiles<T>(domain: &Range<T>, sorted_samples: &[T], k: usize) -> Vec<Range<T>>
+where
+    T: Sized + Ord + Copy,
+{
+    assert!(k > 0, "k must be positive");
+    if domain.start >= domain.end {
+        return Vec::new();
+    }
+    let lo = sorted_samples.partition_point(|s| *s < domain.start);
+    let hi = sorted_samples.partition_point(|s| *s < domain.end);
+    let samples = &sorted_samples[lo..hi];
+
+    let mut out = Vec::with_capacity(k);
+    let mut start = domain.start;
+    for i in 1..k {
+        let Some(&cut) = samples.get(i * samples.len() / k) else {
+            break;
+        };
+        if start < cut {
+            out.push(start..cut);
+            start = cut;
+        }
+    }
+    out.push(start..domain.end);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_samples() {
+        let samples: Vec<u32> = (0..100).collect();
+        assert_eq!(
+            split_by_quantiles(&(0..100), &samples, 4),
+            vec![0..25, 25..50, 50..75, 75..100]
+        );
+    }
+
+    #[test]
+    fn duplicates_collapse() {
+        let samples = [5, 5, 5, 5, 5, 5, 7];
+        assert_eq!(split_by_quantiles(&(0..10), &samples, 3), vec![0..5, 5..10]);
+    }
+
+    #[test]
+    fn ignores_outside_samples() {
+        let samples = [-10, -5, 2, 4, 6, 8, 20];
+        assert_eq!(split_by_quantiles(&(0..10), &samples, 2), vec![0..6, 6..10]);
+    }
+
+    #[test]
+    fn no_samples() {
+        let whole = split_by_quantiles(&(0..10), &[], 5);
+        assert_eq!(whole, core::slice::from_ref(&(0..10)));
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Three-way merging of range sets that have diverged from a common base.
+
+use core::ops::Range;
+
+use crate::runs;
+use crate::storage::{RangeSet, RunStorage};
+
+/// Which of the two edited sets made a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Ours,
+    Theirs,
+}
+
+/// One side added `added` right up against `removed`, which the other side removed.
+///
+/// Pointwise the edits don't clash, and both are kept,
+/// but together they leave a gap neither side asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict<T> {
+    pub added: Range<T>,
+    pub removed: Range<T>,
+    pub added_by: Side,
+}
+
+/// The result of [`merge3`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merged<T, S = Vec<Range<T>>> {
+    /// The base with both sides' edits applied.
+    pub merged: RangeSet<T, S>,
+    /// Places the sides' edits meet, in order of `added`.
+    pub conflicts: Vec<Conflict<T>>,
+}
+
+/** Merge the edits that `ours` and `theirs` each made to `base`.
+
+Wherever one side changed coverage and the other didn't, the change is kept. Regions one side added next to regions the other removed
+are reported as conflicts: typically one side grew a run while the other shrank it.
+
+```
+# use range_split::reconcile::{merge3, Conflict, Side};
+# use range_split::storage::RangeSet;
+let base: RangeSet<u32> = [0..10, 20..30].into_iter().collect();
+let ours = [0..15, 20..30].into_iter().collect();   // grew the first run
+let theirs = [0..5, 25..30].into_iter().collect();  // shrank both
+let result = merge3(&base, &ours, &theirs);
+assert_eq!(result.merged.iter().collect::<Vec<_>>(), vec![0..5, 10..15, 25..30]);
+assert_eq!(result.conflicts, vec![Conflict { added: 10..15, removed: 5..10, added_by: Side::Ours }]);
+```
+**/
+pub fn merge3<T, S>(
+    base: &RangeSet<T, S>,
+    ours: &RangeSet<T, S>,
+    theirs: &RangeSet<T, S>,
+) -> Merged<T, S>
+where
+    T: Sized + Ord + Copy,
+    S: RunStorage<T>,
+{
+    let [base, ours, theirs] = [base, ours, theirs].map(|s| s.iter().collect::<Vec<_>>());
+    let (base, ours, theirs) = (base.as_slice(), ours.as_slice(), theirs.as_slice());
+    let mut points: Vec<T> = [base, ours, theirs]
+        .into_iter()
+        .flatten()
+        .flat_map(|r| [r.start, r.end])
+        .collect();
+    points.sort_unstable();
+    points.dedup();
+
+    let mut merged = Vec::new();
+    // Added and removed regions, by `Side::Ours` then `Side::Theirs`
+    let mut added = [Vec::new(), Vec::new()];
+    let mut removed = [Vec::new(), Vec::new()];
+    for w in points.windows(2) {
+        let segment = w[0]..w[1];
+        let [b, o, t] = [base, ours, theirs].map(|rs| runs::containing(rs, w[0]).is_some());
+        if if o == b { t } else { o } {
+            runs::insert(&mut merged, segment.clone());
+        }
+        for (side, edited) in [o, t].into_iter().enumerate() {
+            match (b, edited) {
+                (false, true) => runs::insert(&mut added[side], segment.clone()),
+                (true, false) => runs::insert(&mut removed[side], segment.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (side, added_by) in [(0, Side::Ours), (1, Side::Theirs)] {
+        let other = &removed[1 - side];
+        for a in &added[side] {
+            // Removed regions can't overlap added ones, only touch them
+            let i = other.partition_point(|r| r.end < a.start);
+            let touching = other[i..]
+                .iter()
+                .take_while(|r| r.start <= a.end)
+                .filter(|r| r.end == a.start || r.start == a.end);
+            for r in touching {
+                conflicts.push(Conflict {
+                    added: a.clone(),
+                    removed: r.clone(),
+                    added_by,
+                });
+            }
+        }
+    }
+    conflicts.sort_by_key(|c| (c.added.start, c.removed.start));
+    Merged {
+        merged: merged.into_iter().collect(),
+        conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn set<S: RunStorage<u32>>(runs: &[Range<u32>]) -> RangeSet<u32, S> {
+        runs.iter().cloned().collect()
+    }
+
+    fn runs<S: RunStorage<u32>>(set: &RangeSet<u32, S>) -> Vec<Range<u32>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn one_sided_edits() {
+        let base = set::<Vec<_>>(&[0..10, 20..30, 60..70]);
+        let ours = set(&[0..10, 20..30, 40..50, 60..70]);
+        let theirs = set(&[0..10, 60..70]);
+        let result = merge3(&base, &ours, &theirs);
+        assert_eq!(runs(&result.merged), vec![0..10, 40..50, 60..70]);
+        assert!(result.conflicts.is_empty())
+    }
+
+    #[test]
+    fn same_edit_both_sides() {
+        let base = set::<BTreeMap<_, _>>(&[0..10, 20..30]);
+        let edited = set(&[0..30, 40..45]);
+        let result = merge3(&base, &edited, &edited);
+        assert_eq!(result.merged, edited);
+        assert!(result.conflicts.is_empty())
+    }
+
+    #[test]
+    fn conflicts_both_ways() {
+        // Ours grows the run down while theirs shrinks its bottom;
+        // theirs grows it up while ours shrinks its top
+        let base = set::<Vec<_>>(&[10..20, 30..40]);
+        let ours = set(&[5..15, 30..40]);
+        let theirs = set(&[12..25, 30..40]);
+        let result = merge3(&base, &ours, &theirs);
+        assert_eq!(runs(&result.merged), vec![5..10, 12..15, 20..25, 30..40]);
+        assert_eq!(
+            result.conflicts,
+            vec![
+                Conflict {
+                    added: 5..10,
+                    removed: 10..12,
+                    added_by: Side::Ours
+                },
+                Conflict {
+                    added: 20..25,
+                    removed: 15..20,
+                    added_by: Side::Theirs
+                },
+            ]
+        )
+    }
+}
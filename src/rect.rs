@@ -0,0 +1,132 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Axis-aligned rectangles, as a pair of ranges.
+
+use core::ops::Range;
+
+use crate::morton::{self, QuadCell};
+use crate::Split;
+
+/** The half-open rectangle `x × y`. It's empty if either side is.
+
+Usage:
+```
+# use range_split::rect::Rect;
+let a = Rect::new(0..10, 0..4);
+let b = Rect::new(5..20, 2..8);
+assert_eq!(a.intersect(&b), Some(Rect::new(5..10, 2..4)));
+assert!(a.contains(9, 3));
+assert!(!a.contains(10, 3));
+assert_eq!(a.intersect(&Rect::new(0..10, 4..5)), None);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rect<T> {
+    pub x: Range<T>,
+    pub y: Range<T>,
+}
+
+impl<T> Rect<T> {
+    pub fn new(x: Range<T>, y: Range<T>) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Rect<T>
+where
+    T: Sized + Ord + Copy,
+{
+    pub fn is_empty(&self) -> bool {
+        self.x.start >= self.x.end || self.y.start >= self.y.end
+    }
+
+    /// Whether the point `(x, y)` is inside.
+    pub fn contains(&self, x: T, y: T) -> bool {
+        self.x.contains(&x) && self.y.contains(&y)
+    }
+
+    /// The part of `self` within `other`, if it's not empty.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let x = self.x.split(&other.x).1?;
+        let y = self.y.split(&other.y).1?;
+        Some(Self::new(x, y)).filter(|r| !r.is_empty())
+    }
+}
+
+impl Rect<u32> {
+    /** The quadtree cells covering `self`, each as large as possible, in Z-order.
+
+    Cells are no deeper than `max_depth` (at most 32, for single points).
+    Those at `max_depth` that `self` only partly covers are included,
+    so that the cells cover `self` exactly only if it's aligned to that depth.
+
+    ```
+    # use range_split::{morton::QuadCell, rect::Rect};
+    let cells = Rect::new(0..4, 0..2).to_quad_cells(32);
+    // Two 2 × 2 cells, at depth 31
+    assert_eq!(cells.len(), 2);
+    assert_eq!(cells[1].rect(), Some(Rect::new(2..4, 0..2)));
+    assert_eq!(cells[1].codes(), 4..8);
+
+    // Coarser, the cell of side 4 holding both
+    assert_eq!(Rect::new(0..4, 0..2).to_quad_cells(30), vec![QuadCell { depth: 30, code: 0 }]);
+    ```
+    **/
+    pub fn to_quad_cells(&self, max_depth: u32) -> Vec<QuadCell> {
+        let mut cells = Vec::new();
+        if !self.is_empty() {
+            morton::quad_cells(
+                core::slice::from_ref(self),
+                0,
+                32,
+                32 - max_depth.min(32),
+                &mut cells,
+            );
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_sides() {
+        let (p, q) = (5, 5);
+        assert!(Rect::new(0..10, p..q).is_empty());
+        assert!(!Rect::new(0..1, 0..1).is_empty());
+        assert_eq!(
+            Rect::new(0..1, p..q).intersect(&Rect::new(0..1, 0..10)),
+            None
+        )
+    }
+
+    #[test]
+    fn quad_cells_cover_exactly() {
+        let rect = Rect::new(3..13, 5..9);
+        let cells = rect.to_quad_cells(32);
+        let area: u64 = cells.iter().map(|c| c.codes().end - c.codes().start).sum();
+        assert_eq!(area, 40);
+        assert!(cells
+            .iter()
+            .all(|c| c.rect().unwrap().intersect(&rect) == c.rect()));
+        assert!(cells
+            .windows(2)
+            .all(|w| w[0].codes().end <= w[1].codes().start))
+    }
+
+    #[test]
+    fn quad_cells_depth_limited() {
+        let rect = Rect::new(3..13, 5..9);
+        let cells = rect.to_quad_cells(29);
+        assert!(cells.iter().all(|c| c.depth <= 29));
+        for x in 3..13 {
+            for y in 5..9 {
+                assert!(cells.iter().any(|c| c.rect().unwrap().contains(x, y)));
+            }
+        }
+        assert!(Rect::new(0..0, 0..5).to_quad_cells(32).is_empty())
+    }
+}
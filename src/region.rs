@@ -0,0 +1,276 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Two-dimensional regions: sets of points in the plane, as disjoint rectangles.
+
+use core::ops::{Add, Mul, Range, Sub};
+
+use crate::morton::{self, QuadCell};
+use crate::rect::Rect;
+use crate::runs;
+
+/// The rows `y` of a region, all covered by the same runs `xs`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Band<T> {
+    y: Range<T>,
+    /// Sorted, disjoint, coalesced, and not empty.
+    xs: Vec<Range<T>>,
+}
+
+/** A region of the plane, as windowing systems call it: the 2D counterpart of a range set.
+
+It's kept as bands of rows, sorted by `y`, each covered by the same sorted, disjoint runs of `x`.
+Touching bands with the same runs are merged, so equal regions have equal representations.
+
+Usage:
+```
+# use range_split::{rect::Rect, region::Region2D};
+let a = Region2D::from(Rect::new(0..10, 0..10));
+let b = Region2D::from(Rect::new(5..15, 5..15));
+
+let union = a.union(&b);
+assert_eq!(union.area::<i32>(), 175);
+assert!(union.contains(12, 12));
+
+// A square with a notch out of its corner
+let cut = a.subtract(&b);
+assert_eq!(cut.iter().collect::<Vec<_>>(), vec![
+    Rect::new(0..10, 0..5),
+    Rect::new(0..5, 5..10),
+]);
+assert!(cut.contains_rect(&Rect::new(0..5, 0..10)));
+assert_eq!(a.intersect(&b).bounds(), Some(Rect::new(5..10, 5..10)));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Region2D<T> {
+    bands: Vec<Band<T>>,
+}
+
+impl<T> Default for Region2D<T> {
+    fn default() -> Self {
+        Self { bands: Vec::new() }
+    }
+}
+
+impl<T> Region2D<T>
+where
+    T: Sized + Ord + Copy,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Combine `self` and `other` band by band, with `op` on the runs of each row.
+    fn combine(
+        &self,
+        other: &Self,
+        op: impl Fn(&[Range<T>], &[Range<T>]) -> Vec<Range<T>>,
+    ) -> Self {
+        let mut ys: Vec<T> = self
+            .bands
+            .iter()
+            .chain(&other.bands)
+            .flat_map(|b| [b.y.start, b.y.end])
+            .collect();
+        ys.sort_unstable();
+        ys.dedup();
+
+        // The runs of `bands` over rows from `y`, moving `i` along
+        let runs_at = |bands: &[Band<T>], i: &mut usize, y: T| -> Vec<Range<T>> {
+            while *i < bands.len() && bands[*i].y.end <= y {
+                *i += 1;
+            }
+            match bands.get(*i) {
+                Some(b) if b.y.start <= y => b.xs.clone(),
+                _ => Vec::new(),
+            }
+        };
+        let mut out = Self::new();
+        let (mut i, mut j) = (0, 0);
+        for w in ys.windows(2) {
+            let a = runs_at(&self.bands, &mut i, w[0]);
+            let b = runs_at(&other.bands, &mut j, w[0]);
+            out.push_band(w[0]..w[1], op(&a, &b));
+        }
+        out
+    }
+
+    /// Append a band below all the others, merging it into the last if they match.
+    pub(crate) fn push_band(&mut self, y: Range<T>, xs: Vec<Range<T>>) {
+        if xs.is_empty() {
+            return;
+        }
+        match self.bands.last_mut() {
+            Some(last) if last.y.end == y.start && last.xs == xs => last.y.end = y.end,
+            _ => self.bands.push(Band { y, xs }),
+        }
+    }
+
+    /// Points in either region.
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| {
+            let mut xs = a.to_vec();
+            for r in b {
+                runs::insert(&mut xs, r.clone());
+            }
+            xs
+        })
+    }
+
+    /// Points in both regions.
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| {
+            let mut xs = a.to_vec();
+            runs::intersect_in_place(&mut xs, b.iter().cloned());
+            xs
+        })
+    }
+
+    /// Points in `self` but not `other`.
+    pub fn subtract(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| {
+            let mut xs = a.to_vec();
+            runs::subtract_in_place(&mut xs, b.iter().cloned());
+            xs
+        })
+    }
+
+    /// Add `rect` to the region.
+    pub fn insert(&mut self, rect: Rect<T>) {
+        *self = self.union(&rect.into());
+    }
+
+    /// Remove `rect` from the region.
+    pub fn remove(&mut self, rect: &Rect<T>) {
+        *self = self.subtract(&rect.clone().into());
+    }
+
+    /// Whether the point `(x, y)` is in the region.
+    pub fn contains(&self, x: T, y: T) -> bool {
+        let i = self.bands.partition_point(|b| b.y.end <= y);
+        self.bands
+            .get(i)
+            .is_some_and(|b| b.y.start <= y && runs::containing(&b.xs, x).is_some())
+    }
+
+    /// Whether every point of `rect` is in the region.
+    pub fn contains_rect(&self, rect: &Rect<T>) -> bool {
+        Self::from(rect.clone()).subtract(self).is_empty()
+    }
+
+    /// The total area, with `D` the type of differences of `T`.
+    pub fn area<D>(&self) -> D
+    where
+        T: Sub<Output = D>,
+        D: Default + Add<Output = D> + Mul<Output = D> + Copy,
+    {
+        self.bands.iter().fold(D::default(), |area, b| {
+            let width = b.xs.iter().fold(D::default(), |w, x| w + (x.end - x.start));
+            area + (b.y.end - b.y.start) * width
+        })
+    }
+
+    /// The smallest rectangle containing the region, unless it's empty.
+    pub fn bounds(&self) -> Option<Rect<T>> {
+        let (first, last) = (self.bands.first()?, self.bands.last()?);
+        let start = self.bands.iter().map(|b| b.xs[0].start).min()?;
+        let end = self.bands.iter().map(|b| b.xs[b.xs.len() - 1].end).max()?;
+        Some(Rect::new(start..end, first.y.start..last.y.end))
+    }
+
+    /// Number of rectangles in the region, as [`iter`](Self::iter) yields them.
+    pub fn len(&self) -> usize {
+        self.bands.iter().map(|b| b.xs.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bands.is_empty()
+    }
+
+    /// Disjoint rectangles making up the region, by rows and then columns.
+    pub fn iter(&self) -> impl Iterator<Item = Rect<T>> + '_ {
+        self.bands
+            .iter()
+            .flat_map(|b| b.xs.iter().map(|x| Rect::new(x.clone(), b.y.clone())))
+    }
+}
+
+impl Region2D<u32> {
+    /// The quadtree cells covering the region, each as large as possible, in Z-order,
+    /// as [`Rect::to_quad_cells`] gives for a rectangle.
+    pub fn to_quad_cells(&self, max_depth: u32) -> Vec<QuadCell> {
+        let rects: Vec<Rect<u32>> = self.iter().collect();
+        let mut cells = Vec::new();
+        if !rects.is_empty() {
+            morton::quad_cells(&rects, 0, 32, 32 - max_depth.min(32), &mut cells);
+        }
+        cells
+    }
+}
+
+impl<T> From<Rect<T>> for Region2D<T>
+where
+    T: Sized + Ord + Copy,
+{
+    fn from(rect: Rect<T>) -> Self {
+        let mut region = Self::new();
+        if !rect.is_empty() {
+            region.push_band(rect.y, vec![rect.x]);
+        }
+        region
+    }
+}
+
+impl<T> FromIterator<Rect<T>> for Region2D<T>
+where
+    T: Sized + Ord + Copy,
+{
+    fn from_iter<I: IntoIterator<Item = Rect<T>>>(iter: I) -> Self {
+        let mut region = Self::new();
+        for rect in iter {
+            region.insert(rect);
+        }
+        region
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bands_merge_back() {
+        let mut region = Region2D::from(Rect::new(0..10, 0..10));
+        region.remove(&Rect::new(3..6, 3..6));
+        assert_eq!(region.len(), 4);
+        assert_eq!(region.area::<i32>(), 91);
+        region.insert(Rect::new(2..7, 2..7));
+        assert_eq!(region, Region2D::from(Rect::new(0..10, 0..10)))
+    }
+
+    #[test]
+    fn disjoint_and_empty() {
+        let a: Region2D<i32> = [Rect::new(0..2, 0..2), Rect::new(5..7, 0..2)]
+            .into_iter()
+            .collect();
+        assert_eq!(a.len(), 2);
+        assert!(!a.contains_rect(&Rect::new(0..7, 0..2)));
+        assert!(a.contains_rect(&Rect::new(5..7, 1..2)));
+        assert!(a.intersect(&Rect::new(2..5, 0..9).into()).is_empty());
+        assert_eq!(a.bounds(), Some(Rect::new(0..7, 0..2)));
+        assert_eq!(Region2D::<i32>::new().bounds(), None)
+    }
+
+    #[test]
+    fn quad_cells_join_rects() {
+        // Neither rectangle of the region holds the 4 × 4 cell at the origin, but both do
+        let region: Region2D<u32> = [Rect::new(0..4, 0..2), Rect::new(0..6, 2..4)]
+            .into_iter()
+            .collect();
+        assert_eq!(region.len(), 2);
+        let cells = region.to_quad_cells(32);
+        let rects: Vec<_> = cells.iter().map(|c| c.rect().unwrap()).collect();
+        assert_eq!(rects, vec![Rect::new(0..4, 0..4), Rect::new(4..6, 2..4)])
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Run-length encoded sequences, as `(value, run length)` pairs from an implicit start,
+//! to and from [`RangeMap`]s.
+
+use crate::map::RangeMap;
+
+/** A contiguous [`RangeMap`] built up from runs, each starting where the last ended.
+
+Usage:
+```
+# use range_split::rle::RleMap;
+let mut mask = RleMap::new(100);
+mask.extend([(false, 3), (true, 2), (true, 4), (false, 0), (false, 1)]);
+assert_eq!(mask.map().as_slice(), &[(100..103, false), (103..109, true), (109..110, false)]);
+assert_eq!(mask.end(), 110);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RleMap<V> {
+    map: RangeMap<u64, V>,
+    end: u64,
+}
+
+impl<V: Clone + PartialEq> RleMap<V> {
+    /// An empty map, whose first run will start at `start`.
+    pub fn new(start: u64) -> Self {
+        Self {
+            map: RangeMap::new(),
+            end: start,
+        }
+    }
+
+    /// Where the next run will start.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Append `len` of `value`, merging it into the last run if that has the same value.
+    /// Empty runs are skipped. Panics if the run would end past `u64::MAX`.
+    pub fn push_run(&mut self, value: V, len: u64) {
+        if len == 0 {
+            return;
+        }
+        let end = self.end.checked_add(len).expect("run ends past u64::MAX");
+        self.map.insert(self.end..end, value);
+        self.end = end;
+    }
+
+    pub fn map(&self) -> &RangeMap<u64, V> {
+        &self.map
+    }
+
+    pub fn into_map(self) -> RangeMap<u64, V> {
+        self.map
+    }
+}
+
+impl<V: Clone + PartialEq> Extend<(V, u64)> for RleMap<V> {
+    fn extend<I: IntoIterator<Item = (V, u64)>>(&mut self, runs: I) {
+        for (value, len) in runs {
+            self.push_run(value, len);
+        }
+    }
+}
+
+/** The range map of the run-length encoded `runs`, the first starting at `start`.
+
+Empty runs are skipped and equal neighbours merged. Panics if the runs end past `u64::MAX`.
+
+```
+# use range_split::rle::from_rle;
+let map = from_rle(10, [('a', 5), ('b', 0), ('a', 2), ('c', 3)]);
+assert_eq!(map.as_slice(), &[(10..17, 'a'), (17..20, 'c')]);
+```
+**/
+pub fn from_rle<V: Clone + PartialEq>(
+    start: u64,
+    runs: impl IntoIterator<Item = (V, u64)>,
+) -> RangeMap<u64, V> {
+    let mut map = RleMap::new(start);
+    map.extend(runs);
+    map.into_map()
+}
+
+/** The run-length encoding of `map`, as its start and its runs: the inverse of [`from_rle`].
+An empty map starts at 0.
+
+`None` if `map` has gaps, since run-length encoding can't express them.
+
+```
+# use range_split::map::RangeMap;
+# use range_split::rle::to_rle;
+let map: RangeMap<u64, char> = [(10..17, 'a'), (17..20, 'a'), (20..21, 'c')].into_iter().collect();
+assert_eq!(to_rle(&map), Some((10, vec![('a', 10), ('c', 1)])));
+
+let gappy: RangeMap<u64, char> = [(0..5, 'a'), (6..9, 'b')].into_iter().collect();
+assert_eq!(to_rle(&gappy), None);
+```
+**/
+pub fn to_rle<V: Clone>(map: &RangeMap<u64, V>) -> Option<(u64, Vec<(V, u64)>)> {
+    let entries = map.as_slice();
+    if !entries.windows(2).all(|w| w[0].0.end == w[1].0.start) {
+        return None;
+    }
+    // Entries of a `RangeMap` are non-empty, and touching ones differ
+    let runs = entries
+        .iter()
+        .map(|(r, v)| (v.clone(), r.end - r.start))
+        .collect();
+    Some((entries.first().map_or(0, |(r, _)| r.start), runs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let runs = vec![(1u8, 4), (0, 2), (1, 1), (7, 9)];
+        let map = from_rle(3, runs.clone());
+        assert_eq!(map.len(), 4);
+        assert_eq!(to_rle(&map), Some((3, runs)));
+        assert_eq!(to_rle::<u8>(&RangeMap::new()), Some((0, vec![])))
+    }
+
+    #[test]
+    fn extend_continues() {
+        let mut map = RleMap::new(0);
+        map.extend([('a', 2)]);
+        map.extend([('a', 3), ('b', 1)]);
+        assert_eq!(map.into_map().into_vec(), vec![(0..5, 'a'), (5..6, 'b')])
+    }
+
+    #[test]
+    #[should_panic]
+    fn overflow_panics() {
+        from_rle(u64::MAX - 1, [('a', 1), ('b', 1)]);
+    }
+}
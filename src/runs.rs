@@ -0,0 +1,135 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Maintenance of sorted, disjoint, coalesced lists of non-empty ranges ("runs"),
+//! shared by the types that keep one internally.
+
+use core::ops::Range;
+
+use crate::Split;
+
+/// Add `range` to `runs`, merging it with any runs it overlaps or touches.
+pub(crate) fn insert<T>(runs: &mut Vec<Range<T>>, range: Range<T>)
+where
+    T: Sized + Ord + Copy,
+{
+    if range.start >= range.end {
+        return;
+    }
+    let i = runs.partition_point(|r| r.end < range.start);
+    let j = runs.partition_point(|r| r.start <= range.end);
+    let mut merged = range;
+    if i < j {
+        merged.start = merged.start.min(runs[i].start);
+        merged.end = merged.end.max(runs[j - 1].end);
+    }
+    runs.splice(i..j, [merged]);
+}
+
+/// Remove `range` from `runs`, splitting any run it falls inside.
+pub(crate) fn remove<T>(runs: &mut Vec<Range<T>>, range: &Range<T>)
+where
+    T: Sized + Ord + Copy,
+{
+    if range.start >= range.end {
+        return;
+    }
+    let i = runs.partition_point(|r| r.end <= range.start);
+    let j = runs.partition_point(|r| r.start < range.end);
+    let kept: Vec<Range<T>> = runs[i..j]
+        .iter()
+        .flat_map(|r| {
+            let (below, _, above) = r.split(range);
+            below.into_iter().chain(above)
+        })
+        .collect();
+    runs.splice(i..j, kept);
+}
+
+/// The run containing `t`, if any.
+pub(crate) fn containing<T>(runs: &[Range<T>], t: T) -> Option<&Range<T>>
+where
+    T: Sized + Ord + Copy,
+{
+    let i = runs.partition_point(|r| r.end <= t);
+    runs.get(i).filter(|r| r.start <= t)
+}
+
+/// The ranges covered by both `a` and `b`, which must each be sorted and disjoint.
+pub(crate) fn intersect<T>(a: &[Range<T>], b: &[Range<T>]) -> Vec<Range<T>>
+where
+    T: Sized + Ord + Copy,
+{
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        out.extend(a[i].split(&b[j]).1);
+        if a[i].end <= b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Replace each run by the pieces `pieces` pushes for it, reusing the allocation of `runs`.
+fn rewrite<T>(runs: &mut Vec<Range<T>>, mut pieces: impl FnMut(Range<T>, &mut Vec<Range<T>>))
+where
+    T: Sized + Ord + Copy,
+{
+    let n = runs.len();
+    for i in 0..n {
+        let r = runs[i].clone();
+        pieces(r, runs);
+    }
+    runs.drain(..n);
+}
+
+/// Keep only the parts of `runs` also covered by `other`, which must be sorted and disjoint.
+pub(crate) fn intersect_in_place<T>(
+    runs: &mut Vec<Range<T>>,
+    other: impl IntoIterator<Item = Range<T>>,
+) where
+    T: Sized + Ord + Copy,
+{
+    let mut other = other.into_iter().peekable();
+    rewrite(runs, |r, out| {
+        while other.next_if(|o| o.end <= r.start).is_some() {}
+        while let Some(o) = other.peek().filter(|o| o.start < r.end) {
+            out.extend(r.split(o).1.filter(|p| p.start < p.end));
+            // One reaching past `r` may cover the next run too
+            if o.end > r.end {
+                break;
+            }
+            other.next();
+        }
+    });
+}
+
+/// Remove from `runs` everything covered by `other`, which must be sorted and disjoint.
+pub(crate) fn subtract_in_place<T>(
+    runs: &mut Vec<Range<T>>,
+    other: impl IntoIterator<Item = Range<T>>,
+) where
+    T: Sized + Ord + Copy,
+{
+    let mut other = other.into_iter().filter(|o| o.start < o.end).peekable();
+    rewrite(runs, |r, out| {
+        while other.next_if(|o| o.end <= r.start).is_some() {}
+        let mut rest = Some(r);
+        while let Some(o) = other.peek() {
+            let Some(r) = rest.take_if(|r| o.start < r.end) else {
+                break;
+            };
+            let (below, _, above) = r.split(o);
+            out.extend(below);
+            rest = above;
+            // Otherwise `o` reaches past `r`, and may cover the next run too
+            if rest.is_some() {
+                other.next();
+            }
+        }
+        out.extend(rest);
+    });
+}
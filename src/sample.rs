@@ -0,0 +1,383 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Uniform sampling from sets of integers given as sorted, disjoint ranges,
+//! and random generation of such sets, using any [`rand::Rng`].
+//!
+//! Requires the `rand` feature.
+
+use core::ops::Range;
+
+use rand::Rng;
+
+use crate::storage::{RangeSet, RunStorage};
+
+/** Uniform draws of subranges of a fixed length from sorted, disjoint runs,
+each lying wholly within one run.
+
+The prefix sums of how many subranges fit in each run are worked out once,
+so each draw is a binary search, in `O(log n)` time for `n` runs.
+
+```
+# use range_split::sample::Sampler;
+use rand::{rngs::StdRng, SeedableRng};
+
+let sampler = Sampler::new([0..3, 10..20], 3);
+// Only 0..3 itself fits in the first run, then 10..13 through 17..20
+assert_eq!(sampler.total(), 9);
+let mut rng = StdRng::seed_from_u64(7);
+let r = sampler.draw(&mut rng).unwrap();
+assert!(r == (0..3) || (10 <= r.start && r.end <= 20));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sampler {
+    /// Where each run that fits a subrange starts.
+    starts: Vec<u64>,
+    /// How many subranges fit in each of those runs and all before it.
+    prefix: Vec<u64>,
+    len: u64,
+}
+
+impl Sampler {
+    /// A sampler of subranges of `len` elements in `runs`. None fit if `len` is zero.
+    pub fn new(runs: impl IntoIterator<Item = Range<u64>>, len: u64) -> Self {
+        let mut sampler = Self {
+            starts: Vec::new(),
+            prefix: Vec::new(),
+            len,
+        };
+        let mut total = 0;
+        for r in runs {
+            let n = r.end.saturating_sub(r.start);
+            if len > 0 && n >= len {
+                total += n - len + 1;
+                sampler.starts.push(r.start);
+                sampler.prefix.push(total);
+            }
+        }
+        sampler
+    }
+
+    /// How many subranges there are to draw from.
+    pub fn total(&self) -> u64 {
+        self.prefix.last().copied().unwrap_or(0)
+    }
+
+    /// A uniformly random subrange, or `None` if there are none.
+    pub fn draw<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Range<u64>> {
+        self.pick(|n| rng.random_range(0..n))
+    }
+
+    /// The subrange numbered `draw(total)`, counting from the start of the first run.
+    fn pick(&self, draw: impl FnOnce(u64) -> u64) -> Option<Range<u64>> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let k = draw(total);
+        assert!(k < total, "draw returned a value out of range");
+        let i = self.prefix.partition_point(|&p| p <= k);
+        let before = if i > 0 { self.prefix[i - 1] } else { 0 };
+        let start = self.starts[i] + (k - before);
+        Some(start..start + self.len)
+    }
+}
+
+/** A uniformly random element of the sorted, disjoint `runs`, or `None` if they're all empty.
+
+Every element is equally likely, so longer runs are proportionally more likely.
+To draw many, make a [`Sampler`] of subranges of length 1 once instead.
+
+```
+# use range_split::sample::sample;
+let allowed = [10..12, 100..102];
+let id = sample(&allowed, &mut rand::rng()).unwrap();
+assert!([10, 11, 100, 101].contains(&id));
+```
+**/
+pub fn sample<R: Rng + ?Sized>(runs: &[Range<u64>], rng: &mut R) -> Option<u64> {
+    Sampler::new(runs.iter().cloned(), 1)
+        .draw(rng)
+        .map(|r| r.start)
+}
+
+/** A uniformly random subrange of `len` elements lying wholly within one of the
+sorted, disjoint `runs`, or `None` if there's none (or `len` is zero).
+
+```
+# use range_split::sample::sample_subrange;
+let allowed = [0..3, 10..20];
+let r = sample_subrange(&allowed, 5, &mut rand::rng()).unwrap();
+// Only the second run is long enough
+assert!(10 <= r.start && r.end <= 20);
+assert_eq!(sample_subrange(&allowed, 11, &mut rand::rng()), None);
+```
+**/
+pub fn sample_subrange<R: Rng + ?Sized>(
+    runs: &[Range<u64>],
+    len: u64,
+    rng: &mut R,
+) -> Option<Range<u64>> {
+    Sampler::new(runs.iter().cloned(), len).draw(rng)
+}
+
+impl<S: RunStorage<u64>> RangeSet<u64, S> {
+    /** A uniformly random element of the set, as [`sample`] gives for its runs.
+
+    ```
+    # use range_split::storage::RangeSet;
+    let ids: RangeSet<u64> = [10..12, 100..102].into_iter().collect();
+    let id = ids.sample(&mut rand::rng()).unwrap();
+    assert!(ids.contains(id));
+    ```
+    **/
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<u64> {
+        self.sampler(1).draw(rng).map(|r| r.start)
+    }
+
+    /// A uniformly random subrange of `len` elements within one run of the set,
+    /// as [`sample_subrange`] gives for its runs.
+    pub fn sample_subrange<R: Rng + ?Sized>(&self, len: u64, rng: &mut R) -> Option<Range<u64>> {
+        self.sampler(len).draw(rng)
+    }
+
+    /// A [`Sampler`] of subranges of `len` elements of the set, for drawing many.
+    pub fn sampler(&self, len: u64) -> Sampler {
+        Sampler::new(self.iter(), len)
+    }
+
+    /** A random set with the shape given by `config`, from the runs [`random_runs`] makes.
+    With a `min_gap` of zero, touching runs merge, so there may be fewer than `config.runs`.
+
+    ```
+    # use range_split::sample::RunsConfig;
+    # use range_split::storage::RangeSet;
+    # use std::collections::BTreeMap;
+    let config = RunsConfig { domain: 0..100, runs: 4, density: 0.2, min_gap: 2 };
+    let mut rng = rand::rng();
+    let set: RangeSet<u64, BTreeMap<u64, u64>> = RangeSet::random(&config, &mut rng).unwrap();
+    assert_eq!(set.len(), 4);
+    assert_eq!(set.stats::<u64>().covered, 20);
+    ```
+    **/
+    pub fn random<R: Rng + ?Sized>(config: &RunsConfig, rng: &mut R) -> Option<Self> {
+        random_runs(config, rng).map(|runs| runs.into_iter().collect())
+    }
+}
+
+/// The shape of the runs made by [`random_runs`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunsConfig {
+    /// Where the runs lie.
+    pub domain: Range<u64>,
+    /// How many runs to make.
+    pub runs: u64,
+    /// The fraction of `domain` to cover, from 0 to 1.
+    /// It's adjusted as needed to fit `runs` runs separated by `min_gap`.
+    pub density: f64,
+    /// The least space between consecutive runs.
+    pub min_gap: u64,
+}
+
+/// `total` split randomly into `parts` non-negative pieces.
+fn composition(total: u64, parts: u64, draw: &mut impl FnMut(u64) -> u64) -> Vec<u64> {
+    let mut cuts: Vec<u64> = (1..parts).map(|_| draw(total + 1)).collect();
+    cuts.sort_unstable();
+    cuts.push(total);
+    let mut prev = 0;
+    cuts.into_iter()
+        .map(|c| {
+            let piece = c - prev;
+            prev = c;
+            piece
+        })
+        .collect()
+}
+
+/** Random sorted, disjoint, non-empty runs with the shape given by `config`,
+for benchmarks and load tests that want realistically fragmented coverage.
+
+Returns `None` if the runs can't fit in the domain at all.
+
+```
+# use range_split::sample::{random_runs, RunsConfig};
+use rand::{rngs::StdRng, SeedableRng};
+
+let config = RunsConfig { domain: 0..1000, runs: 10, density: 0.5, min_gap: 5 };
+// Seeded, for the same runs on every benchmark
+let runs = random_runs(&config, &mut StdRng::seed_from_u64(12345)).unwrap();
+assert_eq!(runs.len(), 10);
+assert_eq!(runs.iter().map(|r| r.end - r.start).sum::<u64>(), 500);
+assert!(runs.windows(2).all(|w| w[1].start - w[0].end >= 5));
+```
+**/
+pub fn random_runs<R: Rng + ?Sized>(config: &RunsConfig, rng: &mut R) -> Option<Vec<Range<u64>>> {
+    runs_with(config, |n| rng.random_range(0..n))
+}
+
+/// The runs of [`random_runs`], where `draw(n)` gives a random value in `0..n`.
+fn runs_with(config: &RunsConfig, mut draw: impl FnMut(u64) -> u64) -> Option<Vec<Range<u64>>> {
+    let n = config.runs;
+    if n == 0 {
+        return Some(Vec::new());
+    }
+    let len = config.domain.end.saturating_sub(config.domain.start);
+    let least_gaps = (n - 1).checked_mul(config.min_gap)?;
+    let most_covered = len.checked_sub(least_gaps)?;
+    if most_covered < n {
+        return None;
+    }
+    let covered = ((config.density * len as f64) as u64).clamp(n, most_covered);
+
+    let sizes = composition(covered - n, n, &mut draw);
+    let gaps = composition(len - covered - least_gaps, n + 1, &mut draw);
+    let mut start = config.domain.start;
+    let mut out = Vec::new();
+    for (i, size) in sizes.into_iter().enumerate() {
+        start += gaps[i] + if i > 0 { config.min_gap } else { 0 };
+        out.push(start..start + size + 1);
+        start += size + 1;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Every possible draw, in order.
+    fn all<T>(n: u64, f: impl Fn(u64) -> Option<T>) -> Vec<T> {
+        (0..n).filter_map(f).collect()
+    }
+
+    #[test]
+    fn each_element_once() {
+        let runs = [0..2, 5..6, 8..10];
+        let got = all(5, |k| {
+            Sampler::new(runs.clone(), 1).pick(|_| k).map(|r| r.start)
+        });
+        assert_eq!(got, vec![0, 1, 5, 8, 9]);
+    }
+
+    #[test]
+    fn empty_runs() {
+        let (a, b) = (5, 5);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(sample(core::slice::from_ref(&(a..b)), &mut rng), None);
+        assert_eq!(sample(&[], &mut rng), None);
+    }
+
+    #[test]
+    fn subranges() {
+        let runs = [0..1, 4..7];
+        let got = all(2, |k| Sampler::new(runs.clone(), 2).pick(|_| k));
+        assert_eq!(got, vec![4..6, 5..7]);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(sample_subrange(&runs, 4, &mut rng), None);
+        assert_eq!(sample_subrange(&runs, 0, &mut rng), None);
+    }
+
+    /// Draws that cycle through 0, 1, 2, ...
+    fn counter() -> impl FnMut(u64) -> u64 {
+        let mut i = 0;
+        move |n| {
+            i += 1;
+            i % n
+        }
+    }
+
+    #[test]
+    fn random_runs_fit() {
+        let config = RunsConfig {
+            domain: 100..200,
+            runs: 7,
+            density: 0.3,
+            min_gap: 3,
+        };
+        let runs = runs_with(&config, counter()).unwrap();
+        assert_eq!(runs.len(), 7);
+        assert!(runs[0].start >= 100 && runs[6].end <= 200);
+        assert!(runs.iter().all(|r| r.start < r.end));
+        assert!(runs.windows(2).all(|w| w[1].start >= w[0].end + 3));
+        assert_eq!(runs.iter().map(|r| r.end - r.start).sum::<u64>(), 30);
+    }
+
+    #[test]
+    fn random_runs_tight() {
+        let config = RunsConfig {
+            domain: 0..5,
+            runs: 3,
+            density: 0.0,
+            min_gap: 1,
+        };
+        assert_eq!(runs_with(&config, counter()), Some(vec![0..1, 2..3, 4..5]));
+        let crowded = RunsConfig { runs: 4, ..config };
+        assert_eq!(runs_with(&crowded, counter()), None);
+    }
+
+    #[test]
+    fn sampler_matches_linear_walk() {
+        let runs = [0..2, 3..3, 5..9, 20..21, 30..40];
+        for len in 0..6 {
+            let sampler = Sampler::new(runs.clone(), len);
+            let expected: Vec<_> = runs
+                .iter()
+                .flat_map(|r| (r.start..r.end).map(move |s| s..s + len))
+                .filter(|s| len > 0 && runs.iter().any(|r| r.start <= s.start && s.end <= r.end))
+                .collect();
+            assert_eq!(sampler.total(), expected.len() as u64);
+            let got = all(sampler.total(), |k| sampler.pick(|_| k));
+            assert_eq!(got, expected);
+        }
+    }
+
+    #[test]
+    fn range_set_entry_points() {
+        let set: RangeSet<u64> = [0..2, 5..6, 8..10].into_iter().collect();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut seen = [false; 10];
+        for _ in 0..200 {
+            let x = set.sample(&mut rng).unwrap();
+            assert!(set.contains(x));
+            seen[x as usize] = true;
+        }
+        // With 200 draws from 5 elements, every one turns up
+        assert_eq!(seen.iter().filter(|&&s| s).count(), 5);
+        let r = set.sample_subrange(2, &mut rng).unwrap();
+        assert!(r == (0..2) || r == (8..10));
+        assert_eq!(RangeSet::<u64>::new().sample(&mut rng), None)
+    }
+
+    #[test]
+    fn random_set() {
+        let config = RunsConfig {
+            domain: 0..50,
+            runs: 5,
+            density: 0.4,
+            min_gap: 1,
+        };
+        let mut rng = StdRng::seed_from_u64(2);
+        let set: RangeSet<u64> = RangeSet::random(&config, &mut rng).unwrap();
+        assert_eq!(set.validate(), Ok(()));
+        assert_eq!(set.len(), 5);
+        assert_eq!(set.stats::<u64>().covered, 20);
+        // The same seed gives the same set
+        let again: RangeSet<u64> =
+            RangeSet::random(&config, &mut StdRng::seed_from_u64(2)).unwrap();
+        assert_eq!(set, again);
+        let crowded = RunsConfig { runs: 30, ..config };
+        assert_eq!(RangeSet::<u64>::random(&crowded, &mut rng), None)
+    }
+
+    #[test]
+    fn whole_domain() {
+        let runs = core::slice::from_ref(&(0..u64::MAX));
+        let last = Sampler::new(runs.iter().cloned(), 1).pick(|n| n - 1);
+        assert_eq!(last, Some(u64::MAX - 1..u64::MAX));
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(sample_subrange(runs, u64::MAX, &mut rng), Some(0..u64::MAX));
+        assert!(sample(runs, &mut rng).is_some());
+    }
+}
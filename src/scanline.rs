@@ -0,0 +1,156 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Coverage kept row by row, as rasterizers walk it.
+
+use core::ops::Range;
+
+use crate::rect::Rect;
+use crate::region::Region2D;
+use crate::storage::RangeSet;
+
+/** The covered spans of each row of a surface `height` rows tall.
+
+Rows are stored in order, each as its own [`RangeSet`],
+so walking the spans row by row reads memory in order.
+Anything outside the surface's rows is ignored.
+
+Usage:
+```
+# use range_split::{rect::Rect, scanline::SpanBuffer};
+let mut spans = SpanBuffer::new(4);
+spans.fill_rect(&Rect::new(0..8, 1..3));
+spans.subtract_rect(&Rect::new(2..4, 0..2));
+spans.fill_rect(&Rect::new(0..2, 3..9));
+assert_eq!(spans.spans().collect::<Vec<_>>(), vec![
+    (1, 0..2), (1, 4..8),
+    (2, 0..8),
+    (3, 0..2),
+]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanBuffer {
+    rows: Vec<RangeSet<u32>>,
+}
+
+impl SpanBuffer {
+    /// An empty buffer of `height` rows.
+    pub fn new(height: u32) -> Self {
+        Self {
+            rows: (0..height).map(|_| RangeSet::new()).collect(),
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        self.rows.len() as u32
+    }
+
+    /// The rows of `rect` within the buffer.
+    fn rows_of(&mut self, rect: &Rect<u32>) -> &mut [RangeSet<u32>] {
+        let height = self.height();
+        let y = rect.y.start.min(height)..rect.y.end.min(height);
+        &mut self.rows[y.start as usize..y.end.max(y.start) as usize]
+    }
+
+    /// Cover `rect`.
+    pub fn fill_rect(&mut self, rect: &Rect<u32>) {
+        for row in self.rows_of(rect) {
+            row.insert(rect.x.clone());
+        }
+    }
+
+    /// Uncover `rect`.
+    pub fn subtract_rect(&mut self, rect: &Rect<u32>) {
+        for row in self.rows_of(rect) {
+            row.remove(&rect.x);
+        }
+    }
+
+    /// The spans of row `y`, if it's in the buffer.
+    pub fn row(&self, y: u32) -> Option<&RangeSet<u32>> {
+        self.rows.get(y as usize)
+    }
+
+    /// Whether `(x, y)` is covered.
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        self.row(y).is_some_and(|row| row.contains(x))
+    }
+
+    /// Whether nothing is covered.
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(RangeSet::is_empty)
+    }
+
+    /// Uncover everything.
+    pub fn clear(&mut self) {
+        self.rows.iter_mut().for_each(|row| *row = RangeSet::new());
+    }
+
+    /// Each row and its spans, in order.
+    pub fn spans(&self) -> impl Iterator<Item = (u32, Range<u32>)> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().map(move |x| (y as u32, x)))
+    }
+
+    /// The covered region, with rows of equal spans merged into rectangles.
+    pub fn to_region(&self) -> Region2D<u32> {
+        let mut region = Region2D::new();
+        for (y, row) in (0..).zip(&self.rows) {
+            region.push_band(y..y + 1, row.iter().collect());
+        }
+        region
+    }
+
+    /// A buffer holding `region`, just tall enough for it.
+    pub fn from_region(region: &Region2D<u32>) -> Self {
+        let height = region.bounds().map_or(0, |b| b.y.end);
+        let mut spans = Self::new(height);
+        for rect in region.iter() {
+            spans.fill_rect(&rect);
+        }
+        spans
+    }
+}
+
+impl From<&Region2D<u32>> for SpanBuffer {
+    fn from(region: &Region2D<u32>) -> Self {
+        Self::from_region(region)
+    }
+}
+
+impl From<&SpanBuffer> for Region2D<u32> {
+    fn from(spans: &SpanBuffer) -> Self {
+        spans.to_region()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_round_trip() {
+        let region: Region2D<u32> = [Rect::new(0..10, 2..6), Rect::new(4..12, 5..8)]
+            .into_iter()
+            .collect();
+        let spans = SpanBuffer::from_region(&region);
+        assert_eq!(spans.height(), 8);
+        assert!(spans.contains(11, 7));
+        assert!(!spans.contains(11, 2));
+        assert_eq!(spans.to_region(), region)
+    }
+
+    #[test]
+    fn clipped_to_height() {
+        let mut spans = SpanBuffer::new(3);
+        spans.fill_rect(&Rect::new(0..4, 2..100));
+        spans.fill_rect(&Rect::new(0..4, 50..60));
+        assert_eq!(spans.spans().collect::<Vec<_>>(), vec![(2, 0..4)]);
+        spans.subtract_rect(&Rect::new(0..9, 0..9));
+        assert!(spans.is_empty());
+        assert!(spans.row(3).is_none())
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Calendar-style helpers for finding free time among busy intervals.
+
+use core::ops::{Range, Sub};
+
+use crate::{runs, Split};
+
+/** Find the free slots within `domain` that are not covered by any of `busy`.
+
+`busy` may be unsorted and may contain overlapping or empty ranges.
+If `min_len` is given, slots shorter than it are dropped.
+
+```
+# use range_split::schedule::free_slots;
+let busy = [10..12, 9..10, 14..15];
+assert_eq!(free_slots(&busy, &(9..17), None), vec![12..14, 15..17]);
+assert_eq!(free_slots(&busy, &(9..17), Some(2)), vec![12..14, 15..17]);
+assert_eq!(free_slots(&busy, &(9..17), Some(3)), vec![]);
+```
+**/
+pub fn free_slots<T, D>(busy: &[Range<T>], domain: &Range<T>, min_len: Option<D>) -> Vec<Range<T>>
+where
+    T: Sized + Ord + Copy + Sub<Output = D>,
+    D: Ord,
+{
+    let mut busy: Vec<Range<T>> = busy.iter().filter(|b| b.start < b.end).cloned().collect();
+    busy.sort_unstable_by_key(|b| b.start);
+
+    let mut free = Vec::new();
+    let mut rest = Some(domain.clone()).filter(|d| d.start < d.end);
+    for b in &busy {
+        let Some(r) = rest else { break };
+        let (below, _, above) = r.split(b);
+        free.extend(below);
+        rest = above;
+    }
+    free.extend(rest);
+
+    if let Some(min_len) = min_len {
+        free.retain(|f| f.end - f.start >= min_len);
+    }
+    free
+}
+
+/** Find the slots within `domain` that are free in every one of `calendars`.
+
+Each calendar is a list of busy ranges, as accepted by [`free_slots`].
+Calendars are intersected one at a time, stopping early once no common free time remains.
+
+```
+# use range_split::schedule::common_free;
+let alice = vec![9..10, 13..14];
+let bob = vec![11..13];
+assert_eq!(common_free(&[alice, bob], &(9..17), Some(1)), vec![10..11, 14..17]);
+```
+**/
+pub fn common_free<T, D, C>(calendars: &[C], domain: &Range<T>, min_len: Option<D>) -> Vec<Range<T>>
+where
+    T: Sized + Ord + Copy + Sub<Output = D>,
+    D: Ord,
+    C: AsRef<[Range<T>]>,
+{
+    let mut free = free_slots(&[], domain, None);
+    for cal in calendars {
+        if free.is_empty() {
+            break;
+        }
+        free = runs::intersect(&free, &free_slots(cal.as_ref(), domain, None));
+    }
+
+    if let Some(min_len) = min_len {
+        free.retain(|f| f.end - f.start >= min_len);
+    }
+    free
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Range<u32> = 9..17;
+
+    #[test]
+    fn nothing_busy() {
+        assert_eq!(free_slots(&[], &DAY, None), vec![9..17])
+    }
+
+    #[test]
+    fn busy_outside_domain() {
+        assert_eq!(free_slots(&[0..8, 18..20], &DAY, None), vec![9..17])
+    }
+
+    #[test]
+    fn overlapping_busy() {
+        assert_eq!(
+            free_slots(&[12..14, 10..13, 16..18], &DAY, None),
+            vec![9..10, 14..16]
+        )
+    }
+
+    #[test]
+    fn fully_booked() {
+        assert_eq!(free_slots(&[8..12, 12..20], &DAY, None), vec![])
+    }
+
+    #[test]
+    fn min_len_filter() {
+        assert_eq!(free_slots(&[10..11, 13..16], &DAY, Some(2)), vec![11..13])
+    }
+
+    #[test]
+    fn common_free_none_in_common() {
+        let cals = [
+            vec![9..11, 11..13],
+            vec![13..15, 15..17],
+            vec![10..11, 16..17],
+        ];
+        assert_eq!(common_free(&cals, &DAY, None), vec![])
+    }
+
+    #[test]
+    fn common_free_no_calendars() {
+        let cals: [Vec<Range<u32>>; 0] = [];
+        assert_eq!(common_free(&cals, &DAY, None), vec![9..17])
+    }
+
+    #[test]
+    fn common_free_min_len() {
+        let cals = [vec![10..11, 12..13], vec![15..16, 16..17]];
+        assert_eq!(common_free(&cals, &DAY, Some(2)), vec![13..15])
+    }
+}
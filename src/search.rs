@@ -0,0 +1,103 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Splitting ranges at points found by binary search.
+
+use core::ops::Range;
+
+use crate::interval_arith::Integer;
+
+/** Split `range` where a monotone predicate flips from `false` to `true`.
+
+`pred` must be `false` for some (possibly empty) prefix of `range` and `true` for the rest.
+Returns `(false prefix, true suffix)`, omitting empty parts.
+Only `O(log n)` calls to `pred` are made, and nothing overflows, even over all of `T`.
+
+```
+# use range_split::search::split_by_predicate;
+// The first "bad" version is 37
+assert_eq!(split_by_predicate(&(0..100), |&v| v >= 37), (Some(0..37), Some(37..100)));
+assert_eq!(split_by_predicate(&(0..100), |_| true), (None, Some(0..100)));
+```
+**/
+pub fn split_by_predicate<T>(
+    range: &Range<T>,
+    pred: impl Fn(&T) -> bool,
+) -> (Option<Range<T>>, Option<Range<T>>)
+where
+    T: Integer,
+{
+    let (mut lo, mut hi) = (range.start, range.end);
+    // Invariant: everything below `lo` is false, everything at or above `hi` is true
+    while lo < hi {
+        // Below `hi`, as it rounds down
+        let mid = lo.midpoint_floor(hi);
+        if pred(&mid) {
+            hi = mid;
+        } else {
+            lo = mid + T::ONE;
+        }
+    }
+    let prefix = Some(range.start..lo).filter(|r| r.start < r.end);
+    let suffix = Some(lo..range.end).filter(|r| r.start < r.end);
+    (prefix, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_false() {
+        assert_eq!(split_by_predicate(&(5..10), |_| false), (Some(5..10), None))
+    }
+
+    #[test]
+    fn empty_range() {
+        assert_eq!(split_by_predicate(&(5..5), |_| true), (None, None))
+    }
+
+    #[test]
+    fn every_split_point() {
+        for k in 0..=10u32 {
+            let (prefix, suffix) = split_by_predicate(&(0..10), |&x| x >= k);
+            assert_eq!(prefix.map_or(0, |p| p.end), k.min(10));
+            assert_eq!(suffix.map_or(10, |s| s.start), k.min(10));
+        }
+    }
+
+    #[test]
+    fn near_max() {
+        let r = u64::MAX - 3..u64::MAX;
+        assert_eq!(
+            split_by_predicate(&r, |&x| x > u64::MAX - 2),
+            (
+                Some(u64::MAX - 3..u64::MAX - 1),
+                Some(u64::MAX - 1..u64::MAX)
+            )
+        )
+    }
+
+    #[test]
+    fn counts_calls() {
+        let calls = core::cell::Cell::new(0);
+        split_by_predicate(&(0..1u64 << 40), |&x| {
+            calls.set(calls.get() + 1);
+            x >= 12345
+        });
+        assert!(calls.get() <= 41);
+    }
+
+    #[test]
+    fn full_signed_domain() {
+        assert_eq!(
+            split_by_predicate(&(-30000i16..30000), |&x| x >= 50),
+            (Some(-30000..50), Some(50..30000))
+        );
+        for k in [i8::MIN, -1, 0, 1, i8::MAX] {
+            let (prefix, suffix) = split_by_predicate(&(i8::MIN..i8::MAX), |&x| x >= k);
+            assert_eq!(prefix.map_or(i8::MIN, |p| p.end), k);
+            assert_eq!(suffix.map_or(i8::MAX, |s| s.start), k);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Choosing non-overlapping subsets of ranges, as when accepting bookings.
+
+use core::ops::{Add, Range};
+
+/** The non-overlapping subset of `ranges` with the greatest total weight,
+as that weight and the indices of the chosen ranges, in order of position.
+
+Ranges that only touch don't overlap. Ranges with a negative weight are never chosen,
+and on a tie the smaller selection wins. Takes `O(n log n)` time.
+
+```
+# use range_split::select::max_weight_disjoint_subset;
+// One long, lucrative booking against two short ones
+let bookings = [(0..10, 50), (0..4, 30), (4..10, 30), (10..12, 5)];
+assert_eq!(max_weight_disjoint_subset(&bookings), (65, vec![1, 2, 3]));
+```
+**/
+pub fn max_weight_disjoint_subset<T, W>(ranges: &[(Range<T>, W)]) -> (W, Vec<usize>)
+where
+    T: Sized + Ord + Copy,
+    W: Copy + Ord + Default + Add<Output = W>,
+{
+    let mut by_end: Vec<usize> = (0..ranges.len()).collect();
+    by_end.sort_by_key(|&i| (ranges[i].0.end, ranges[i].0.start));
+    let ends: Vec<T> = by_end.iter().map(|&i| ranges[i].0.end).collect();
+
+    // best[k] is the greatest weight using only the first k ranges by end;
+    // took[k] whether that uses the kth, and compatible[k] how many end before it starts
+    let mut best = vec![W::default(); by_end.len() + 1];
+    let mut took = vec![false; by_end.len()];
+    let mut compatible = vec![0; by_end.len()];
+    for (k, &i) in by_end.iter().enumerate() {
+        let (range, weight) = &ranges[i];
+        // Only ranges earlier in the order can precede this one; an empty range would count itself
+        compatible[k] = ends.partition_point(|&e| e <= range.start).min(k);
+        let with = best[compatible[k]] + *weight;
+        took[k] = with > best[k];
+        best[k + 1] = if took[k] { with } else { best[k] };
+    }
+
+    let mut chosen = Vec::new();
+    let mut k = by_end.len();
+    while k > 0 {
+        if took[k - 1] {
+            chosen.push(by_end[k - 1]);
+            k = compatible[k - 1];
+        } else {
+            k -= 1;
+        }
+    }
+    chosen.sort_by_key(|&i| (ranges[i].0.start, ranges[i].0.end));
+    (best[by_end.len()], chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Best weight by trying every subset.
+    fn brute(ranges: &[(Range<u32>, i32)]) -> i32 {
+        (0u32..1 << ranges.len())
+            .filter_map(|mask| {
+                let picked: Vec<_> = (0..ranges.len()).filter(|i| mask >> i & 1 == 1).collect();
+                let disjoint = picked.iter().all(|&a| {
+                    picked.iter().all(|&b| {
+                        a == b
+                            || ranges[a].0.end <= ranges[b].0.start
+                            || ranges[b].0.end <= ranges[a].0.start
+                    })
+                });
+                disjoint.then(|| picked.iter().map(|&i| ranges[i].1).sum())
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut x: u32 = 3;
+        for _ in 0..200 {
+            let ranges: Vec<(Range<u32>, i32)> = (0..8)
+                .map(|_| {
+                    x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    let start = (x >> 16) % 20;
+                    let len = 1 + (x >> 8) % 6;
+                    (start..start + len, (x >> 4) as i32 % 13 - 2)
+                })
+                .collect();
+            let (weight, chosen) = max_weight_disjoint_subset(&ranges);
+            assert_eq!(weight, brute(&ranges));
+            assert_eq!(chosen.iter().map(|&i| ranges[i].1).sum::<i32>(), weight);
+            assert!(chosen
+                .windows(2)
+                .all(|w| ranges[w[0]].0.end <= ranges[w[1]].0.start));
+        }
+    }
+
+    #[test]
+    fn empty_and_negative() {
+        let none: [(Range<u8>, i32); 0] = [];
+        assert_eq!(max_weight_disjoint_subset(&none), (0, vec![]));
+        assert_eq!(
+            max_weight_disjoint_subset(&[(0..5u8, -3), (5..6, -1)]),
+            (0, vec![])
+        )
+    }
+}
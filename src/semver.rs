@@ -0,0 +1,490 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Version requirements as sets of half-open ranges of [`semver::Version`]s,
+//! so that they can be intersected, subtracted and combined exactly.
+//!
+//! Versions are ordered as [`Version`]'s `Ord` orders them: pre-releases come just
+//! before their release, and build metadata after it. Each comparator of a
+//! [`VersionReq`] is read as the versions it admits in that order: so unlike Cargo,
+//! which keeps pre-releases out of requirements that don't name one, `>=1.0.0, <2.0.0`
+//! holds `1.5.0-beta`. Caret, tilde, wildcard and partial comparators stop short of the
+//! pre-releases of the next incompatible version: `^1.2` is `>=1.2.0, <2.0.0-0`.
+
+use core::fmt;
+use core::ops::Range;
+use core::str::FromStr;
+
+use semver::{BuildMetadata, Comparator, Op, Prerelease, Version, VersionReq};
+
+/// A point in the order of versions: a version, or past them all.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Point {
+    At(Version),
+    Infinity,
+}
+
+/// Whether `v` comes before `p`.
+fn is_before(v: &Version, p: &Point) -> bool {
+    match p {
+        Point::At(p) => v < p,
+        Point::Infinity => true,
+    }
+}
+
+/// The least version of all.
+fn min_version() -> Version {
+    first_of(0, 0, 0)
+}
+
+/// `major.minor.patch-0`, the least version with those numbers.
+fn first_of(major: u64, minor: u64, patch: u64) -> Version {
+    Version {
+        pre: Prerelease::new("0").unwrap(),
+        ..Version::new(major, minor, patch)
+    }
+}
+
+/// The least version after every `major.minor.patch`, carrying into the minor and major parts.
+fn after_patch(major: u64, minor: u64, patch: u64) -> Point {
+    match patch.checked_add(1) {
+        Some(patch) => Point::At(first_of(major, minor, patch)),
+        None => after_minor(major, minor),
+    }
+}
+
+/// The least version after every `major.minor.*`, carrying into the major part.
+fn after_minor(major: u64, minor: u64) -> Point {
+    match minor.checked_add(1) {
+        Some(minor) => Point::At(first_of(major, minor, 0)),
+        None => after_major(major),
+    }
+}
+
+/// The least version after every `major.*`, if there is one.
+fn after_major(major: u64) -> Point {
+    major
+        .checked_add(1)
+        .map_or(Point::Infinity, |major| Point::At(first_of(major, 0, 0)))
+}
+
+/// The least version after `v` and all its builds.
+fn after(v: &Version) -> Point {
+    if v.pre.is_empty() {
+        after_patch(v.major, v.minor, v.patch)
+    } else {
+        // `alpha.0` is the first pre-release identifier list after `alpha`
+        let pre = Prerelease::new(&format!("{}.0", v.pre)).unwrap();
+        Point::At(Version {
+            pre,
+            build: BuildMetadata::EMPTY,
+            ..v.clone()
+        })
+    }
+}
+
+/// A version requirement that couldn't be read as a set of versions.
+#[derive(Debug)]
+pub enum ReqError {
+    /// The requirement isn't valid syntax.
+    Syntax(semver::Error),
+    /// A comparator uses an operator this version of the crate doesn't know.
+    UnknownOp(Comparator),
+}
+
+impl fmt::Display for ReqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(e) => write!(f, "invalid version requirement: {e}"),
+            Self::UnknownOp(c) => write!(f, "unknown operator in `{c}`"),
+        }
+    }
+}
+
+impl std::error::Error for ReqError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Syntax(e) => Some(e),
+            Self::UnknownOp(_) => None,
+        }
+    }
+}
+
+impl From<semver::Error> for ReqError {
+    fn from(e: semver::Error) -> Self {
+        Self::Syntax(e)
+    }
+}
+
+/** A set of versions, kept as sorted, disjoint, coalesced half-open ranges.
+
+Usage:
+```
+# use range_split::semver::VersionSet;
+use semver::Version;
+
+let wanted: VersionSet = "^1.2".parse().unwrap();
+let broken: VersionSet = ">=1.4.0, <1.4.3".parse().unwrap();
+let ok = wanted.subtract(&broken);
+assert!(ok.contains(&Version::parse("1.3.9").unwrap()));
+assert!(!ok.contains(&Version::parse("1.4.1+build.7").unwrap()));
+// Pre-releases come before their release
+assert!(!ok.contains(&Version::parse("1.4.3-rc.1").unwrap()));
+assert_eq!(ok.to_req(), None); // Cargo has no syntax for unions
+
+let both = ok.intersect(&"~1.4".parse().unwrap());
+assert_eq!(both.to_req().unwrap().to_string(), ">=1.4.3, <1.5.0-0");
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct VersionSet {
+    /// Non-empty; every start is `Point::At`.
+    runs: Vec<Range<Point>>,
+}
+
+impl VersionSet {
+    /// No versions.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Every version.
+    pub fn all() -> Self {
+        Self::from_range(min_version(), None)
+    }
+
+    /// The versions from `start`, up to but not including `end` if there is one.
+    pub fn from_range(start: Version, end: Option<Version>) -> Self {
+        Self::from_points(Point::At(start), end.map_or(Point::Infinity, Point::At))
+    }
+
+    fn from_points(start: Point, end: Point) -> Self {
+        let runs = if start < end {
+            vec![start..end]
+        } else {
+            Vec::new()
+        };
+        Self { runs }
+    }
+
+    /// The versions matching one comparator, such as `>=1.2` or `~0.3.1`.
+    fn comparator(c: &Comparator) -> Result<Self, ReqError> {
+        let lower = Version {
+            pre: c.pre.clone(),
+            ..Version::new(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0))
+        };
+        // The least version after all those matching `c` exactly
+        let upper = match (c.minor, c.patch) {
+            (Some(_), Some(_)) => after(&lower),
+            (Some(minor), None) => after_minor(c.major, minor),
+            _ => after_major(c.major),
+        };
+        let (start, end) = match c.op {
+            Op::Exact | Op::Wildcard => (lower, upper),
+            Op::Greater => match upper {
+                Point::At(v) => (v, Point::Infinity),
+                Point::Infinity => return Ok(Self::empty()),
+            },
+            Op::GreaterEq => (lower, Point::Infinity),
+            Op::Less => (min_version(), Point::At(lower)),
+            Op::LessEq => (min_version(), upper),
+            Op::Tilde => match c.minor {
+                Some(minor) => (lower, after_minor(c.major, minor)),
+                None => (lower, upper),
+            },
+            Op::Caret => {
+                let end = match (c.major, c.minor, c.patch) {
+                    (0, Some(0), Some(patch)) => after_patch(0, 0, patch),
+                    (0, Some(minor), _) => after_minor(0, minor),
+                    _ => after_major(c.major),
+                };
+                (lower, end)
+            }
+            _ => return Err(ReqError::UnknownOp(c.clone())),
+        };
+        Ok(Self::from_points(Point::At(start), end))
+    }
+
+    pub fn contains(&self, v: &Version) -> bool {
+        let i = self.runs.partition_point(|r| !is_before(v, &r.end));
+        self.runs.get(i).is_some_and(|r| !is_before(v, &r.start))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// The ranges of versions, in order, as their starts and ends (`None` for no upper bound).
+    pub fn ranges(&self) -> impl Iterator<Item = (&Version, Option<&Version>)> {
+        self.runs.iter().map(|r| match (&r.start, &r.end) {
+            (Point::At(start), Point::At(end)) => (start, Some(end)),
+            (Point::At(start), Point::Infinity) => (start, None),
+            (Point::Infinity, _) => unreachable!("runs start at a version"),
+        })
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut all: Vec<&Range<Point>> = self.runs.iter().chain(&other.runs).collect();
+        all.sort_by(|a, b| a.start.cmp(&b.start));
+        let mut runs: Vec<Range<Point>> = Vec::new();
+        for r in all {
+            match runs.last_mut() {
+                Some(last) if r.start <= last.end => {
+                    if r.end > last.end {
+                        last.end = r.end.clone();
+                    }
+                }
+                _ => runs.push(r.clone()),
+            }
+        }
+        Self { runs }
+    }
+
+    pub fn intersect(&self, other: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut runs = Vec::new();
+        while let (Some(a), Some(b)) = (self.runs.get(i), other.runs.get(j)) {
+            let start = (&a.start).max(&b.start);
+            let end = (&a.end).min(&b.end);
+            if start < end {
+                runs.push(start.clone()..end.clone());
+            }
+            if a.end <= b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { runs }
+    }
+
+    pub fn subtract(&self, other: &Self) -> Self {
+        let mut runs = Vec::new();
+        let mut j = 0;
+        for a in &self.runs {
+            let mut start = a.start.clone();
+            // Skip the runs of `other` wholly before this one
+            while other.runs.get(j).is_some_and(|b| b.end <= start) {
+                j += 1;
+            }
+            let mut k = j;
+            while let Some(b) = other.runs.get(k).filter(|b| b.start < a.end) {
+                if start < b.start {
+                    runs.push(start.clone()..b.start.clone());
+                }
+                if b.end >= a.end {
+                    start = a.end.clone();
+                    break;
+                }
+                start = b.end.clone();
+                k += 1;
+            }
+            if start < a.end {
+                runs.push(start..a.end.clone());
+            }
+        }
+        Self { runs }
+    }
+
+    /// The versions only in `self`, in both, and only in `other`.
+    pub fn split(&self, other: &Self) -> (Self, Self, Self) {
+        (
+            self.subtract(other),
+            self.intersect(other),
+            other.subtract(self),
+        )
+    }
+
+    /// The set as a requirement, if it's a single range whose ends have no build metadata,
+    /// which comparators can't express.
+    pub fn to_req(&self) -> Option<VersionReq> {
+        let [r] = self.runs.as_slice() else {
+            return None;
+        };
+        let Point::At(start) = &r.start else {
+            unreachable!("runs start at a version")
+        };
+        let cmp = |op, v: &Version| {
+            v.build.is_empty().then(|| Comparator {
+                op,
+                major: v.major,
+                minor: Some(v.minor),
+                patch: Some(v.patch),
+                pre: v.pre.clone(),
+            })
+        };
+        let comparators = match &r.end {
+            Point::Infinity if *start == min_version() => Vec::new(),
+            Point::Infinity => vec![cmp(Op::GreaterEq, start)?],
+            end if *end == after(start) => vec![cmp(Op::Exact, start)?],
+            Point::At(end) if *start == min_version() => vec![cmp(Op::Less, end)?],
+            Point::At(end) => vec![cmp(Op::GreaterEq, start)?, cmp(Op::Less, end)?],
+        };
+        Some(VersionReq { comparators })
+    }
+}
+
+impl TryFrom<&VersionReq> for VersionSet {
+    type Error = ReqError;
+
+    /// The versions matching all of `req`'s comparators.
+    fn try_from(req: &VersionReq) -> Result<Self, Self::Error> {
+        req.comparators.iter().try_fold(Self::all(), |set, c| {
+            Ok(set.intersect(&Self::comparator(c)?))
+        })
+    }
+}
+
+impl FromStr for VersionSet {
+    type Err = ReqError;
+
+    /// Parse a Cargo requirement: comma-separated comparators, all of which must match.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(&VersionReq::parse(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The single range matching `s`.
+    fn req(s: &str) -> (Version, Option<Version>) {
+        let set: VersionSet = s.parse().unwrap();
+        let ranges: Vec<_> = set.ranges().collect();
+        match ranges.as_slice() {
+            [(start, end)] => ((*start).clone(), end.cloned()),
+            other => panic!("{s} gave {other:?}"),
+        }
+    }
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn caret() {
+        assert_eq!(req("^1.2.3"), (v("1.2.3"), Some(v("2.0.0-0"))));
+        assert_eq!(req("0.2.3"), (v("0.2.3"), Some(v("0.3.0-0"))));
+        assert_eq!(req("^0.0.3"), (v("0.0.3"), Some(v("0.0.4-0"))));
+        assert_eq!(req("^0.0.3-beta"), (v("0.0.3-beta"), Some(v("0.0.4-0"))));
+        assert_eq!(req("^0.0"), (v("0.0.0"), Some(v("0.1.0-0"))));
+        assert_eq!(req("^0"), (v("0.0.0"), Some(v("1.0.0-0"))));
+    }
+
+    #[test]
+    fn tilde_and_wildcards() {
+        assert_eq!(req("~1.2.3"), (v("1.2.3"), Some(v("1.3.0-0"))));
+        assert_eq!(req("~1"), (v("1.0.0"), Some(v("2.0.0-0"))));
+        assert_eq!(req("1.*"), (v("1.0.0"), Some(v("2.0.0-0"))));
+        assert_eq!(req("*"), (v("0.0.0-0"), None));
+    }
+
+    #[test]
+    fn inequalities() {
+        assert_eq!(req(">1.2"), (v("1.3.0-0"), None));
+        assert_eq!(req("<=1.2.3"), (v("0.0.0-0"), Some(v("1.2.4-0"))));
+        assert_eq!(req(">= 1.0, < 1.5"), (v("1.0.0"), Some(v("1.5.0"))));
+        assert!(">2, <1".parse::<VersionSet>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn pre_releases_and_builds() {
+        let exact: VersionSet = "=1.2.3".parse().unwrap();
+        assert!(exact.contains(&v("1.2.3+build.5")));
+        assert!(!exact.contains(&v("1.2.3-rc.1")));
+        assert!(!exact.contains(&v("1.2.4-0")));
+
+        let pre: VersionSet = "=1.0.0-alpha".parse().unwrap();
+        assert!(pre.contains(&v("1.0.0-alpha+exp.sha.5114f85")));
+        assert!(!pre.contains(&v("1.0.0-alpha.0")));
+
+        let rc: VersionSet = ">=1.0.0-rc.1, <1.0.0".parse().unwrap();
+        assert!(rc.contains(&v("1.0.0-rc.2")));
+        assert!(!rc.contains(&v("1.0.0")));
+
+        let caret: VersionSet = "^1".parse().unwrap();
+        assert!(caret.contains(&v("1.9.0-beta")));
+        assert!(!caret.contains(&v("2.0.0-alpha")));
+    }
+
+    #[test]
+    fn bad_requirements() {
+        for bad in ["", "^", "1.2.3.4", "1.*.3", "!1.0", ">=1, ?"] {
+            assert!(bad.parse::<VersionSet>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn carries_at_the_limits() {
+        const M: u64 = u64::MAX;
+        let first = |s: &str| Some(v(&format!("{s}-0")));
+        assert_eq!(
+            req(&format!("=1.2.{M}")),
+            (v(&format!("1.2.{M}")), first("1.3.0"))
+        );
+        assert_eq!(req(&format!("<=1.{M}.{M}")), (v("0.0.0-0"), first("2.0.0")));
+        assert_eq!(req(&format!(">1.{M}")), (v("2.0.0-0"), None));
+        assert_eq!(
+            req(&format!("~1.{M}.3")),
+            (v(&format!("1.{M}.3")), first("2.0.0"))
+        );
+        assert_eq!(
+            req(&format!("^0.0.{M}")),
+            (v(&format!("0.0.{M}")), first("0.1.0"))
+        );
+        assert_eq!(req(&format!("^{M}.1")), (v(&format!("{M}.1.0")), None));
+        assert_eq!(
+            req(&format!("={M}.{M}.{M}")),
+            (v(&format!("{M}.{M}.{M}")), None)
+        );
+        assert!(format!(">{M}.{M}.{M}")
+            .parse::<VersionSet>()
+            .unwrap()
+            .is_empty());
+
+        let exact = format!("=1.2.{M}");
+        let set: VersionSet = exact.parse().unwrap();
+        assert_eq!(set.to_req().unwrap().to_string(), exact);
+    }
+
+    #[test]
+    fn round_trips_through_to_req() {
+        for s in [
+            "*",
+            "=1.2.3",
+            "=1.0.0-alpha",
+            "<1.0.0",
+            ">=0.3.0",
+            ">=1.2.0, <1.3.0-0",
+        ] {
+            let set: VersionSet = s.parse().unwrap();
+            assert_eq!(set.to_req().unwrap().to_string(), s);
+        }
+        let built = VersionSet::from_range(v("1.0.0+build"), None);
+        assert_eq!(built.to_req(), None);
+    }
+
+    #[test]
+    fn split_three_ways() {
+        let a: VersionSet = "^1".parse().unwrap();
+        let b: VersionSet = ">=1.5".parse().unwrap();
+        let (only_a, both, only_b) = a.split(&b);
+        assert_eq!(only_a.to_req().unwrap().to_string(), ">=1.0.0, <1.5.0");
+        assert_eq!(both.to_req().unwrap().to_string(), ">=1.5.0, <2.0.0-0");
+        assert_eq!(only_b.to_req().unwrap().to_string(), ">=2.0.0-0");
+    }
+
+    #[test]
+    fn set_algebra() {
+        let parse = |s: &str| s.parse::<VersionSet>().unwrap();
+        let holes = parse("^1")
+            .subtract(&parse("~1.2"))
+            .subtract(&parse("~1.4"));
+        assert_eq!(holes.ranges().count(), 3);
+        let filled = holes.union(&parse("~1.2")).union(&parse("~1.4"));
+        assert_eq!(filled, parse("^1"));
+        assert_eq!(holes.intersect(&parse("~1.2")), VersionSet::empty());
+        assert_eq!(parse("*").subtract(&parse("*")), VersionSet::empty());
+    }
+}
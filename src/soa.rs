@@ -0,0 +1,170 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! A range set stored as two parallel arrays, of starts and of ends.
+
+use core::ops::Range;
+
+/** A sorted, disjoint, coalesced set of ranges, with the starts and ends of its runs
+kept in separate arrays rather than as a list of `Range`s.
+
+Lookups binary search the starts alone, so they touch half the memory,
+which matters once sets outgrow the cache.
+
+Usage:
+```
+# use range_split::soa::SoaRangeSet;
+let mut set = SoaRangeSet::new();
+set.insert(0..10);
+set.insert(20..30);
+set.insert(10..12);
+set.remove(&(5..7));
+assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..5, 7..12, 20..30]);
+assert!(set.contains(8));
+assert_eq!(set.overlapping(&(11..21)).collect::<Vec<_>>(), vec![7..12, 20..30]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoaRangeSet<T> {
+    starts: Vec<T>,
+    /// Always the same length as `starts`.
+    ends: Vec<T>,
+}
+
+impl<T> Default for SoaRangeSet<T> {
+    fn default() -> Self {
+        Self {
+            starts: Vec::new(),
+            ends: Vec::new(),
+        }
+    }
+}
+
+impl<T> SoaRangeSet<T>
+where
+    T: Sized + Ord + Copy,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `range`, merging it with any runs it overlaps or touches.
+    pub fn insert(&mut self, range: impl Into<Range<T>>) {
+        let range = range.into();
+        if range.start >= range.end {
+            return;
+        }
+        let i = self.ends.partition_point(|&e| e < range.start);
+        let j = self.starts.partition_point(|&s| s <= range.end);
+        let mut merged = range;
+        if i < j {
+            merged.start = merged.start.min(self.starts[i]);
+            merged.end = merged.end.max(self.ends[j - 1]);
+        }
+        self.starts.splice(i..j, [merged.start]);
+        self.ends.splice(i..j, [merged.end]);
+    }
+
+    /// Remove `range`, splitting any run it falls inside.
+    pub fn remove(&mut self, range: &Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        let i = self.ends.partition_point(|&e| e <= range.start);
+        let j = self.starts.partition_point(|&s| s < range.end);
+        if i == j {
+            return;
+        }
+        let (first, last) = (self.starts[i], self.ends[j - 1]);
+        let below = (first < range.start).then_some(first..range.start);
+        let above = (range.end < last).then_some(range.end..last);
+        let kept = || below.clone().into_iter().chain(above.clone());
+        self.starts.splice(i..j, kept().map(|r| r.start));
+        self.ends.splice(i..j, kept().map(|r| r.end));
+    }
+
+    /// Index of the first run ending after `t`.
+    fn first_ending_after(&self, t: T) -> usize {
+        let i = self.starts.partition_point(|&s| s <= t);
+        if i > 0 && self.ends[i - 1] > t {
+            i - 1
+        } else {
+            i
+        }
+    }
+
+    pub fn contains(&self, t: T) -> bool {
+        self.containing(t).is_some()
+    }
+
+    /// The run containing `t`, if any.
+    pub fn containing(&self, t: T) -> Option<Range<T>> {
+        let i = self.starts.partition_point(|&s| s <= t);
+        (i > 0 && self.ends[i - 1] > t).then(|| self.starts[i - 1]..self.ends[i - 1])
+    }
+
+    /// The runs that overlap `range`, whole and in order.
+    pub fn overlapping(&self, range: &Range<T>) -> impl Iterator<Item = Range<T>> + '_ {
+        let (i, j) = if range.start < range.end {
+            let i = self.first_ending_after(range.start);
+            (i, self.starts.partition_point(|&s| s < range.end).max(i))
+        } else {
+            (0, 0)
+        };
+        (i..j).map(|k| self.starts[k]..self.ends[k])
+    }
+
+    /// Number of disjoint runs.
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// The runs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.starts.iter().zip(&self.ends).map(|(&s, &e)| s..e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffered::Buffered;
+
+    #[test]
+    fn matches_run_list() {
+        let mut soa = SoaRangeSet::new();
+        let mut aos = Buffered::new();
+        let mut x: u32 = 7;
+        for step in 0..2000 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let start = (x >> 16) % 500;
+            let range = start..start + (x >> 8) % 20;
+            if step % 3 == 0 {
+                soa.remove(&range);
+                aos.remove(&range);
+            } else {
+                soa.insert(range.clone());
+                aos.add(range);
+            }
+            assert!(soa.iter().eq(aos.iter().cloned()));
+        }
+        for t in 0..520 {
+            assert_eq!(soa.contains(t), aos.contains(t));
+        }
+    }
+
+    #[test]
+    fn overlapping_edges() {
+        let mut set = SoaRangeSet::new();
+        set.insert(10..20);
+        set.insert(30..40);
+        assert_eq!(set.overlapping(&(20..30)).count(), 0);
+        assert_eq!(set.overlapping(&(19..31)).count(), 2);
+        assert_eq!(set.overlapping(&(15..15)).count(), 0);
+        assert_eq!(set.overlapping(&(0..100)).count(), 2)
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Flattening overlapping, attributed spans (styles, labels) into disjoint segments.
+
+use core::ops::Range;
+use std::collections::BTreeSet;
+
+/** Flatten possibly-overlapping `spans` into sorted, disjoint spans.
+
+Each output segment's style is `merge` applied to the styles of every input span covering it,
+in input order. Uncovered gaps and empty input spans produce no output.
+
+```
+# use range_split::spans::flatten_spans;
+let spans = [(0..10, "bold"), (5..15, "red")];
+let flat = flatten_spans(spans, |s| s.join("+"));
+assert_eq!(flat, vec![(0..5, "bold".into()), (5..10, "bold+red".into()), (10..15, "red".into())]);
+```
+**/
+pub fn flatten_spans<T, S, M>(
+    spans: impl IntoIterator<Item = (Range<T>, S)>,
+    merge: impl Fn(&[S]) -> M,
+) -> Vec<(Range<T>, M)>
+where
+    T: Sized + Ord + Copy,
+    S: Clone,
+{
+    let (ranges, styles): (Vec<Range<T>>, Vec<S>) =
+        spans.into_iter().filter(|(r, _)| r.start < r.end).unzip();
+
+    // (position, is_start, span index); ends sort before starts at the same position
+    let mut events: Vec<(T, bool, usize)> = ranges
+        .iter()
+        .enumerate()
+        .flat_map(|(i, r)| [(r.start, true, i), (r.end, false, i)])
+        .collect();
+    events.sort_unstable();
+
+    let mut out = Vec::new();
+    let mut active = BTreeSet::new();
+    let mut covering = Vec::new();
+    let mut e = 0;
+    while e < events.len() {
+        let pos = events[e].0;
+        while e < events.len() && events[e].0 == pos {
+            let (_, is_start, i) = events[e];
+            if is_start {
+                active.insert(i);
+            } else {
+                active.remove(&i);
+            }
+            e += 1;
+        }
+        if let (Some(&(next, _, _)), false) = (events.get(e), active.is_empty()) {
+            covering.clear();
+            covering.extend(active.iter().map(|&i| styles[i].clone()));
+            out.push((pos..next, merge(&covering)));
+        }
+    }
+    out
+}
+
+/** Segment possibly-overlapping labeled spans, as for compiler-style diagnostics.
+
+Each output segment carries every label active over it, in input order,
+so rendering is stable regardless of nesting.
+
+```
+# use range_split::spans::label_segments;
+let spans = [(4..9, "expected `u32`"), (0..12, "in this call")];
+assert_eq!(label_segments(spans), vec![
+    (0..4, vec!["in this call"]),
+    (4..9, vec!["expected `u32`", "in this call"]),
+    (9..12, vec!["in this call"]),
+]);
+```
+**/
+pub fn label_segments<T, L>(
+    spans: impl IntoIterator<Item = (Range<T>, L)>,
+) -> Vec<(Range<T>, Vec<L>)>
+where
+    T: Sized + Ord + Copy,
+    L: Clone,
+{
+    flatten_spans(spans, <[L]>::to_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concat(spans: &[(Range<u32>, char)]) -> Vec<(Range<u32>, String)> {
+        flatten_spans(spans.iter().cloned(), |s| s.iter().collect())
+    }
+
+    #[test]
+    fn disjoint_passthrough() {
+        assert_eq!(
+            concat(&[(5..8, 'b'), (0..3, 'a')]),
+            vec![(0..3, "a".into()), (5..8, "b".into())]
+        )
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(
+            concat(&[(0..10, 'a'), (3..6, 'b')]),
+            vec![(0..3, "a".into()), (3..6, "ab".into()), (6..10, "a".into())]
+        )
+    }
+
+    #[test]
+    fn input_order_not_position_order() {
+        assert_eq!(
+            concat(&[(4..6, 'x'), (0..6, 'y')]),
+            vec![(0..4, "y".into()), (4..6, "xy".into())]
+        )
+    }
+
+    #[test]
+    fn touching_and_empty() {
+        assert_eq!(
+            concat(&[(0..3, 'a'), (3..5, 'b'), (4..4, 'c')]),
+            vec![(0..3, "a".into()), (3..5, "b".into())]
+        )
+    }
+
+    #[test]
+    fn labels_identical_spans() {
+        assert_eq!(
+            label_segments([(2..4, 1), (2..4, 2), (3..6, 3)]),
+            vec![(2..3, vec![1, 2]), (3..4, vec![1, 2, 3]), (4..6, vec![3])]
+        )
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Finding the ranges that contain each of many points.
+
+use core::ops::Range;
+
+/** For each of `points`, the indices of the `ranges` that contain it, in ascending order.
+
+Both inputs must be sorted (`ranges` by start), and are swept together in one pass,
+so the cost is proportional to the input plus the output
+(plus the number of ranges active at each point), rather than ranges times points.
+
+```
+# use range_split::stab::stab_all;
+let ranges = [0..10, 2..4, 3..20, 15..16];
+let hits = stab_all(&ranges, &[1, 3, 10, 15, 30]);
+assert_eq!(hits, vec![vec![0], vec![0, 1, 2], vec![2], vec![2, 3], vec![]]);
+```
+**/
+pub fn stab_all<T>(ranges: &[Range<T>], points: &[T]) -> Vec<Vec<usize>>
+where
+    T: Sized + Ord,
+{
+    let mut active: Vec<usize> = Vec::new();
+    let mut next = 0;
+    points
+        .iter()
+        .map(|p| {
+            while let Some(r) = ranges.get(next).filter(|r| r.start <= *p) {
+                if r.start < r.end {
+                    active.push(next);
+                }
+                next += 1;
+            }
+            active.retain(|&i| *p < ranges[i].end);
+            active.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundaries_are_half_open() {
+        let ranges = [0..5, 5..10];
+        assert_eq!(
+            stab_all(&ranges, &[0, 5, 10]),
+            vec![vec![0], vec![1], vec![]]
+        );
+    }
+
+    #[test]
+    fn repeated_points() {
+        let ranges = [0..5, 1..2];
+        assert_eq!(stab_all(&ranges, &[1, 1]), vec![vec![0, 1], vec![0, 1]]);
+    }
+
+    #[test]
+    fn empty_ranges_never_match() {
+        let (a, b) = (3, 3);
+        let ranges = [0..1, a..b];
+        assert_eq!(stab_all(&ranges, &[3]), vec![Vec::<usize>::new()]);
+    }
+
+    #[test]
+    fn no_points() {
+        assert!(stab_all(&[0..1, 2..3], &[]).is_empty())
+    }
+}
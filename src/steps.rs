@@ -0,0 +1,116 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Step functions, such as pricing tiers or configuration epochs, given as
+//! the points where the value changes: "from here onwards, the value is `V`".
+
+use core::ops::Range;
+
+use crate::map::RangeMap;
+
+impl<T, V> RangeMap<T, V>
+where
+    T: Sized + Ord + Copy,
+    V: Clone + PartialEq,
+{
+    /** The map of the ranges of `domain` over which each value holds, given the points
+    where the value changes. They needn't be sorted. If two change at the same point, the later one wins.
+
+    A change before `domain` gives the value at its start. Nothing is given for the part of
+    `domain` before the first change, or for changes at or after its end. Neighbouring ranges
+    with equal values are merged.
+
+    ```
+    # use range_split::map::RangeMap;
+    let tiers = [(0, "free"), (100, "basic"), (1000, "pro"), (5000, "pro")];
+    assert_eq!(
+        RangeMap::from_boundaries(&(50..2000), tiers).as_slice(),
+        &[(50..100, "free"), (100..1000, "basic"), (1000..2000, "pro")]
+    );
+    ```
+    **/
+    pub fn from_boundaries(
+        domain: &Range<T>,
+        boundaries: impl IntoIterator<Item = (T, V)>,
+    ) -> Self {
+        let mut changes: Vec<(T, V)> = boundaries.into_iter().collect();
+        // Stable, so that of changes at the same point the later is inserted later
+        changes.sort_by_key(|(at, _)| *at);
+
+        let mut map = Self::new();
+        for (at, v) in changes {
+            let at = at.max(domain.start);
+            if at >= domain.end {
+                break;
+            }
+            // Everything from `at` onwards takes the new value
+            map.insert(at..domain.end, v);
+        }
+        map
+    }
+
+    /** The points where the value of a step function changes, and the value from each onwards:
+    the inverse of [`from_boundaries`](Self::from_boundaries).
+
+    `None` if the map has gaps, since a gap can't be told apart from the
+    previous value carrying on.
+
+    ```
+    # use range_split::map::RangeMap;
+    let epochs: RangeMap<i32, char> = [(0..10, 'a'), (10..25, 'b'), (25..30, 'a')].into_iter().collect();
+    assert_eq!(epochs.to_boundaries(), Some(vec![(0, 'a'), (10, 'b'), (25, 'a')]));
+
+    let gappy: RangeMap<i32, char> = [(0..10, 'a'), (12..20, 'b')].into_iter().collect();
+    assert_eq!(gappy.to_boundaries(), None);
+    ```
+    **/
+    pub fn to_boundaries(&self) -> Option<Vec<(T, V)>> {
+        let entries = self.as_slice();
+        if !entries.windows(2).all(|w| w[0].0.end == w[1].0.start) {
+            return None;
+        }
+        // Touching entries have different values, so each starts a step
+        Some(entries.iter().map(|(r, v)| (r.start, v.clone())).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsorted_and_ties() {
+        let changes = [(20, 'c'), (0, 'a'), (10, 'x'), (10, 'b')];
+        assert_eq!(
+            RangeMap::from_boundaries(&(0..30), changes).into_vec(),
+            vec![(0..10, 'a'), (10..20, 'b'), (20..30, 'c')]
+        )
+    }
+
+    #[test]
+    fn before_domain() {
+        let changes = [(-10, 1), (-5, 2), (3, 3)];
+        assert_eq!(
+            RangeMap::from_boundaries(&(0..5), changes).into_vec(),
+            vec![(0..3, 2), (3..5, 3)]
+        );
+        assert!(RangeMap::from_boundaries(&(0..5), [(7, 1)]).is_empty())
+    }
+
+    #[test]
+    fn tie_back_to_previous_value() {
+        let changes = [(0, 'a'), (10, 'b'), (10, 'a')];
+        assert_eq!(
+            RangeMap::from_boundaries(&(0..20), changes).into_vec(),
+            vec![(0..20, 'a')]
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let changes = vec![(5, 'a'), (8, 'b'), (9, 'a')];
+        let map = RangeMap::from_boundaries(&(0..12), changes.clone());
+        assert_eq!(map.as_slice(), &[(5..8, 'a'), (8..9, 'b'), (9..12, 'a')]);
+        assert_eq!(map.to_boundaries(), Some(changes))
+    }
+}
@@ -0,0 +1,804 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Interchangeable storage for the runs of a range set, with a set generic over it.
+//!
+//! Each backend keeps its runs sorted, disjoint, and coalesced:
+//! * `Vec<Range<T>>`, the default, is compact and fast to scan;
+//! * `BTreeMap<T, T>` (start to end) keeps random inserts cheap in large sets;
+//! * [`SoaRangeSet`] keeps starts and ends apart, for cache-friendly lookups.
+
+use core::fmt;
+use core::ops::{Add, Range, Sub};
+use std::collections::BTreeMap;
+
+use crate::runs;
+use crate::soa::SoaRangeSet;
+
+/// Somewhere to keep the runs of a [`RangeSet`].
+pub trait RunStorage<T>: Default {
+    type Iter<'a>: Iterator<Item = Range<T>>
+    where
+        Self: 'a;
+
+    /// Add the non-empty `range`, merging it with any runs it overlaps or touches.
+    fn insert(&mut self, range: Range<T>);
+
+    /// Remove the non-empty `range`, splitting any run it falls inside.
+    fn remove(&mut self, range: &Range<T>);
+
+    /// The run containing `t`, if any.
+    fn containing(&self, t: T) -> Option<Range<T>>;
+
+    /// The runs overlapping the non-empty `range`, in order.
+    fn overlapping(&self, range: &Range<T>) -> Vec<Range<T>>
+    where
+        T: Ord,
+    {
+        self.iter()
+            .skip_while(|r| r.end <= range.start)
+            .take_while(|r| r.start < range.end)
+            .collect()
+    }
+
+    /// Keep only the parts of the runs also covered by `other`, which must be sorted and disjoint.
+    fn intersect_in_place(&mut self, other: impl IntoIterator<Item = Range<T>>)
+    where
+        T: Sized + Ord + Copy,
+    {
+        let mut kept: Vec<Range<T>> = self.iter().collect();
+        runs::intersect_in_place(&mut kept, other);
+        *self = Self::default();
+        for r in kept {
+            self.insert(r);
+        }
+    }
+
+    /// Remove everything covered by `other`, which must be sorted and disjoint.
+    fn subtract_in_place(&mut self, other: impl IntoIterator<Item = Range<T>>)
+    where
+        T: Sized + Ord + Copy,
+    {
+        for r in other.into_iter().filter(|r| r.start < r.end) {
+            self.remove(&r);
+        }
+    }
+
+    /// Number of runs.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The runs, in order.
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+impl<T> RunStorage<T> for Vec<Range<T>>
+where
+    T: Sized + Ord + Copy,
+{
+    type Iter<'a>
+        = core::iter::Cloned<core::slice::Iter<'a, Range<T>>>
+    where
+        T: 'a;
+
+    fn insert(&mut self, range: Range<T>) {
+        runs::insert(self, range);
+    }
+
+    fn remove(&mut self, range: &Range<T>) {
+        runs::remove(self, range);
+    }
+
+    fn containing(&self, t: T) -> Option<Range<T>> {
+        runs::containing(self, t).cloned()
+    }
+
+    fn overlapping(&self, range: &Range<T>) -> Vec<Range<T>> {
+        let i = self.partition_point(|r| r.end <= range.start);
+        let j = self.partition_point(|r| r.start < range.end);
+        self[i..j.max(i)].to_vec()
+    }
+
+    fn intersect_in_place(&mut self, other: impl IntoIterator<Item = Range<T>>) {
+        runs::intersect_in_place(self, other);
+    }
+
+    fn subtract_in_place(&mut self, other: impl IntoIterator<Item = Range<T>>) {
+        runs::subtract_in_place(self, other);
+    }
+
+    fn len(&self) -> usize {
+        <[Range<T>]>::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        <[Range<T>]>::iter(self).cloned()
+    }
+}
+
+impl<T> RunStorage<T> for BTreeMap<T, T>
+where
+    T: Sized + Ord + Copy,
+{
+    type Iter<'a>
+        = Box<dyn Iterator<Item = Range<T>> + 'a>
+    where
+        T: 'a;
+
+    fn insert(&mut self, range: Range<T>) {
+        let mut merged = range;
+        if let Some((&s, &e)) = self.range(..=merged.start).next_back() {
+            if e >= merged.start {
+                merged.start = s;
+                merged.end = merged.end.max(e);
+            }
+        }
+        let absorbed: Vec<T> = self
+            .range(merged.start..=merged.end)
+            .map(|(&s, _)| s)
+            .collect();
+        for s in absorbed {
+            let e = BTreeMap::remove(self, &s).expect("key just seen");
+            merged.end = merged.end.max(e);
+        }
+        BTreeMap::insert(self, merged.start, merged.end);
+    }
+
+    fn remove(&mut self, range: &Range<T>) {
+        // A run starting before `range` keeps its lower part
+        if let Some((&s, &e)) = self.range(..range.start).next_back() {
+            if e > range.start {
+                BTreeMap::insert(self, s, range.start);
+                if e > range.end {
+                    BTreeMap::insert(self, range.end, e);
+                }
+            }
+        }
+        let inside: Vec<T> = self.range(range.clone()).map(|(&s, _)| s).collect();
+        for s in inside {
+            let e = BTreeMap::remove(self, &s).expect("key just seen");
+            if e > range.end {
+                BTreeMap::insert(self, range.end, e);
+            }
+        }
+    }
+
+    fn containing(&self, t: T) -> Option<Range<T>> {
+        self.range(..=t)
+            .next_back()
+            .filter(|(_, &e)| e > t)
+            .map(|(&s, &e)| s..e)
+    }
+
+    fn overlapping(&self, range: &Range<T>) -> Vec<Range<T>> {
+        let before = self
+            .range(..range.start)
+            .next_back()
+            .filter(|(_, &e)| e > range.start);
+        before
+            .into_iter()
+            .chain(self.range(range.clone()))
+            .map(|(&s, &e)| s..e)
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        Box::new(BTreeMap::iter(self).map(|(&s, &e)| s..e))
+    }
+}
+
+impl<T> RunStorage<T> for SoaRangeSet<T>
+where
+    T: Sized + Ord + Copy,
+{
+    type Iter<'a>
+        = Box<dyn Iterator<Item = Range<T>> + 'a>
+    where
+        T: 'a;
+
+    fn insert(&mut self, range: Range<T>) {
+        SoaRangeSet::insert(self, range);
+    }
+
+    fn remove(&mut self, range: &Range<T>) {
+        SoaRangeSet::remove(self, range);
+    }
+
+    fn containing(&self, t: T) -> Option<Range<T>> {
+        SoaRangeSet::containing(self, t)
+    }
+
+    fn overlapping(&self, range: &Range<T>) -> Vec<Range<T>> {
+        SoaRangeSet::overlapping(self, range).collect()
+    }
+
+    fn len(&self) -> usize {
+        SoaRangeSet::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        Box::new(SoaRangeSet::iter(self))
+    }
+}
+
+/// How the runs of a [`RangeSet`] fail to be sorted, disjoint, and coalesced;
+/// see [`RangeSet::validate`]. `index` counts runs from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantError<T> {
+    /// A run is empty (or inverted).
+    Empty { index: usize, run: Range<T> },
+    /// A run starts before the previous one ends.
+    OutOfOrder {
+        index: usize,
+        previous: Range<T>,
+        run: Range<T>,
+    },
+    /// A run starts exactly where the previous one ends, so they should have merged.
+    Uncoalesced {
+        index: usize,
+        previous: Range<T>,
+        run: Range<T>,
+    },
+}
+
+impl<T: fmt::Debug> fmt::Display for InvariantError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::Empty { index, run } => write!(f, "run {index} ({run:?}) is empty"),
+            InvariantError::OutOfOrder {
+                index,
+                previous,
+                run,
+            } => write!(
+                f,
+                "run {index} ({run:?}) starts before the end of {previous:?}"
+            ),
+            InvariantError::Uncoalesced {
+                index,
+                previous,
+                run,
+            } => write!(
+                f,
+                "run {index} ({run:?}) touches {previous:?} without merging"
+            ),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for InvariantError<T> {}
+
+impl<T> InvariantError<T> {
+    /// The index of the offending run.
+    pub fn index(&self) -> usize {
+        match self {
+            InvariantError::Empty { index, .. }
+            | InvariantError::OutOfOrder { index, .. }
+            | InvariantError::Uncoalesced { index, .. } => *index,
+        }
+    }
+
+    /// What went wrong, by run index only, so that `T` needn't be `Debug`.
+    #[cfg(feature = "debug-invariants")]
+    fn summary(&self) -> String {
+        let index = self.index();
+        match self {
+            InvariantError::Empty { .. } => format!("run {index} is empty"),
+            InvariantError::OutOfOrder { .. } => {
+                format!("run {index} starts before the end of run {}", index - 1)
+            }
+            InvariantError::Uncoalesced { .. } => {
+                format!("run {index} touches run {} without merging", index - 1)
+            }
+        }
+    }
+}
+
+/// A change to the coverage of a [`RangeSet`], as reported by
+/// [`insert_with`](RangeSet::insert_with) and [`remove_with`](RangeSet::remove_with).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Change<T> {
+    /// `.0` is newly covered.
+    Added(Range<T>),
+    /// `.0` is no longer covered.
+    Removed(Range<T>),
+}
+
+/// Summary figures for a [`RangeSet`]; see [`RangeSet::stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats<T, D> {
+    /// Number of runs.
+    pub runs: usize,
+    /// Total length of the runs.
+    pub covered: D,
+    /// Number of gaps between runs (not counting the unbounded space either side).
+    pub gaps: usize,
+    /// Total length of the gaps.
+    pub gap_total: D,
+    /// The longest gap, earliest first on ties.
+    pub largest_gap: Option<Range<T>>,
+    /// The longest run, earliest first on ties.
+    pub largest_run: Option<Range<T>>,
+}
+
+/** A sorted, disjoint, coalesced set of ranges, kept in the storage `S`.
+
+Usage:
+```
+# use range_split::storage::RangeSet;
+# use std::collections::BTreeMap;
+let mut set: RangeSet<u32> = RangeSet::new();
+set.insert(0..10);
+set.insert(20..30);
+set.remove(&(5..25));
+assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..5, 25..30]);
+
+let mut tree: RangeSet<u32, BTreeMap<u32, u32>> = RangeSet::new();
+tree.insert(0..10);
+tree.insert(10..20);
+assert_eq!(tree.containing(15), Some(0..20));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeSet<T, S = Vec<Range<T>>> {
+    runs: S,
+    marker: core::marker::PhantomData<T>,
+}
+
+impl<T, S: Default> Default for RangeSet<T, S> {
+    fn default() -> Self {
+        Self {
+            runs: S::default(),
+            marker: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, S> RangeSet<T, S>
+where
+    T: Sized + Ord + Copy,
+    S: RunStorage<T>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Under the `debug-invariants` feature, called after every change to the runs:
+    /// panic naming `op` and the broken invariant.
+    #[cfg(feature = "debug-invariants")]
+    fn check(&self, op: &str) {
+        if let Err(e) = self.validate() {
+            panic!("RangeSet::{op} broke the run invariants: {}", e.summary());
+        }
+    }
+
+    /// Add `range`, merging it with any runs it overlaps or touches.
+    pub fn insert(&mut self, range: impl Into<Range<T>>) {
+        let range = range.into();
+        if range.start < range.end {
+            self.runs.insert(range);
+            #[cfg(feature = "debug-invariants")]
+            self.check("insert");
+        }
+    }
+
+    /// Remove `range`, splitting any run it falls inside.
+    pub fn remove(&mut self, range: &Range<T>) {
+        if range.start < range.end {
+            self.runs.remove(range);
+            #[cfg(feature = "debug-invariants")]
+            self.check("remove");
+        }
+    }
+
+    /** Check that the runs are non-empty, sorted, disjoint, and coalesced,
+    as a correct [`RunStorage`] keeps them.
+
+    Under the `debug-invariants` feature, this is done after every change, panicking on failure.
+    ```
+    # use range_split::storage::RangeSet;
+    let mut set: RangeSet<u32> = RangeSet::new();
+    set.insert(0..10);
+    assert_eq!(set.validate(), Ok(()));
+    ```
+    **/
+    pub fn validate(&self) -> Result<(), InvariantError<T>> {
+        let mut previous: Option<Range<T>> = None;
+        for (index, run) in self.iter().enumerate() {
+            if run.start >= run.end {
+                return Err(InvariantError::Empty { index, run });
+            }
+            if let Some(previous) = previous.replace(run.clone()) {
+                if run.start < previous.end {
+                    return Err(InvariantError::OutOfOrder {
+                        index,
+                        previous,
+                        run,
+                    });
+                }
+                if run.start == previous.end {
+                    return Err(InvariantError::Uncoalesced {
+                        index,
+                        previous,
+                        run,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /** [`insert`](Self::insert) `range`, then pass each newly covered piece to `observe`, in order.
+
+    Only what wasn't already covered is reported, however the runs merge.
+    ```
+    # use range_split::storage::{Change, RangeSet};
+    let mut set: RangeSet<u32> = RangeSet::new();
+    set.insert(10..20);
+    let (tx, rx) = std::sync::mpsc::channel();
+    set.insert_with(0..30, |c| tx.send(c).unwrap());
+    set.remove_with(&(15..25), |c| tx.send(c).unwrap());
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![
+        Change::Added(0..10),
+        Change::Added(20..30),
+        Change::Removed(15..25),
+    ]);
+    ```
+    **/
+    pub fn insert_with(&mut self, range: impl Into<Range<T>>, mut observe: impl FnMut(Change<T>)) {
+        let range = range.into();
+        if range.start >= range.end {
+            return;
+        }
+        let mut added = Vec::new();
+        let mut cursor = range.start;
+        for run in self.runs.overlapping(&range) {
+            if cursor < run.start {
+                added.push(cursor..run.start);
+            }
+            cursor = cursor.max(run.end);
+        }
+        if cursor < range.end {
+            added.push(cursor..range.end);
+        }
+        self.runs.insert(range);
+        #[cfg(feature = "debug-invariants")]
+        self.check("insert_with");
+        added.into_iter().map(Change::Added).for_each(&mut observe);
+    }
+
+    /// [`remove`](Self::remove) `range`, then pass each piece no longer covered to `observe`, in order.
+    pub fn remove_with(&mut self, range: &Range<T>, mut observe: impl FnMut(Change<T>)) {
+        if range.start >= range.end {
+            return;
+        }
+        let removed: Vec<Range<T>> = self
+            .runs
+            .overlapping(range)
+            .into_iter()
+            .map(|run| run.start.max(range.start)..run.end.min(range.end))
+            .collect();
+        self.runs.remove(range);
+        #[cfg(feature = "debug-invariants")]
+        self.check("remove_with");
+        removed
+            .into_iter()
+            .map(Change::Removed)
+            .for_each(&mut observe);
+    }
+
+    /** Keep only what's also in `other`, in place.
+
+    ```
+    # use range_split::storage::RangeSet;
+    # use std::collections::BTreeMap;
+    let mut set: RangeSet<u32> = [0..10, 20..30].into_iter().collect();
+    let window: RangeSet<u32, BTreeMap<u32, u32>> = [5..25].into_iter().collect();
+    set.intersect_assign(&window);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![5..10, 20..25]);
+    set.subtract_assign(&window);
+    assert!(set.is_empty());
+    ```
+    **/
+    pub fn intersect_assign<S2: RunStorage<T>>(&mut self, other: &RangeSet<T, S2>) {
+        self.runs.intersect_in_place(other.iter());
+        #[cfg(feature = "debug-invariants")]
+        self.check("intersect_assign");
+    }
+
+    /// Remove everything in `other`, in place.
+    pub fn subtract_assign<S2: RunStorage<T>>(&mut self, other: &RangeSet<T, S2>) {
+        self.runs.subtract_in_place(other.iter());
+        #[cfg(feature = "debug-invariants")]
+        self.check("subtract_assign");
+    }
+
+    pub fn contains(&self, t: T) -> bool {
+        self.runs.containing(t).is_some()
+    }
+
+    /// The run containing `t`, if any.
+    pub fn containing(&self, t: T) -> Option<Range<T>> {
+        self.runs.containing(t)
+    }
+
+    /// Number of disjoint runs.
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// The runs, in order.
+    pub fn iter(&self) -> S::Iter<'_> {
+        self.runs.iter()
+    }
+
+    /** Run and gap counts, lengths and extremes, in one pass over the runs.
+
+    ```
+    # use range_split::storage::RangeSet;
+    let mut set: RangeSet<u32> = RangeSet::new();
+    set.insert(0..10);
+    set.insert(15..17);
+    set.insert(30..40);
+    let stats = set.stats();
+    assert_eq!((stats.runs, stats.covered), (3, 22));
+    assert_eq!((stats.gaps, stats.gap_total), (2, 18));
+    assert_eq!(stats.largest_gap, Some(17..30));
+    assert_eq!(stats.largest_run, Some(0..10));
+    ```
+    **/
+    pub fn stats<D>(&self) -> Stats<T, D>
+    where
+        T: Sub<Output = D>,
+        D: Default + Add<Output = D> + Ord + Copy,
+    {
+        let mut stats = Stats {
+            runs: 0,
+            covered: D::default(),
+            gaps: 0,
+            gap_total: D::default(),
+            largest_gap: None,
+            largest_run: None,
+        };
+        let longer = |best: &Option<Range<T>>, r: &Range<T>| {
+            best.as_ref()
+                .is_none_or(|b| r.end - r.start > b.end - b.start)
+        };
+        let mut prev_end = None;
+        for run in self.iter() {
+            stats.runs += 1;
+            stats.covered = stats.covered + (run.end - run.start);
+            if longer(&stats.largest_run, &run) {
+                stats.largest_run = Some(run.clone());
+            }
+            if let Some(end) = prev_end {
+                let gap = end..run.start;
+                stats.gaps += 1;
+                stats.gap_total = stats.gap_total + (gap.end - gap.start);
+                if longer(&stats.largest_gap, &gap) {
+                    stats.largest_gap = Some(gap);
+                }
+            }
+            prev_end = Some(run.end);
+        }
+        stats
+    }
+
+    /// The underlying storage.
+    pub fn storage(&self) -> &S {
+        &self.runs
+    }
+}
+
+impl<T, S> FromIterator<Range<T>> for RangeSet<T, S>
+where
+    T: Sized + Ord + Copy,
+    S: RunStorage<T>,
+{
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T, S> Extend<Range<T>> for RangeSet<T, S>
+where
+    T: Sized + Ord + Copy,
+    S: RunStorage<T>,
+{
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise<S: RunStorage<u32>>() -> Vec<Vec<Range<u32>>> {
+        let mut set: RangeSet<u32, S> = RangeSet::new();
+        let mut history = vec![];
+        let mut x: u32 = 11;
+        for step in 0..1000 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let start = (x >> 16) % 300;
+            let range = start..start + (x >> 8) % 25;
+            if step % 3 == 0 {
+                set.remove(&range);
+            } else {
+                set.insert(range);
+            }
+            history.push(set.iter().collect());
+        }
+        history
+    }
+
+    #[test]
+    fn backends_agree() {
+        let vec = exercise::<Vec<Range<u32>>>();
+        assert_eq!(exercise::<BTreeMap<u32, u32>>(), vec);
+        assert_eq!(exercise::<SoaRangeSet<u32>>(), vec);
+    }
+
+    fn assign_ops<S: RunStorage<u32>>() {
+        let mut set: RangeSet<u32, S> = [0..10, 20..30, 40..50].into_iter().collect();
+        let other: RangeSet<u32> = [5..22, 25..26, 28..45].into_iter().collect();
+        set.intersect_assign(&other);
+        let kept = vec![5..10, 20..22, 25..26, 28..30, 40..45];
+        assert_eq!(set.iter().collect::<Vec<_>>(), kept);
+        set.subtract_assign(
+            &[0..6, 21..29, 44..100]
+                .into_iter()
+                .collect::<RangeSet<u32>>(),
+        );
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![6..10, 20..21, 29..30, 40..44]
+        );
+    }
+
+    #[test]
+    fn assign_ops_every_backend() {
+        assign_ops::<Vec<Range<u32>>>();
+        assign_ops::<BTreeMap<u32, u32>>();
+        assign_ops::<SoaRangeSet<u32>>();
+    }
+
+    #[test]
+    fn stats_empty_and_single() {
+        let mut set: RangeSet<i32, SoaRangeSet<i32>> = RangeSet::new();
+        let empty = set.stats::<i32>();
+        assert_eq!((empty.runs, empty.covered, empty.gaps), (0, 0, 0));
+        assert_eq!(empty.largest_run, None);
+
+        set.insert(-5..5);
+        let single = set.stats::<i32>();
+        assert_eq!((single.runs, single.covered, single.gaps), (1, 10, 0));
+        assert_eq!(single.largest_gap, None)
+    }
+
+    fn deltas<S: RunStorage<u32>>() {
+        let mut set: RangeSet<u32, S> = RangeSet::new();
+        let mut seen = vec![];
+        set.insert_with(5..10, |c| seen.push(c));
+        set.insert_with(10..12, |c| seen.push(c));
+        set.insert_with(0..12, |c| seen.push(c));
+        set.insert_with(3..8, |c| seen.push(c));
+        set.remove_with(&(20..30), |c| seen.push(c));
+        set.remove_with(&(2..4), |c| seen.push(c));
+        assert_eq!(
+            seen,
+            vec![
+                Change::Added(5..10),
+                Change::Added(10..12),
+                Change::Added(0..5),
+                Change::Removed(2..4),
+            ]
+        );
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..2, 4..12]);
+    }
+
+    #[test]
+    fn deltas_every_backend() {
+        deltas::<Vec<Range<u32>>>();
+        deltas::<BTreeMap<u32, u32>>();
+        deltas::<SoaRangeSet<u32>>();
+    }
+
+    /// Storage that forgets to merge.
+    #[derive(Default)]
+    struct Careless(Vec<Range<u32>>);
+
+    impl RunStorage<u32> for Careless {
+        type Iter<'a> = core::iter::Cloned<core::slice::Iter<'a, Range<u32>>>;
+
+        fn insert(&mut self, range: Range<u32>) {
+            self.0.push(range);
+            self.0.sort_by_key(|r| r.start);
+        }
+
+        fn remove(&mut self, range: &Range<u32>) {
+            runs::remove(&mut self.0, range);
+        }
+
+        fn containing(&self, t: u32) -> Option<Range<u32>> {
+            self.0.as_slice().iter().find(|r| r.contains(&t)).cloned()
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn iter(&self) -> Self::Iter<'_> {
+            self.0.as_slice().iter().cloned()
+        }
+    }
+
+    #[test]
+    fn validate_catches_careless_storage() {
+        let mut set: RangeSet<u32, Careless> = RangeSet::new();
+        set.runs.insert(0..10);
+        set.runs.insert(10..20);
+        assert_eq!(
+            set.validate(),
+            Err(InvariantError::Uncoalesced {
+                index: 1,
+                previous: 0..10,
+                run: 10..20
+            })
+        );
+        set.runs.insert(5..7);
+        assert!(matches!(
+            set.validate(),
+            Err(InvariantError::OutOfOrder { index: 1, .. })
+        ))
+    }
+
+    #[test]
+    #[cfg(feature = "debug-invariants")]
+    #[should_panic(
+        expected = "RangeSet::insert broke the run invariants: run 1 touches run 0 without merging"
+    )]
+    fn debug_invariants_panic() {
+        let mut set: RangeSet<u32, Careless> = RangeSet::new();
+        set.insert(0..10);
+        set.insert(10..20);
+    }
+
+    #[test]
+    fn no_debug_needed() {
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        struct Opaque(u32);
+        let mut set: RangeSet<Opaque> = RangeSet::new();
+        set.insert(Opaque(0)..Opaque(10));
+        set.remove(&(Opaque(3)..Opaque(5)));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.validate().map_err(|e| e.index()), Ok(()))
+    }
+
+    #[test]
+    fn btree_edges() {
+        let mut set: RangeSet<u32, BTreeMap<u32, u32>> = RangeSet::new();
+        set.insert(0..10);
+        set.remove(&(3..6));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..3, 6..10]);
+        set.insert(3..6);
+        assert_eq!(set.len(), 1);
+        assert!(!set.contains(10));
+        set.remove(&(0..10));
+        assert!(set.is_empty())
+    }
+}
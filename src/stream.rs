@@ -0,0 +1,185 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Splitting ranges against boundaries that arrive one at a time,
+//! such as the cut points of a content-defined chunker.
+
+use core::ops::Range;
+use std::collections::VecDeque;
+
+/** Splits query ranges at a stream of boundaries.
+
+Boundaries must arrive in non-decreasing order, and so must the starts of queries.
+Each call returns the pieces it has finished: a piece is finished once a boundary
+at or beyond its end has been seen, so any part of a query beyond the latest boundary
+is held back until more boundaries (or [`StreamSplitter::finish`]) arrive.
+Each piece comes with the index of the query it's from, counting queries from 0.
+
+Usage:
+```
+# use range_split::stream::StreamSplitter;
+let mut s = StreamSplitter::new();
+assert_eq!(s.boundary(10), vec![]);
+assert_eq!(s.query(5..25), vec![(0, 5..10)]);
+assert_eq!(s.boundary(20), vec![(0, 10..20)]);
+assert_eq!(s.boundary(30), vec![(0, 20..25)]);
+assert_eq!(s.query(28..40), vec![(1, 28..30)]);
+assert_eq!(s.finish(), vec![(1, 30..40)]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamSplitter<T> {
+    /// The boundaries above the latest query start.
+    boundaries: VecDeque<T>,
+    last_boundary: Option<T>,
+    last_start: Option<T>,
+    /// The number of queries made so far.
+    queries: usize,
+    /// The unfinished tails of queries, with their indices, in the order they were made.
+    pending: Vec<(usize, Range<T>)>,
+}
+
+impl<T> Default for StreamSplitter<T> {
+    fn default() -> Self {
+        Self {
+            boundaries: VecDeque::new(),
+            last_boundary: None,
+            last_start: None,
+            queries: 0,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<T> StreamSplitter<T>
+where
+    T: Sized + Ord + Copy,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a boundary, returning the pieces of earlier queries that it finishes.
+    /// Panics if `at` is less than the previous boundary.
+    pub fn boundary(&mut self, at: T) -> Vec<(usize, Range<T>)> {
+        let mut out = Vec::new();
+        if let Some(last) = self.last_boundary {
+            assert!(last <= at, "boundaries must not decrease");
+            if last == at {
+                return out;
+            }
+        }
+        self.last_boundary = Some(at);
+        if self.last_start.is_none_or(|s| s < at) {
+            self.boundaries.push_back(at);
+        }
+        self.pending.retain_mut(|(query, r)| {
+            if r.end <= at {
+                out.push((*query, r.clone()));
+                false
+            } else {
+                if r.start < at {
+                    out.push((*query, r.start..at));
+                    r.start = at;
+                }
+                true
+            }
+        });
+        out
+    }
+
+    /// Split `range` at the boundaries seen so far, returning the finished pieces.
+    /// Panics if `range` starts before the previous query.
+    pub fn query(&mut self, range: Range<T>) -> Vec<(usize, Range<T>)> {
+        if let Some(last) = self.last_start {
+            assert!(last <= range.start, "query starts must not decrease");
+        }
+        self.last_start = Some(range.start);
+        let query = self.queries;
+        self.queries += 1;
+        while self.boundaries.front().is_some_and(|&b| b <= range.start) {
+            self.boundaries.pop_front();
+        }
+
+        let mut out = Vec::new();
+        if range.start >= range.end {
+            return out;
+        }
+        let mut start = range.start;
+        for &b in &self.boundaries {
+            if b >= range.end {
+                out.push((query, start..range.end));
+                return out;
+            }
+            out.push((query, start..b));
+            start = b;
+        }
+        self.pending.push((query, start..range.end));
+        out
+    }
+
+    /// End the stream, returning the unfinished pieces, which no boundary divides.
+    pub fn finish(self) -> Vec<(usize, Range<T>)> {
+        self.pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_before_any_boundary() {
+        let mut s = StreamSplitter::new();
+        assert_eq!(s.query(0..10), vec![]);
+        assert_eq!(s.boundary(4), vec![(0, 0..4)]);
+        assert_eq!(s.boundary(6), vec![(0, 4..6)]);
+        assert_eq!(s.boundary(10), vec![(0, 6..10)]);
+        assert_eq!(s.finish(), vec![]);
+    }
+
+    #[test]
+    fn query_fully_behind_boundaries() {
+        let mut s = StreamSplitter::new();
+        for b in [3, 6, 9, 12] {
+            s.boundary(b);
+        }
+        assert_eq!(s.query(4..11), vec![(0, 4..6), (0, 6..9), (0, 9..11)]);
+        assert_eq!(s.query(12..13), vec![]);
+        assert_eq!(s.finish(), vec![(1, 12..13)]);
+    }
+
+    #[test]
+    fn overlapping_pending_queries() {
+        let mut s = StreamSplitter::new();
+        s.query(0..10);
+        s.query(2..5);
+        assert_eq!(s.boundary(3), vec![(0, 0..3), (1, 2..3)]);
+        assert_eq!(s.boundary(8), vec![(0, 3..8), (1, 3..5)]);
+    }
+
+    #[test]
+    fn empty_queries_count() {
+        let mut s = StreamSplitter::new();
+        let (p, q) = (4, 4);
+        assert_eq!(s.query(p..q), vec![]);
+        s.query(5..9);
+        assert_eq!(s.finish(), vec![(1, 5..9)]);
+    }
+
+    #[test]
+    fn repeated_boundary_ignored() {
+        let mut s = StreamSplitter::new();
+        s.query(0..10);
+        assert_eq!(s.boundary(5), vec![(0, 0..5)]);
+        assert_eq!(s.boundary(5), vec![]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn decreasing_boundaries() {
+        let mut s = StreamSplitter::new();
+        s.boundary(5);
+        s.boundary(4);
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Ranges carrying metadata, such as which source produced them,
+//! that is kept through splitting and set operations.
+
+use core::ops::Range;
+
+use crate::Split;
+
+/** A range with a tag. Splitting gives each piece a copy of the tag.
+
+Usage:
+```
+# use range_split::{tagged::Tagged, Split};
+let bytes = Tagged::new(0..100, "mirror-a");
+let (below, inter, above) = bytes.split(&Tagged::new(40..60, "ignored"));
+assert_eq!(below, Some(Tagged::new(0..40, "mirror-a")));
+assert_eq!(inter.unwrap().tag, "mirror-a");
+assert_eq!(above.unwrap().range, 60..100);
+
+assert_eq!(bytes.intersect(&(90..200)), Some(Tagged::new(90..100, "mirror-a")));
+assert_eq!(bytes.subtract(&(10..95)), (Some(Tagged::new(0..10, "mirror-a")), Some(Tagged::new(95..100, "mirror-a"))));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tagged<T, M> {
+    pub range: Range<T>,
+    pub tag: M,
+}
+
+impl<T, M> Tagged<T, M> {
+    pub fn new(range: Range<T>, tag: M) -> Self {
+        Self { range, tag }
+    }
+}
+
+impl<T, M> Tagged<T, M>
+where
+    T: Sized + Ord + Copy,
+    M: Clone,
+{
+    fn with(&self, range: Option<Range<T>>) -> Option<Self> {
+        range.map(|range| Self::new(range, self.tag.clone()))
+    }
+
+    /// The part of `self` within `other`, keeping the tag.
+    pub fn intersect(&self, other: &Range<T>) -> Option<Self> {
+        self.with(self.range.split(other).1)
+    }
+
+    /// The parts of `self` below and above `other`, keeping the tag.
+    pub fn subtract(&self, other: &Range<T>) -> (Option<Self>, Option<Self>) {
+        let (below, _, above) = self.range.split(other);
+        (self.with(below), self.with(above))
+    }
+}
+
+/// Splits by `other`'s range, ignoring its tag; every piece keeps `self`'s tag.
+impl<T, M> Split for Tagged<T, M>
+where
+    T: Sized + Ord + Copy,
+    M: Clone,
+{
+    fn split(&self, other: &Self) -> (Option<Self>, Option<Self>, Option<Self>) {
+        let (below, inter, above) = self.range.split(&other.range);
+        (self.with(below), self.with(inter), self.with(above))
+    }
+}
+
+/** Sorted, disjoint tagged ranges. Touching or overlapping ranges merge only if their tags are
+equal; otherwise the range inserted later takes over where they overlap.
+
+Usage:
+```
+# use range_split::tagged::{Tagged, TaggedSet};
+let mut set = TaggedSet::new();
+set.insert(Tagged::new(0..10, 'a'));
+set.insert(Tagged::new(10..20, 'a'));
+set.insert(Tagged::new(15..30, 'b'));
+assert_eq!(set.iter().collect::<Vec<_>>(), vec![
+    &Tagged::new(0..15, 'a'),
+    &Tagged::new(15..30, 'b'),
+]);
+assert_eq!(set.tag_at(12), Some(&'a'));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaggedSet<T, M> {
+    /// Non-empty, sorted, disjoint, and with no touching neighbours of equal tag.
+    runs: Vec<Tagged<T, M>>,
+}
+
+impl<T, M> Default for TaggedSet<T, M> {
+    fn default() -> Self {
+        Self { runs: Vec::new() }
+    }
+}
+
+impl<T, M> TaggedSet<T, M>
+where
+    T: Sized + Ord + Copy,
+    M: Clone + PartialEq,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `tagged`, replacing whatever it overlaps and merging with touching runs of the same tag.
+    pub fn insert(&mut self, tagged: Tagged<T, M>) {
+        let mut new = tagged;
+        if new.range.start >= new.range.end {
+            return;
+        }
+        let i = self.runs.partition_point(|r| r.range.end < new.range.start);
+        let j = self
+            .runs
+            .partition_point(|r| r.range.start <= new.range.end);
+        let (mut before, mut after) = (None, None);
+        for run in self.runs.drain(i..j) {
+            let (below, above) = run.subtract(&new.range);
+            // Any remainder touches `new`, as every run here touches or overlaps it
+            match below {
+                Some(b) if b.tag == new.tag => new.range.start = b.range.start,
+                b => before = before.or(b),
+            }
+            match above {
+                Some(a) if a.tag == new.tag => new.range.end = a.range.end,
+                a => after = after.or(a),
+            }
+        }
+        let pieces = before.into_iter().chain([new]).chain(after);
+        self.runs.splice(i..i, pieces);
+    }
+
+    /// Remove `range`, keeping the tags of what's left either side.
+    pub fn remove(&mut self, range: &Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        let i = self.runs.partition_point(|r| r.range.end <= range.start);
+        let j = self.runs.partition_point(|r| r.range.start < range.end);
+        let kept: Vec<Tagged<T, M>> = self.runs[i..j]
+            .iter()
+            .flat_map(|r| {
+                let (below, above) = r.subtract(range);
+                below.into_iter().chain(above)
+            })
+            .collect();
+        self.runs.splice(i..j, kept);
+    }
+
+    /// The tag of the run containing `t`, if any.
+    pub fn tag_at(&self, t: T) -> Option<&M> {
+        let i = self.runs.partition_point(|r| r.range.end <= t);
+        self.runs
+            .get(i)
+            .filter(|r| r.range.start <= t)
+            .map(|r| &r.tag)
+    }
+
+    /// The runs within `range`, clipped to it, in order.
+    pub fn query(&self, range: &Range<T>) -> Vec<Tagged<T, M>> {
+        let i = self.runs.partition_point(|r| r.range.end <= range.start);
+        self.runs[i..]
+            .iter()
+            .take_while(|r| r.range.start < range.end)
+            .filter_map(|r| r.intersect(range))
+            .collect()
+    }
+
+    /// Number of runs.
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// The runs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Tagged<T, M>> {
+        self.runs.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(set: &TaggedSet<u32, char>) -> Vec<(Range<u32>, char)> {
+        set.iter().map(|t| (t.range.clone(), t.tag)).collect()
+    }
+
+    #[test]
+    fn overwrite_middle() {
+        let mut set = TaggedSet::new();
+        set.insert(Tagged::new(0..30, 'a'));
+        set.insert(Tagged::new(10..20, 'b'));
+        assert_eq!(runs(&set), vec![(0..10, 'a'), (10..20, 'b'), (20..30, 'a')]);
+        // Back to 'a' in the middle merges all three
+        set.insert(Tagged::new(5..25, 'a'));
+        assert_eq!(runs(&set), vec![(0..30, 'a')])
+    }
+
+    #[test]
+    fn different_tags_stay_apart() {
+        let mut set = TaggedSet::new();
+        set.insert(Tagged::new(0..10, 'a'));
+        set.insert(Tagged::new(10..20, 'b'));
+        set.insert(Tagged::new(20..30, 'a'));
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.tag_at(10), Some(&'b'));
+        assert_eq!(set.tag_at(30), None)
+    }
+
+    #[test]
+    fn remove_and_query_keep_tags() {
+        let mut set = TaggedSet::new();
+        set.insert(Tagged::new(0..10, 'a'));
+        set.insert(Tagged::new(10..20, 'b'));
+        set.remove(&(5..12));
+        assert_eq!(runs(&set), vec![(0..5, 'a'), (12..20, 'b')]);
+        assert_eq!(
+            set.query(&(3..15)),
+            vec![Tagged::new(3..5, 'a'), Tagged::new(12..15, 'b')]
+        )
+    }
+}
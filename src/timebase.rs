@@ -0,0 +1,283 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Exact frame-boundary arithmetic for ranges of media timestamps.
+//!
+//! Timestamps are integer ticks of a [`Rational`] timebase (seconds per tick),
+//! and frames occur at a [`Rational`] frame rate (frames per second).
+//! All arithmetic is done in integers, so cut points land exactly on frames.
+
+use core::ops::Range;
+
+use crate::endpoint::RangeArithError;
+
+/// A positive rational number `num / den`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    num: u64,
+    den: u64,
+}
+
+impl Rational {
+    /// Panics if either part is zero.
+    pub const fn new(num: u64, den: u64) -> Self {
+        assert!(num != 0 && den != 0, "Rational parts must be non-zero");
+        Self { num, den }
+    }
+
+    /// The numerator, never zero.
+    pub const fn num(&self) -> u64 {
+        self.num
+    }
+
+    /// The denominator, never zero.
+    pub const fn den(&self) -> u64 {
+        self.den
+    }
+}
+
+/// Which way to move range endpoints that don't already sit on a frame boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Snap {
+    /// Shrink to the whole frames contained in the range.
+    Inward,
+    /// Grow to the whole frames touched by the range.
+    Outward,
+}
+
+/** Maps between timestamp ticks and frame numbers.
+
+Frame `n` covers the ticks from [`checked_frame_start(n)`](FrameGrid::checked_frame_start) up to
+(but not including) `checked_frame_start(n + 1)`. Each operation has a `checked_` form,
+which returns [`RangeArithError::Overflow`] when a tick or frame doesn't fit in a `u64`,
+and a `_panicking` form.
+
+```
+# use range_split::timebase::{FrameGrid, Rational, Snap};
+// NTSC video in milliseconds
+let grid = FrameGrid::new(Rational::new(30000, 1001), Rational::new(1, 1000));
+assert_eq!(grid.checked_frame_start(1), Ok(34));
+assert_eq!(grid.checked_snap(&(10..100), Snap::Inward), Ok(34..67));
+assert_eq!(grid.snap_panicking(&(35..68), Snap::Outward), 34..101);
+assert_eq!(grid.split_frames(&(30..70)).collect::<Vec<_>>(), vec![30..34, 34..67, 67..70]);
+```
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameGrid {
+    rate: Rational,
+    timebase: Rational,
+}
+
+impl FrameGrid {
+    /// `rate` is in frames per second, `timebase` in seconds per tick.
+    pub const fn new(rate: Rational, timebase: Rational) -> Self {
+        Self { rate, timebase }
+    }
+
+    /// The frame containing tick `t`. Panics on overflow; see [`checked_frame_at`](Self::checked_frame_at).
+    pub fn frame_at_panicking(&self, t: u64) -> u64 {
+        self.checked_frame_at(t).expect(OVERFLOW)
+    }
+
+    /// The frame containing tick `t`.
+    pub fn checked_frame_at(&self, t: u64) -> Result<u64, RangeArithError> {
+        let n = product(&[t, self.timebase.num, self.rate.num])?;
+        let d = self.timebase.den as u128 * self.rate.den as u128;
+        to_u64(n / d)
+    }
+
+    /// The first tick at or after the exact start of `frame`.
+    /// Panics on overflow; see [`checked_frame_start`](Self::checked_frame_start).
+    pub fn frame_start_panicking(&self, frame: u64) -> u64 {
+        self.checked_frame_start(frame).expect(OVERFLOW)
+    }
+
+    /// The first tick at or after the exact start of `frame`.
+    pub fn checked_frame_start(&self, frame: u64) -> Result<u64, RangeArithError> {
+        let n = product(&[frame, self.rate.den, self.timebase.den])?;
+        let d = self.rate.num as u128 * self.timebase.num as u128;
+        to_u64(n.div_ceil(d))
+    }
+
+    /// Whether tick `t` is the first tick of a frame.
+    pub fn is_boundary(&self, t: u64) -> bool {
+        self.checked_floor(t) == Ok(t)
+    }
+
+    /// The first frame boundary at or after `t`. Panics on overflow; see [`checked_ceil`](Self::checked_ceil).
+    pub fn ceil_panicking(&self, t: u64) -> u64 {
+        self.checked_ceil(t).expect(OVERFLOW)
+    }
+
+    /// The first frame boundary at or after `t`.
+    pub fn checked_ceil(&self, t: u64) -> Result<u64, RangeArithError> {
+        let f = self.checked_frame_at(t)?;
+        match self.checked_frame_start(f)? {
+            s if s == t => Ok(s),
+            _ => self.checked_frame_start(f.checked_add(1).ok_or(RangeArithError::Overflow)?),
+        }
+    }
+
+    /// The last frame boundary at or before `t`. Panics on overflow; see [`checked_floor`](Self::checked_floor).
+    pub fn floor_panicking(&self, t: u64) -> u64 {
+        self.checked_floor(t).expect(OVERFLOW)
+    }
+
+    /// The last frame boundary at or before `t`.
+    pub fn checked_floor(&self, t: u64) -> Result<u64, RangeArithError> {
+        self.checked_frame_start(self.checked_frame_at(t)?)
+    }
+
+    /// Move the endpoints of `range` onto frame boundaries.
+    /// An inward snap of a range containing no whole frame gives an empty range,
+    /// and either snap of an empty (or inverted) range gives an empty range.
+    /// Panics on overflow; see [`checked_snap`](Self::checked_snap).
+    pub fn snap_panicking(&self, range: &Range<u64>, snap: Snap) -> Range<u64> {
+        self.checked_snap(range, snap).expect(OVERFLOW)
+    }
+
+    /// Move the endpoints of `range` onto frame boundaries, as [`snap_panicking`](Self::snap_panicking) does.
+    pub fn checked_snap(
+        &self,
+        range: &Range<u64>,
+        snap: Snap,
+    ) -> Result<Range<u64>, RangeArithError> {
+        Ok(match snap {
+            Snap::Inward => {
+                let start = self.checked_ceil(range.start)?;
+                start..self.checked_floor(range.end)?.max(start)
+            }
+            Snap::Outward if range.start >= range.end => {
+                let start = self.checked_floor(range.start)?;
+                start..start
+            }
+            Snap::Outward => self.checked_floor(range.start)?..self.checked_ceil(range.end)?,
+        })
+    }
+
+    /// Cut `range` at every frame boundary strictly inside it.
+    /// Boundaries that don't fit in a `u64` (or can't be computed without overflow)
+    /// are past `range.end`, so the last piece runs to the end.
+    pub fn split_frames(&self, range: &Range<u64>) -> impl Iterator<Item = Range<u64>> + '_ {
+        let end = range.end;
+        let mut start = range.start;
+        core::iter::from_fn(move || {
+            if start >= end {
+                return None;
+            }
+            let next = self
+                .checked_frame_at(start)
+                .ok()
+                .and_then(|f| f.checked_add(1));
+            let cut = next
+                .and_then(|f| self.checked_frame_start(f).ok())
+                .map_or(end, |c| c.min(end));
+            let piece = start..cut;
+            start = cut;
+            Some(piece)
+        })
+    }
+}
+
+const OVERFLOW: &str = "timestamp overflows u64";
+
+fn to_u64(x: u128) -> Result<u64, RangeArithError> {
+    u64::try_from(x).map_err(|_| RangeArithError::Overflow)
+}
+
+fn product(factors: &[u64]) -> Result<u128, RangeArithError> {
+    factors.iter().try_fold(1u128, |p, &f| {
+        p.checked_mul(u128::from(f))
+            .ok_or(RangeArithError::Overflow)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NTSC: Rational = Rational::new(30000, 1001);
+    const MPEG: Rational = Rational::new(1, 90000);
+    const MILLIS: Rational = Rational::new(1, 1000);
+
+    #[test]
+    #[should_panic(expected = "must be non-zero")]
+    fn zero_rational() {
+        Rational::new(0, 1);
+    }
+
+    #[test]
+    fn exact_ticks_per_frame() {
+        let grid = FrameGrid::new(NTSC, MPEG);
+        assert_eq!(grid.frame_start_panicking(1), 3003);
+        assert_eq!(grid.frame_at_panicking(3002), 0);
+        assert_eq!(grid.frame_at_panicking(3003), 1);
+    }
+
+    #[test]
+    fn inexact_ticks_per_frame() {
+        let grid = FrameGrid::new(NTSC, MILLIS);
+        let starts: Vec<u64> = (0..4).map(|f| grid.frame_start_panicking(f)).collect();
+        assert_eq!(starts, vec![0, 34, 67, 101]);
+        assert_eq!(grid.frame_start_panicking(30), 1001);
+        assert!((0..2000).all(|f| grid.frame_at_panicking(grid.frame_start_panicking(f)) == f));
+    }
+
+    #[test]
+    fn checked_near_the_top() {
+        let grid = FrameGrid::new(Rational::new(u64::MAX, 1), Rational::new(u64::MAX, 1));
+        assert_eq!(
+            grid.checked_frame_at(u64::MAX),
+            Err(RangeArithError::Overflow)
+        );
+        let grid = FrameGrid::new(NTSC, MILLIS);
+        assert_eq!(grid.checked_ceil(u64::MAX), Err(RangeArithError::Overflow));
+        assert_eq!(grid.checked_snap(&(35..68), Snap::Outward), Ok(34..101));
+    }
+
+    #[test]
+    fn snap_on_boundaries() {
+        let grid = FrameGrid::new(NTSC, MILLIS);
+        assert_eq!(grid.snap_panicking(&(34..101), Snap::Inward), 34..101);
+        assert_eq!(grid.snap_panicking(&(34..101), Snap::Outward), 34..101);
+    }
+
+    #[test]
+    fn snap_outward() {
+        let grid = FrameGrid::new(NTSC, MILLIS);
+        assert_eq!(grid.snap_panicking(&(35..68), Snap::Outward), 34..101);
+    }
+
+    #[test]
+    fn snap_empty() {
+        let grid = FrameGrid::new(NTSC, MILLIS);
+        let (p, q) = (10, 5);
+        assert_eq!(grid.snap_panicking(&(35..35), Snap::Outward), 34..34);
+        assert_eq!(grid.snap_panicking(&(p..q), Snap::Outward), 0..0);
+        assert_eq!(grid.snap_panicking(&(35..35), Snap::Inward), 67..67);
+        assert_eq!(grid.snap_panicking(&(p..q), Snap::Inward), 34..34);
+    }
+
+    #[test]
+    fn snap_inward_within_one_frame() {
+        let grid = FrameGrid::new(NTSC, MILLIS);
+        assert_eq!(grid.snap_panicking(&(35..60), Snap::Inward), 67..67);
+    }
+
+    #[test]
+    fn split_on_boundaries() {
+        let grid = FrameGrid::new(NTSC, MPEG);
+        let pieces: Vec<_> = grid.split_frames(&(0..6006)).collect();
+        assert_eq!(pieces, vec![0..3003, 3003..6006]);
+    }
+
+    #[test]
+    fn split_near_the_top() {
+        let grid = FrameGrid::new(Rational::new(30, 1), MILLIS);
+        let pieces: Vec<_> = grid.split_frames(&(u64::MAX - 40..u64::MAX)).collect();
+        assert_eq!(pieces.first().map(|p| p.start), Some(u64::MAX - 40));
+        assert_eq!(pieces.last().map(|p| p.end), Some(u64::MAX));
+        assert!(pieces.windows(2).all(|w| w[0].end == w[1].start));
+        assert!(pieces[1..].iter().all(|p| grid.is_boundary(p.start)));
+    }
+}
@@ -0,0 +1,521 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Splitting char ranges at Unicode block boundaries.
+//!
+//! Block data is from the Unicode Character Database, `Blocks-14.0.0.txt`.
+//! The surrogate blocks are omitted because they contain no `char`s.
+
+use crate::{chars::CharRange, Split};
+
+/// The name of the Unicode block containing `c`, if any.
+pub fn block_of(c: char) -> Option<&'static str> {
+    let i = BLOCKS.partition_point(|b| b.1 < c);
+    BLOCKS.get(i).filter(|b| b.0 <= c).map(|(_, _, name)| *name)
+}
+
+/** Split `range` into the pieces lying in each Unicode block, in order.
+
+Pieces not assigned to any block have a name of `None`.
+
+```
+# use range_split::{chars::CharRange, unicode::split_blocks};
+let pieces = split_blocks(&CharRange::inclusive('z'..='\u{100}'));
+assert_eq!(pieces, vec![
+    (CharRange::inclusive('z'..='\u{7F}'), Some("Basic Latin")),
+    (CharRange::inclusive('\u{80}'..='\u{FF}'), Some("Latin-1 Supplement")),
+    (CharRange::inclusive('\u{100}'..='\u{100}'), Some("Latin Extended-A")),
+]);
+```
+**/
+pub fn split_blocks(range: &CharRange) -> Vec<(CharRange, Option<&'static str>)> {
+    let mut out = Vec::new();
+    let Some(first) = range.first() else {
+        return out;
+    };
+    let mut rest = Some(range.clone());
+    for &(start, end, name) in &BLOCKS[BLOCKS.partition_point(|b| b.1 < first)..] {
+        let Some(r) = rest else { break };
+        let (below, inter, above) = r.split(&CharRange::inclusive(start..=end));
+        out.extend(below.map(|b| (b, None)));
+        out.extend(inter.map(|i| (i, Some(name))));
+        rest = above;
+    }
+    out.extend(rest.map(|r| (r, None)));
+    out
+}
+
+/// `(first, last, name)` for each block, in order.
+const BLOCKS: &[(char, char, &str)] = &[
+    ('\u{0000}', '\u{007F}', "Basic Latin"),
+    ('\u{0080}', '\u{00FF}', "Latin-1 Supplement"),
+    ('\u{0100}', '\u{017F}', "Latin Extended-A"),
+    ('\u{0180}', '\u{024F}', "Latin Extended-B"),
+    ('\u{0250}', '\u{02AF}', "IPA Extensions"),
+    ('\u{02B0}', '\u{02FF}', "Spacing Modifier Letters"),
+    ('\u{0300}', '\u{036F}', "Combining Diacritical Marks"),
+    ('\u{0370}', '\u{03FF}', "Greek and Coptic"),
+    ('\u{0400}', '\u{04FF}', "Cyrillic"),
+    ('\u{0500}', '\u{052F}', "Cyrillic Supplement"),
+    ('\u{0530}', '\u{058F}', "Armenian"),
+    ('\u{0590}', '\u{05FF}', "Hebrew"),
+    ('\u{0600}', '\u{06FF}', "Arabic"),
+    ('\u{0700}', '\u{074F}', "Syriac"),
+    ('\u{0750}', '\u{077F}', "Arabic Supplement"),
+    ('\u{0780}', '\u{07BF}', "Thaana"),
+    ('\u{07C0}', '\u{07FF}', "NKo"),
+    ('\u{0800}', '\u{083F}', "Samaritan"),
+    ('\u{0840}', '\u{085F}', "Mandaic"),
+    ('\u{0860}', '\u{086F}', "Syriac Supplement"),
+    ('\u{0870}', '\u{089F}', "Arabic Extended-B"),
+    ('\u{08A0}', '\u{08FF}', "Arabic Extended-A"),
+    ('\u{0900}', '\u{097F}', "Devanagari"),
+    ('\u{0980}', '\u{09FF}', "Bengali"),
+    ('\u{0A00}', '\u{0A7F}', "Gurmukhi"),
+    ('\u{0A80}', '\u{0AFF}', "Gujarati"),
+    ('\u{0B00}', '\u{0B7F}', "Oriya"),
+    ('\u{0B80}', '\u{0BFF}', "Tamil"),
+    ('\u{0C00}', '\u{0C7F}', "Telugu"),
+    ('\u{0C80}', '\u{0CFF}', "Kannada"),
+    ('\u{0D00}', '\u{0D7F}', "Malayalam"),
+    ('\u{0D80}', '\u{0DFF}', "Sinhala"),
+    ('\u{0E00}', '\u{0E7F}', "Thai"),
+    ('\u{0E80}', '\u{0EFF}', "Lao"),
+    ('\u{0F00}', '\u{0FFF}', "Tibetan"),
+    ('\u{1000}', '\u{109F}', "Myanmar"),
+    ('\u{10A0}', '\u{10FF}', "Georgian"),
+    ('\u{1100}', '\u{11FF}', "Hangul Jamo"),
+    ('\u{1200}', '\u{137F}', "Ethiopic"),
+    ('\u{1380}', '\u{139F}', "Ethiopic Supplement"),
+    ('\u{13A0}', '\u{13FF}', "Cherokee"),
+    (
+        '\u{1400}',
+        '\u{167F}',
+        "Unified Canadian Aboriginal Syllabics",
+    ),
+    ('\u{1680}', '\u{169F}', "Ogham"),
+    ('\u{16A0}', '\u{16FF}', "Runic"),
+    ('\u{1700}', '\u{171F}', "Tagalog"),
+    ('\u{1720}', '\u{173F}', "Hanunoo"),
+    ('\u{1740}', '\u{175F}', "Buhid"),
+    ('\u{1760}', '\u{177F}', "Tagbanwa"),
+    ('\u{1780}', '\u{17FF}', "Khmer"),
+    ('\u{1800}', '\u{18AF}', "Mongolian"),
+    (
+        '\u{18B0}',
+        '\u{18FF}',
+        "Unified Canadian Aboriginal Syllabics Extended",
+    ),
+    ('\u{1900}', '\u{194F}', "Limbu"),
+    ('\u{1950}', '\u{197F}', "Tai Le"),
+    ('\u{1980}', '\u{19DF}', "New Tai Lue"),
+    ('\u{19E0}', '\u{19FF}', "Khmer Symbols"),
+    ('\u{1A00}', '\u{1A1F}', "Buginese"),
+    ('\u{1A20}', '\u{1AAF}', "Tai Tham"),
+    (
+        '\u{1AB0}',
+        '\u{1AFF}',
+        "Combining Diacritical Marks Extended",
+    ),
+    ('\u{1B00}', '\u{1B7F}', "Balinese"),
+    ('\u{1B80}', '\u{1BBF}', "Sundanese"),
+    ('\u{1BC0}', '\u{1BFF}', "Batak"),
+    ('\u{1C00}', '\u{1C4F}', "Lepcha"),
+    ('\u{1C50}', '\u{1C7F}', "Ol Chiki"),
+    ('\u{1C80}', '\u{1C8F}', "Cyrillic Extended-C"),
+    ('\u{1C90}', '\u{1CBF}', "Georgian Extended"),
+    ('\u{1CC0}', '\u{1CCF}', "Sundanese Supplement"),
+    ('\u{1CD0}', '\u{1CFF}', "Vedic Extensions"),
+    ('\u{1D00}', '\u{1D7F}', "Phonetic Extensions"),
+    ('\u{1D80}', '\u{1DBF}', "Phonetic Extensions Supplement"),
+    (
+        '\u{1DC0}',
+        '\u{1DFF}',
+        "Combining Diacritical Marks Supplement",
+    ),
+    ('\u{1E00}', '\u{1EFF}', "Latin Extended Additional"),
+    ('\u{1F00}', '\u{1FFF}', "Greek Extended"),
+    ('\u{2000}', '\u{206F}', "General Punctuation"),
+    ('\u{2070}', '\u{209F}', "Superscripts and Subscripts"),
+    ('\u{20A0}', '\u{20CF}', "Currency Symbols"),
+    (
+        '\u{20D0}',
+        '\u{20FF}',
+        "Combining Diacritical Marks for Symbols",
+    ),
+    ('\u{2100}', '\u{214F}', "Letterlike Symbols"),
+    ('\u{2150}', '\u{218F}', "Number Forms"),
+    ('\u{2190}', '\u{21FF}', "Arrows"),
+    ('\u{2200}', '\u{22FF}', "Mathematical Operators"),
+    ('\u{2300}', '\u{23FF}', "Miscellaneous Technical"),
+    ('\u{2400}', '\u{243F}', "Control Pictures"),
+    ('\u{2440}', '\u{245F}', "Optical Character Recognition"),
+    ('\u{2460}', '\u{24FF}', "Enclosed Alphanumerics"),
+    ('\u{2500}', '\u{257F}', "Box Drawing"),
+    ('\u{2580}', '\u{259F}', "Block Elements"),
+    ('\u{25A0}', '\u{25FF}', "Geometric Shapes"),
+    ('\u{2600}', '\u{26FF}', "Miscellaneous Symbols"),
+    ('\u{2700}', '\u{27BF}', "Dingbats"),
+    (
+        '\u{27C0}',
+        '\u{27EF}',
+        "Miscellaneous Mathematical Symbols-A",
+    ),
+    ('\u{27F0}', '\u{27FF}', "Supplemental Arrows-A"),
+    ('\u{2800}', '\u{28FF}', "Braille Patterns"),
+    ('\u{2900}', '\u{297F}', "Supplemental Arrows-B"),
+    (
+        '\u{2980}',
+        '\u{29FF}',
+        "Miscellaneous Mathematical Symbols-B",
+    ),
+    (
+        '\u{2A00}',
+        '\u{2AFF}',
+        "Supplemental Mathematical Operators",
+    ),
+    ('\u{2B00}', '\u{2BFF}', "Miscellaneous Symbols and Arrows"),
+    ('\u{2C00}', '\u{2C5F}', "Glagolitic"),
+    ('\u{2C60}', '\u{2C7F}', "Latin Extended-C"),
+    ('\u{2C80}', '\u{2CFF}', "Coptic"),
+    ('\u{2D00}', '\u{2D2F}', "Georgian Supplement"),
+    ('\u{2D30}', '\u{2D7F}', "Tifinagh"),
+    ('\u{2D80}', '\u{2DDF}', "Ethiopic Extended"),
+    ('\u{2DE0}', '\u{2DFF}', "Cyrillic Extended-A"),
+    ('\u{2E00}', '\u{2E7F}', "Supplemental Punctuation"),
+    ('\u{2E80}', '\u{2EFF}', "CJK Radicals Supplement"),
+    ('\u{2F00}', '\u{2FDF}', "Kangxi Radicals"),
+    ('\u{2FF0}', '\u{2FFF}', "Ideographic Description Characters"),
+    ('\u{3000}', '\u{303F}', "CJK Symbols and Punctuation"),
+    ('\u{3040}', '\u{309F}', "Hiragana"),
+    ('\u{30A0}', '\u{30FF}', "Katakana"),
+    ('\u{3100}', '\u{312F}', "Bopomofo"),
+    ('\u{3130}', '\u{318F}', "Hangul Compatibility Jamo"),
+    ('\u{3190}', '\u{319F}', "Kanbun"),
+    ('\u{31A0}', '\u{31BF}', "Bopomofo Extended"),
+    ('\u{31C0}', '\u{31EF}', "CJK Strokes"),
+    ('\u{31F0}', '\u{31FF}', "Katakana Phonetic Extensions"),
+    ('\u{3200}', '\u{32FF}', "Enclosed CJK Letters and Months"),
+    ('\u{3300}', '\u{33FF}', "CJK Compatibility"),
+    ('\u{3400}', '\u{4DBF}', "CJK Unified Ideographs Extension A"),
+    ('\u{4DC0}', '\u{4DFF}', "Yijing Hexagram Symbols"),
+    ('\u{4E00}', '\u{9FFF}', "CJK Unified Ideographs"),
+    ('\u{A000}', '\u{A48F}', "Yi Syllables"),
+    ('\u{A490}', '\u{A4CF}', "Yi Radicals"),
+    ('\u{A4D0}', '\u{A4FF}', "Lisu"),
+    ('\u{A500}', '\u{A63F}', "Vai"),
+    ('\u{A640}', '\u{A69F}', "Cyrillic Extended-B"),
+    ('\u{A6A0}', '\u{A6FF}', "Bamum"),
+    ('\u{A700}', '\u{A71F}', "Modifier Tone Letters"),
+    ('\u{A720}', '\u{A7FF}', "Latin Extended-D"),
+    ('\u{A800}', '\u{A82F}', "Syloti Nagri"),
+    ('\u{A830}', '\u{A83F}', "Common Indic Number Forms"),
+    ('\u{A840}', '\u{A87F}', "Phags-pa"),
+    ('\u{A880}', '\u{A8DF}', "Saurashtra"),
+    ('\u{A8E0}', '\u{A8FF}', "Devanagari Extended"),
+    ('\u{A900}', '\u{A92F}', "Kayah Li"),
+    ('\u{A930}', '\u{A95F}', "Rejang"),
+    ('\u{A960}', '\u{A97F}', "Hangul Jamo Extended-A"),
+    ('\u{A980}', '\u{A9DF}', "Javanese"),
+    ('\u{A9E0}', '\u{A9FF}', "Myanmar Extended-B"),
+    ('\u{AA00}', '\u{AA5F}', "Cham"),
+    ('\u{AA60}', '\u{AA7F}', "Myanmar Extended-A"),
+    ('\u{AA80}', '\u{AADF}', "Tai Viet"),
+    ('\u{AAE0}', '\u{AAFF}', "Meetei Mayek Extensions"),
+    ('\u{AB00}', '\u{AB2F}', "Ethiopic Extended-A"),
+    ('\u{AB30}', '\u{AB6F}', "Latin Extended-E"),
+    ('\u{AB70}', '\u{ABBF}', "Cherokee Supplement"),
+    ('\u{ABC0}', '\u{ABFF}', "Meetei Mayek"),
+    ('\u{AC00}', '\u{D7AF}', "Hangul Syllables"),
+    ('\u{D7B0}', '\u{D7FF}', "Hangul Jamo Extended-B"),
+    ('\u{E000}', '\u{F8FF}', "Private Use Area"),
+    ('\u{F900}', '\u{FAFF}', "CJK Compatibility Ideographs"),
+    ('\u{FB00}', '\u{FB4F}', "Alphabetic Presentation Forms"),
+    ('\u{FB50}', '\u{FDFF}', "Arabic Presentation Forms-A"),
+    ('\u{FE00}', '\u{FE0F}', "Variation Selectors"),
+    ('\u{FE10}', '\u{FE1F}', "Vertical Forms"),
+    ('\u{FE20}', '\u{FE2F}', "Combining Half Marks"),
+    ('\u{FE30}', '\u{FE4F}', "CJK Compatibility Forms"),
+    ('\u{FE50}', '\u{FE6F}', "Small Form Variants"),
+    ('\u{FE70}', '\u{FEFF}', "Arabic Presentation Forms-B"),
+    ('\u{FF00}', '\u{FFEF}', "Halfwidth and Fullwidth Forms"),
+    ('\u{FFF0}', '\u{FFFF}', "Specials"),
+    ('\u{10000}', '\u{1007F}', "Linear B Syllabary"),
+    ('\u{10080}', '\u{100FF}', "Linear B Ideograms"),
+    ('\u{10100}', '\u{1013F}', "Aegean Numbers"),
+    ('\u{10140}', '\u{1018F}', "Ancient Greek Numbers"),
+    ('\u{10190}', '\u{101CF}', "Ancient Symbols"),
+    ('\u{101D0}', '\u{101FF}', "Phaistos Disc"),
+    ('\u{10280}', '\u{1029F}', "Lycian"),
+    ('\u{102A0}', '\u{102DF}', "Carian"),
+    ('\u{102E0}', '\u{102FF}', "Coptic Epact Numbers"),
+    ('\u{10300}', '\u{1032F}', "Old Italic"),
+    ('\u{10330}', '\u{1034F}', "Gothic"),
+    ('\u{10350}', '\u{1037F}', "Old Permic"),
+    ('\u{10380}', '\u{1039F}', "Ugaritic"),
+    ('\u{103A0}', '\u{103DF}', "Old Persian"),
+    ('\u{10400}', '\u{1044F}', "Deseret"),
+    ('\u{10450}', '\u{1047F}', "Shavian"),
+    ('\u{10480}', '\u{104AF}', "Osmanya"),
+    ('\u{104B0}', '\u{104FF}', "Osage"),
+    ('\u{10500}', '\u{1052F}', "Elbasan"),
+    ('\u{10530}', '\u{1056F}', "Caucasian Albanian"),
+    ('\u{10570}', '\u{105BF}', "Vithkuqi"),
+    ('\u{10600}', '\u{1077F}', "Linear A"),
+    ('\u{10780}', '\u{107BF}', "Latin Extended-F"),
+    ('\u{10800}', '\u{1083F}', "Cypriot Syllabary"),
+    ('\u{10840}', '\u{1085F}', "Imperial Aramaic"),
+    ('\u{10860}', '\u{1087F}', "Palmyrene"),
+    ('\u{10880}', '\u{108AF}', "Nabataean"),
+    ('\u{108E0}', '\u{108FF}', "Hatran"),
+    ('\u{10900}', '\u{1091F}', "Phoenician"),
+    ('\u{10920}', '\u{1093F}', "Lydian"),
+    ('\u{10980}', '\u{1099F}', "Meroitic Hieroglyphs"),
+    ('\u{109A0}', '\u{109FF}', "Meroitic Cursive"),
+    ('\u{10A00}', '\u{10A5F}', "Kharoshthi"),
+    ('\u{10A60}', '\u{10A7F}', "Old South Arabian"),
+    ('\u{10A80}', '\u{10A9F}', "Old North Arabian"),
+    ('\u{10AC0}', '\u{10AFF}', "Manichaean"),
+    ('\u{10B00}', '\u{10B3F}', "Avestan"),
+    ('\u{10B40}', '\u{10B5F}', "Inscriptional Parthian"),
+    ('\u{10B60}', '\u{10B7F}', "Inscriptional Pahlavi"),
+    ('\u{10B80}', '\u{10BAF}', "Psalter Pahlavi"),
+    ('\u{10C00}', '\u{10C4F}', "Old Turkic"),
+    ('\u{10C80}', '\u{10CFF}', "Old Hungarian"),
+    ('\u{10D00}', '\u{10D3F}', "Hanifi Rohingya"),
+    ('\u{10E60}', '\u{10E7F}', "Rumi Numeral Symbols"),
+    ('\u{10E80}', '\u{10EBF}', "Yezidi"),
+    ('\u{10F00}', '\u{10F2F}', "Old Sogdian"),
+    ('\u{10F30}', '\u{10F6F}', "Sogdian"),
+    ('\u{10F70}', '\u{10FAF}', "Old Uyghur"),
+    ('\u{10FB0}', '\u{10FDF}', "Chorasmian"),
+    ('\u{10FE0}', '\u{10FFF}', "Elymaic"),
+    ('\u{11000}', '\u{1107F}', "Brahmi"),
+    ('\u{11080}', '\u{110CF}', "Kaithi"),
+    ('\u{110D0}', '\u{110FF}', "Sora Sompeng"),
+    ('\u{11100}', '\u{1114F}', "Chakma"),
+    ('\u{11150}', '\u{1117F}', "Mahajani"),
+    ('\u{11180}', '\u{111DF}', "Sharada"),
+    ('\u{111E0}', '\u{111FF}', "Sinhala Archaic Numbers"),
+    ('\u{11200}', '\u{1124F}', "Khojki"),
+    ('\u{11280}', '\u{112AF}', "Multani"),
+    ('\u{112B0}', '\u{112FF}', "Khudawadi"),
+    ('\u{11300}', '\u{1137F}', "Grantha"),
+    ('\u{11400}', '\u{1147F}', "Newa"),
+    ('\u{11480}', '\u{114DF}', "Tirhuta"),
+    ('\u{11580}', '\u{115FF}', "Siddham"),
+    ('\u{11600}', '\u{1165F}', "Modi"),
+    ('\u{11660}', '\u{1167F}', "Mongolian Supplement"),
+    ('\u{11680}', '\u{116CF}', "Takri"),
+    ('\u{11700}', '\u{1174F}', "Ahom"),
+    ('\u{11800}', '\u{1184F}', "Dogra"),
+    ('\u{118A0}', '\u{118FF}', "Warang Citi"),
+    ('\u{11900}', '\u{1195F}', "Dives Akuru"),
+    ('\u{119A0}', '\u{119FF}', "Nandinagari"),
+    ('\u{11A00}', '\u{11A4F}', "Zanabazar Square"),
+    ('\u{11A50}', '\u{11AAF}', "Soyombo"),
+    (
+        '\u{11AB0}',
+        '\u{11ABF}',
+        "Unified Canadian Aboriginal Syllabics Extended-A",
+    ),
+    ('\u{11AC0}', '\u{11AFF}', "Pau Cin Hau"),
+    ('\u{11C00}', '\u{11C6F}', "Bhaiksuki"),
+    ('\u{11C70}', '\u{11CBF}', "Marchen"),
+    ('\u{11D00}', '\u{11D5F}', "Masaram Gondi"),
+    ('\u{11D60}', '\u{11DAF}', "Gunjala Gondi"),
+    ('\u{11EE0}', '\u{11EFF}', "Makasar"),
+    ('\u{11FB0}', '\u{11FBF}', "Lisu Supplement"),
+    ('\u{11FC0}', '\u{11FFF}', "Tamil Supplement"),
+    ('\u{12000}', '\u{123FF}', "Cuneiform"),
+    (
+        '\u{12400}',
+        '\u{1247F}',
+        "Cuneiform Numbers and Punctuation",
+    ),
+    ('\u{12480}', '\u{1254F}', "Early Dynastic Cuneiform"),
+    ('\u{12F90}', '\u{12FFF}', "Cypro-Minoan"),
+    ('\u{13000}', '\u{1342F}', "Egyptian Hieroglyphs"),
+    (
+        '\u{13430}',
+        '\u{1343F}',
+        "Egyptian Hieroglyph Format Controls",
+    ),
+    ('\u{14400}', '\u{1467F}', "Anatolian Hieroglyphs"),
+    ('\u{16800}', '\u{16A3F}', "Bamum Supplement"),
+    ('\u{16A40}', '\u{16A6F}', "Mro"),
+    ('\u{16A70}', '\u{16ACF}', "Tangsa"),
+    ('\u{16AD0}', '\u{16AFF}', "Bassa Vah"),
+    ('\u{16B00}', '\u{16B8F}', "Pahawh Hmong"),
+    ('\u{16E40}', '\u{16E9F}', "Medefaidrin"),
+    ('\u{16F00}', '\u{16F9F}', "Miao"),
+    (
+        '\u{16FE0}',
+        '\u{16FFF}',
+        "Ideographic Symbols and Punctuation",
+    ),
+    ('\u{17000}', '\u{187FF}', "Tangut"),
+    ('\u{18800}', '\u{18AFF}', "Tangut Components"),
+    ('\u{18B00}', '\u{18CFF}', "Khitan Small Script"),
+    ('\u{18D00}', '\u{18D7F}', "Tangut Supplement"),
+    ('\u{1AFF0}', '\u{1AFFF}', "Kana Extended-B"),
+    ('\u{1B000}', '\u{1B0FF}', "Kana Supplement"),
+    ('\u{1B100}', '\u{1B12F}', "Kana Extended-A"),
+    ('\u{1B130}', '\u{1B16F}', "Small Kana Extension"),
+    ('\u{1B170}', '\u{1B2FF}', "Nushu"),
+    ('\u{1BC00}', '\u{1BC9F}', "Duployan"),
+    ('\u{1BCA0}', '\u{1BCAF}', "Shorthand Format Controls"),
+    ('\u{1CF00}', '\u{1CFCF}', "Znamenny Musical Notation"),
+    ('\u{1D000}', '\u{1D0FF}', "Byzantine Musical Symbols"),
+    ('\u{1D100}', '\u{1D1FF}', "Musical Symbols"),
+    ('\u{1D200}', '\u{1D24F}', "Ancient Greek Musical Notation"),
+    ('\u{1D2E0}', '\u{1D2FF}', "Mayan Numerals"),
+    ('\u{1D300}', '\u{1D35F}', "Tai Xuan Jing Symbols"),
+    ('\u{1D360}', '\u{1D37F}', "Counting Rod Numerals"),
+    (
+        '\u{1D400}',
+        '\u{1D7FF}',
+        "Mathematical Alphanumeric Symbols",
+    ),
+    ('\u{1D800}', '\u{1DAAF}', "Sutton SignWriting"),
+    ('\u{1DF00}', '\u{1DFFF}', "Latin Extended-G"),
+    ('\u{1E000}', '\u{1E02F}', "Glagolitic Supplement"),
+    ('\u{1E100}', '\u{1E14F}', "Nyiakeng Puachue Hmong"),
+    ('\u{1E290}', '\u{1E2BF}', "Toto"),
+    ('\u{1E2C0}', '\u{1E2FF}', "Wancho"),
+    ('\u{1E7E0}', '\u{1E7FF}', "Ethiopic Extended-B"),
+    ('\u{1E800}', '\u{1E8DF}', "Mende Kikakui"),
+    ('\u{1E900}', '\u{1E95F}', "Adlam"),
+    ('\u{1EC70}', '\u{1ECBF}', "Indic Siyaq Numbers"),
+    ('\u{1ED00}', '\u{1ED4F}', "Ottoman Siyaq Numbers"),
+    (
+        '\u{1EE00}',
+        '\u{1EEFF}',
+        "Arabic Mathematical Alphabetic Symbols",
+    ),
+    ('\u{1F000}', '\u{1F02F}', "Mahjong Tiles"),
+    ('\u{1F030}', '\u{1F09F}', "Domino Tiles"),
+    ('\u{1F0A0}', '\u{1F0FF}', "Playing Cards"),
+    ('\u{1F100}', '\u{1F1FF}', "Enclosed Alphanumeric Supplement"),
+    ('\u{1F200}', '\u{1F2FF}', "Enclosed Ideographic Supplement"),
+    (
+        '\u{1F300}',
+        '\u{1F5FF}',
+        "Miscellaneous Symbols and Pictographs",
+    ),
+    ('\u{1F600}', '\u{1F64F}', "Emoticons"),
+    ('\u{1F650}', '\u{1F67F}', "Ornamental Dingbats"),
+    ('\u{1F680}', '\u{1F6FF}', "Transport and Map Symbols"),
+    ('\u{1F700}', '\u{1F77F}', "Alchemical Symbols"),
+    ('\u{1F780}', '\u{1F7FF}', "Geometric Shapes Extended"),
+    ('\u{1F800}', '\u{1F8FF}', "Supplemental Arrows-C"),
+    (
+        '\u{1F900}',
+        '\u{1F9FF}',
+        "Supplemental Symbols and Pictographs",
+    ),
+    ('\u{1FA00}', '\u{1FA6F}', "Chess Symbols"),
+    (
+        '\u{1FA70}',
+        '\u{1FAFF}',
+        "Symbols and Pictographs Extended-A",
+    ),
+    ('\u{1FB00}', '\u{1FBFF}', "Symbols for Legacy Computing"),
+    (
+        '\u{20000}',
+        '\u{2A6DF}',
+        "CJK Unified Ideographs Extension B",
+    ),
+    (
+        '\u{2A700}',
+        '\u{2B73F}',
+        "CJK Unified Ideographs Extension C",
+    ),
+    (
+        '\u{2B740}',
+        '\u{2B81F}',
+        "CJK Unified Ideographs Extension D",
+    ),
+    (
+        '\u{2B820}',
+        '\u{2CEAF}',
+        "CJK Unified Ideographs Extension E",
+    ),
+    (
+        '\u{2CEB0}',
+        '\u{2EBEF}',
+        "CJK Unified Ideographs Extension F",
+    ),
+    (
+        '\u{2F800}',
+        '\u{2FA1F}',
+        "CJK Compatibility Ideographs Supplement",
+    ),
+    (
+        '\u{30000}',
+        '\u{3134F}',
+        "CJK Unified Ideographs Extension G",
+    ),
+    ('\u{E0000}', '\u{E007F}', "Tags"),
+    ('\u{E0100}', '\u{E01EF}', "Variation Selectors Supplement"),
+    ('\u{F0000}', '\u{FFFFF}', "Supplementary Private Use Area-A"),
+    (
+        '\u{100000}',
+        '\u{10FFFF}',
+        "Supplementary Private Use Area-B",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_sorted() {
+        assert!(BLOCKS.windows(2).all(|w| w[0].1 < w[1].0))
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(block_of('A'), Some("Basic Latin"));
+        assert_eq!(block_of('\u{1F600}'), Some("Emoticons"));
+        assert_eq!(block_of('\u{2FE0}'), None);
+    }
+
+    #[test]
+    fn unassigned_gap() {
+        let pieces = split_blocks(&CharRange::inclusive('\u{2FD0}'..='\u{2FF0}'));
+        assert_eq!(
+            pieces,
+            vec![
+                (
+                    CharRange::inclusive('\u{2FD0}'..='\u{2FDF}'),
+                    Some("Kangxi Radicals")
+                ),
+                (CharRange::inclusive('\u{2FE0}'..='\u{2FEF}'), None),
+                (
+                    CharRange::inclusive('\u{2FF0}'..='\u{2FF0}'),
+                    Some("Ideographic Description Characters")
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn across_surrogates() {
+        let pieces = split_blocks(&CharRange::inclusive('\u{D7FF}'..='\u{E000}'));
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[1].1, Some("Private Use Area"));
+    }
+
+    #[test]
+    fn everything() {
+        let pieces = split_blocks(&CharRange::ALL);
+        let total: u32 = pieces.iter().map(|(r, _)| r.len()).sum();
+        assert_eq!(total, CharRange::ALL.len());
+        assert_eq!(
+            pieces.iter().filter(|p| p.1.is_some()).count(),
+            BLOCKS.len()
+        );
+    }
+}
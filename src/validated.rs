@@ -0,0 +1,151 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Ranges checked once, at construction, to be well-formed.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::Range;
+
+/// Why a range was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeError {
+    /// `start > end`, or the endpoints can't be compared (as with a NaN).
+    Inverted,
+    /// `start == end`, where a non-empty range was needed.
+    Empty,
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Inverted => write!(f, "range starts after it ends"),
+            RangeError::Empty => write!(f, "range is empty"),
+        }
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+/** A range with `start <= end`.
+
+```
+# use range_split::validated::{RangeError, ValidatedRange};
+let (lo, hi) = (5, 2);
+assert!(ValidatedRange::try_from(2..5).is_ok());
+assert!(ValidatedRange::try_from(2..2).is_ok());
+assert_eq!(ValidatedRange::try_from(lo..hi), Err(RangeError::Inverted));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidatedRange<T>(Range<T>);
+
+/** A range with `start < end`.
+
+```
+# use range_split::validated::{NonEmptyRange, RangeError};
+assert_eq!(NonEmptyRange::try_from(2..5).unwrap().start(), &2);
+assert_eq!(NonEmptyRange::try_from(2..2), Err(RangeError::Empty));
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NonEmptyRange<T>(Range<T>);
+
+macro_rules! accessors {
+    ($name:ident) => {
+        impl<T> $name<T> {
+            pub fn start(&self) -> &T {
+                &self.0.start
+            }
+
+            pub fn end(&self) -> &T {
+                &self.0.end
+            }
+
+            pub fn as_range(&self) -> &Range<T> {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> Range<T> {
+                self.0
+            }
+        }
+
+        impl<T> From<$name<T>> for Range<T> {
+            fn from(r: $name<T>) -> Self {
+                r.0
+            }
+        }
+    };
+}
+
+accessors!(ValidatedRange);
+accessors!(NonEmptyRange);
+
+impl<T: PartialOrd> ValidatedRange<T> {
+    pub fn new(start: T, end: T) -> Result<Self, RangeError> {
+        Self::try_from(start..end)
+    }
+}
+
+impl<T: PartialOrd> TryFrom<Range<T>> for ValidatedRange<T> {
+    type Error = RangeError;
+
+    fn try_from(r: Range<T>) -> Result<Self, Self::Error> {
+        match r.start.partial_cmp(&r.end) {
+            Some(Ordering::Less | Ordering::Equal) => Ok(Self(r)),
+            _ => Err(RangeError::Inverted),
+        }
+    }
+}
+
+impl<T: PartialOrd> NonEmptyRange<T> {
+    pub fn new(start: T, end: T) -> Result<Self, RangeError> {
+        Self::try_from(start..end)
+    }
+}
+
+impl<T: PartialOrd> TryFrom<Range<T>> for NonEmptyRange<T> {
+    type Error = RangeError;
+
+    fn try_from(r: Range<T>) -> Result<Self, Self::Error> {
+        match ValidatedRange::try_from(r)? {
+            ValidatedRange(r) if r.start == r.end => Err(RangeError::Empty),
+            ValidatedRange(r) => Ok(Self(r)),
+        }
+    }
+}
+
+impl<T> From<NonEmptyRange<T>> for ValidatedRange<T> {
+    fn from(r: NonEmptyRange<T>) -> Self {
+        Self(r.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverted_beats_empty() {
+        let (lo, hi) = (3, 1);
+        assert_eq!(NonEmptyRange::new(lo, hi), Err(RangeError::Inverted));
+    }
+
+    #[test]
+    fn incomparable_floats() {
+        assert_eq!(
+            ValidatedRange::new(f64::NAN, 1.0),
+            Err(RangeError::Inverted)
+        );
+        assert_eq!(NonEmptyRange::new(0.0, f64::NAN), Err(RangeError::Inverted));
+    }
+
+    #[test]
+    fn into_containers() {
+        let mut buf = crate::buffered::Buffered::new();
+        buf.add(NonEmptyRange::new(0, 10).unwrap());
+        buf.add(ValidatedRange::new(10, 20).unwrap());
+        assert_eq!(buf.end(0), Some(20));
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Borrowed windows onto [`RangeSet`]s.
+
+use core::ops::Range;
+
+use crate::storage::RangeSet;
+use crate::{runs, Split};
+
+/** The part of a [`RangeSet`] that lies within a window,
+borrowed rather than copied: the runs at either edge are clipped as they're read.
+
+Usage:
+```
+# use range_split::storage::RangeSet;
+let set: RangeSet<u32> = [0..10, 20..30, 40..50].into_iter().collect();
+let view = set.view(5..45);
+assert_eq!(view.iter().collect::<Vec<_>>(), vec![5..10, 20..30, 40..45]);
+assert!(view.contains(7) && !view.contains(2));
+
+// Only copied when asked for
+let owned: RangeSet<u32> = view.restrict(&(25..100)).to_set();
+assert_eq!(owned.iter().collect::<Vec<_>>(), vec![25..30, 40..45]);
+```
+**/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeSetView<'a, T> {
+    /// Only the runs that overlap `window`.
+    runs: &'a [Range<T>],
+    window: Range<T>,
+}
+
+impl<'a, T> RangeSetView<'a, T>
+where
+    T: Sized + Ord + Copy,
+{
+    /// View `set` through `window`.
+    pub fn new(set: &'a RangeSet<T>, window: Range<T>) -> Self {
+        Self::over(set.storage(), window)
+    }
+
+    /// View `runs`, which must be sorted and disjoint, through `window`.
+    fn over(runs: &'a [Range<T>], window: Range<T>) -> Self {
+        let i = runs.partition_point(|r| r.end <= window.start);
+        let j = runs.partition_point(|r| r.start < window.end).max(i);
+        Self {
+            runs: &runs[i..j],
+            window,
+        }
+    }
+
+    /// The window this views through.
+    pub fn window(&self) -> &Range<T> {
+        &self.window
+    }
+
+    /// A view of the part of this one within `window` as well.
+    pub fn restrict(&self, window: &Range<T>) -> Self {
+        let narrower = match self.window.split(window).1 {
+            Some(w) => w,
+            None => self.window.start..self.window.start,
+        };
+        Self::over(self.runs, narrower)
+    }
+
+    /// The clipped runs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.runs.iter().filter_map(|r| r.split(&self.window).1)
+    }
+
+    pub fn contains(&self, t: T) -> bool {
+        self.window.contains(&t) && runs::containing(self.runs, t).is_some()
+    }
+
+    /// Number of runs within the window.
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn first(&self) -> Option<Range<T>> {
+        self.iter().next()
+    }
+
+    pub fn last(&self) -> Option<Range<T>> {
+        self.runs.last().and_then(|r| r.split(&self.window).1)
+    }
+
+    /// Copy the clipped runs out.
+    pub fn to_vec(&self) -> Vec<Range<T>> {
+        self.iter().collect()
+    }
+
+    /// Copy the clipped runs out into an owned set.
+    pub fn to_set(&self) -> RangeSet<T> {
+        self.iter().collect()
+    }
+}
+
+impl<T> RangeSet<T>
+where
+    T: Sized + Ord + Copy,
+{
+    /// The part of the set within `window`, without copying it; see [`RangeSetView`].
+    pub fn view(&self, window: Range<T>) -> RangeSetView<'_, T> {
+        RangeSetView::new(self, window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs() -> RangeSet<u32> {
+        [10..20, 30..40, 50..60].into_iter().collect()
+    }
+
+    #[test]
+    fn window_in_a_gap() {
+        let set = runs();
+        let view = RangeSetView::new(&set, 20..30);
+        assert!(view.is_empty());
+        assert_eq!(view.first(), None);
+    }
+
+    #[test]
+    fn inverted_window() {
+        let (a, b) = (40, 20);
+        assert!(runs().view(a..b).is_empty())
+    }
+
+    #[test]
+    fn edges_clipped() {
+        let set = runs();
+        let view = RangeSetView::new(&set, 15..55);
+        assert_eq!(view.len(), 3);
+        assert_eq!(view.first(), Some(15..20));
+        assert_eq!(view.last(), Some(50..55));
+        assert!(!view.contains(55));
+    }
+
+    #[test]
+    fn disjoint_restriction() {
+        let set = runs();
+        let view = RangeSetView::new(&set, 0..35);
+        assert!(view.restrict(&(40..60)).is_empty())
+    }
+}
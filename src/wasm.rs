@@ -0,0 +1,382 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! JavaScript bindings through `wasm-bindgen`: splitting, range sets, and the
+//! [`Buffered`] tracker, each for `u32` and `f64` endpoints.
+//!
+//! Batches of ranges travel as flat typed arrays of `[start0, end0, start1, end1, ...]`,
+//! so that they cross into WebAssembly in one copy. `f64` endpoints may be infinite but not NaN.
+//! Build with e.g. `wasm-pack build --features wasm`.
+
+use core::cmp::Ordering;
+use core::ops::{Range, Sub};
+
+use wasm_bindgen::prelude::*;
+
+use crate::buffered::Buffered;
+use crate::storage::RangeSet;
+use crate::Split;
+
+/// An endpoint type, and how it crosses to and from JavaScript.
+trait Endpoint: Sized + Ord + Copy {
+    type Js: Copy;
+
+    fn from_js(x: Self::Js) -> Result<Self, &'static str>;
+
+    fn to_js(self) -> Self::Js;
+}
+
+impl Endpoint for u32 {
+    type Js = u32;
+
+    fn from_js(x: u32) -> Result<Self, &'static str> {
+        Ok(x)
+    }
+
+    fn to_js(self) -> u32 {
+        self
+    }
+}
+
+/// A position given as an `f64` that isn't NaN, so that positions are totally ordered.
+#[derive(Debug, Clone, Copy)]
+struct Position(f64);
+
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Position {}
+
+impl PartialOrd for Position {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Position {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Sub for Position {
+    type Output = f64;
+
+    fn sub(self, other: Self) -> f64 {
+        self.0 - other.0
+    }
+}
+
+impl Endpoint for Position {
+    type Js = f64;
+
+    fn from_js(x: f64) -> Result<Self, &'static str> {
+        if x.is_nan() {
+            return Err("range endpoints can't be NaN");
+        }
+        // `total_cmp` puts -0 before 0, which would keep touching ranges apart
+        Ok(Self(x + 0.0))
+    }
+
+    fn to_js(self) -> f64 {
+        self.0
+    }
+}
+
+fn range<E: Endpoint>(start: E::Js, end: E::Js) -> Result<Range<E>, &'static str> {
+    Ok(E::from_js(start)?..E::from_js(end)?)
+}
+
+/// The ranges in the flat array `flat`.
+fn from_flat<E: Endpoint>(flat: &[E::Js]) -> Result<Vec<Range<E>>, &'static str> {
+    if flat.len() % 2 != 0 {
+        return Err("a flat array of ranges must have an even length");
+    }
+    flat.chunks_exact(2).map(|p| range(p[0], p[1])).collect()
+}
+
+fn to_flat<E: Endpoint>(ranges: impl IntoIterator<Item = Range<E>>) -> Box<[E::Js]> {
+    ranges
+        .into_iter()
+        .flat_map(|r| [r.start.to_js(), r.end.to_js()])
+        .collect()
+}
+
+/// The pieces of `a` split by `b`, flat, with each missing piece written as empty.
+fn split_flat<E: Endpoint>(a: Range<E>, b: Range<E>) -> Box<[E::Js]> {
+    let empty = a.start..a.start;
+    let (below, inter, above) = a.split(&b);
+    let pieces = [below, inter, above].map(|p| {
+        p.filter(|p| p.start < p.end)
+            .unwrap_or_else(|| empty.clone())
+    });
+    to_flat(pieces)
+}
+
+/// The bindings for one endpoint type.
+macro_rules! bindings {
+    ($t:ty, $js:ty, $set:ident, $buffered:ident, $split:ident, $split_js:literal) => {
+        /// A sorted, disjoint, coalesced set of ranges. Its `length` is the number of runs.
+        #[wasm_bindgen]
+        #[derive(Clone, Default)]
+        pub struct $set {
+            set: RangeSet<$t>,
+        }
+
+        #[wasm_bindgen]
+        impl $set {
+            #[wasm_bindgen(constructor)]
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// The set of the ranges in a flat array.
+            #[wasm_bindgen(js_name = fromFlat)]
+            pub fn from_flat(flat: &[$js]) -> Result<$set, JsError> {
+                let mut out = Self::new();
+                out.insert_many(flat)?;
+                Ok(out)
+            }
+
+            pub fn insert(&mut self, start: $js, end: $js) -> Result<(), JsError> {
+                self.set
+                    .insert(range::<$t>(start, end).map_err(JsError::new)?);
+                Ok(())
+            }
+
+            pub fn remove(&mut self, start: $js, end: $js) -> Result<(), JsError> {
+                self.set
+                    .remove(&range::<$t>(start, end).map_err(JsError::new)?);
+                Ok(())
+            }
+
+            /// Insert every range of a flat array.
+            #[wasm_bindgen(js_name = insertMany)]
+            pub fn insert_many(&mut self, flat: &[$js]) -> Result<(), JsError> {
+                for r in from_flat::<$t>(flat).map_err(JsError::new)? {
+                    self.set.insert(r);
+                }
+                Ok(())
+            }
+
+            /// Remove every range of a flat array.
+            #[wasm_bindgen(js_name = removeMany)]
+            pub fn remove_many(&mut self, flat: &[$js]) -> Result<(), JsError> {
+                for r in from_flat::<$t>(flat).map_err(JsError::new)? {
+                    self.set.remove(&r);
+                }
+                Ok(())
+            }
+
+            pub fn contains(&self, t: $js) -> bool {
+                <$t>::from_js(t).is_ok_and(|t| self.set.contains(t))
+            }
+
+            /// For each of `points`, 1 if it's in the set and 0 if not.
+            #[wasm_bindgen(js_name = containsMany)]
+            pub fn contains_many(&self, points: &[$js]) -> Box<[u8]> {
+                points.iter().map(|&t| u8::from(self.contains(t))).collect()
+            }
+
+            #[wasm_bindgen(getter)]
+            pub fn length(&self) -> usize {
+                self.set.len()
+            }
+
+            /// The runs, in order, as a flat array.
+            #[wasm_bindgen(js_name = toFlat)]
+            pub fn to_flat(&self) -> Box<[$js]> {
+                to_flat(self.set.iter())
+            }
+
+            pub fn union(&self, other: &$set) -> $set {
+                let mut out = self.clone();
+                for r in other.set.iter() {
+                    out.set.insert(r);
+                }
+                out
+            }
+
+            pub fn intersect(&self, other: &$set) -> $set {
+                let mut out = self.clone();
+                out.set.intersect_assign(&other.set);
+                out
+            }
+
+            pub fn subtract(&self, other: &$set) -> $set {
+                let mut out = self.clone();
+                out.set.subtract_assign(&other.set);
+                out
+            }
+        }
+
+        /// The buffered regions of a media timeline, as [`Buffered`] tracks them.
+        #[wasm_bindgen]
+        #[derive(Default)]
+        pub struct $buffered {
+            buffered: Buffered<$t>,
+        }
+
+        #[wasm_bindgen]
+        impl $buffered {
+            #[wasm_bindgen(constructor)]
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            pub fn add(&mut self, start: $js, end: $js) -> Result<(), JsError> {
+                self.buffered
+                    .add(range::<$t>(start, end).map_err(JsError::new)?);
+                Ok(())
+            }
+
+            pub fn remove(&mut self, start: $js, end: $js) -> Result<(), JsError> {
+                self.buffered
+                    .remove(&range::<$t>(start, end).map_err(JsError::new)?);
+                Ok(())
+            }
+
+            /// Add every range of a flat array.
+            #[wasm_bindgen(js_name = addMany)]
+            pub fn add_many(&mut self, flat: &[$js]) -> Result<(), JsError> {
+                for r in from_flat::<$t>(flat).map_err(JsError::new)? {
+                    self.buffered.add(r);
+                }
+                Ok(())
+            }
+
+            #[wasm_bindgen(getter)]
+            pub fn length(&self) -> usize {
+                self.buffered.len()
+            }
+
+            /// Start of the `i`th buffered range, or `undefined`.
+            pub fn start(&self, i: usize) -> Option<$js> {
+                self.buffered.start(i).map(<$t>::to_js)
+            }
+
+            /// End of the `i`th buffered range, or `undefined`.
+            pub fn end(&self, i: usize) -> Option<$js> {
+                self.buffered.end(i).map(<$t>::to_js)
+            }
+
+            pub fn contains(&self, t: $js) -> bool {
+                <$t>::from_js(t).is_ok_and(|t| self.buffered.contains(t))
+            }
+
+            /// Where playback from `t` would run out of data, or `undefined` if `t` isn't buffered.
+            #[wasm_bindgen(js_name = nextGap)]
+            pub fn next_gap(&self, t: $js) -> Option<$js> {
+                let t = <$t>::from_js(t).ok()?;
+                self.buffered.next_gap(t).map(<$t>::to_js)
+            }
+
+            /// How much is buffered contiguously from `t` onward.
+            #[wasm_bindgen(js_name = bufferedAhead)]
+            pub fn buffered_ahead(&self, t: $js) -> $js {
+                <$t>::from_js(t).map_or(Default::default(), |t| self.buffered.buffered_ahead(t))
+            }
+
+            /// The buffered ranges, in order, as a flat array.
+            #[wasm_bindgen(js_name = toFlat)]
+            pub fn to_flat(&self) -> Box<[$js]> {
+                to_flat(self.buffered.iter().cloned())
+            }
+
+            /// The buffered ranges as a set.
+            pub fn ranges(&self) -> $set {
+                $set {
+                    set: self.buffered.ranges().clone(),
+                }
+            }
+        }
+
+        /// Split `a` by `b` into the parts below, inside, and above `b`, as a flat array of
+        /// three ranges. A missing piece comes back empty, with its start equal to its end.
+        #[wasm_bindgen(js_name = $split_js)]
+        pub fn $split(
+            a_start: $js,
+            a_end: $js,
+            b_start: $js,
+            b_end: $js,
+        ) -> Result<Box<[$js]>, JsError> {
+            let a = range::<$t>(a_start, a_end).map_err(JsError::new)?;
+            let b = range::<$t>(b_start, b_end).map_err(JsError::new)?;
+            Ok(split_flat(a, b))
+        }
+    };
+}
+
+bindings!(u32, u32, RangeSetU32, BufferedU32, split_u32, "splitU32");
+bindings!(
+    Position,
+    f64,
+    RangeSetF64,
+    BufferedF64,
+    split_f64,
+    "splitF64"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_arrays() {
+        assert_eq!(from_flat::<u32>(&[0, 5, 8, 9]), Ok(vec![0..5, 8..9]));
+        assert!(from_flat::<u32>(&[0, 5, 8]).is_err());
+        assert!(from_flat::<Position>(&[0.0, f64::NAN]).is_err());
+        assert_eq!(&*to_flat([0u32..5, 8..9]), &[0, 5, 8, 9]);
+    }
+
+    #[test]
+    fn split_pieces() {
+        assert_eq!(&*split_flat(0u32..10, 3..6), &[0, 3, 3, 6, 6, 10]);
+        assert_eq!(&*split_flat(0u32..10, 20..30), &[0, 10, 0, 0, 0, 0]);
+        let (p, q) = (6, 3);
+        assert_eq!(&*split_flat(0u32..10, p..q), &[0, 6, 0, 0, 3, 10]);
+    }
+
+    #[test]
+    fn sets() {
+        let mut a = RangeSetU32::new();
+        a.insert_many(&[0, 5, 3, 10, 20, 30]).ok().unwrap();
+        assert_eq!(a.length(), 2);
+        assert_eq!(&*a.contains_many(&[4, 15, 20]), &[1, 0, 1]);
+        let mut b = RangeSetU32::new();
+        b.insert(5, 25).ok().unwrap();
+        assert_eq!(&*a.intersect(&b).to_flat(), &[5, 10, 20, 25]);
+        assert_eq!(&*a.subtract(&b).to_flat(), &[0, 5, 25, 30]);
+        assert_eq!(&*a.union(&b).to_flat(), &[0, 30]);
+    }
+
+    #[test]
+    fn buffered_seconds() {
+        let mut buf = BufferedF64::new();
+        buf.add_many(&[0.0, 4.5, 4.5, 10.0, 20.0, f64::INFINITY])
+            .ok()
+            .unwrap();
+        assert_eq!(buf.length(), 2);
+        assert_eq!(
+            (buf.start(1), buf.end(1)),
+            (Some(20.0), Some(f64::INFINITY))
+        );
+        assert_eq!(buf.start(2), None);
+        assert_eq!(buf.next_gap(3.0), Some(10.0));
+        assert_eq!(buf.buffered_ahead(7.5), 2.5);
+        assert_eq!(buf.buffered_ahead(f64::NAN), 0.0);
+        assert!(!buf.contains(f64::NAN));
+        assert_eq!(buf.ranges().length(), 2);
+    }
+
+    #[test]
+    fn negative_zero_touches() {
+        let mut set = RangeSetF64::new();
+        set.insert(-1.0, -0.0).ok().unwrap();
+        set.insert(0.0, 1.0).ok().unwrap();
+        assert_eq!(&*set.to_flat(), &[-1.0, 1.0]);
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2023 Alex Jago <abjago.net>
+// Released under the MIT or Apache-2.0 licenses, at your option.
+
+//! Splitting ranges of timestamps into buckets of several granularities at once,
+//! as timer wheels and downsampling retention policies arrange them.
+//!
+//! Each level's buckets are aligned to multiples of its width, counted from zero
+//! (so, for Unix timestamps, from the epoch).
+
+use core::ops::Range;
+
+/// A piece of a range from [`split_cascade`], and the level whose bucket it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bucket {
+    /// Index into the widths given.
+    pub level: usize,
+    pub range: Range<u64>,
+}
+
+/** Cut `range` into whole, aligned buckets of the widths in `widths`, using the widest
+that fit: the finest level up to the first boundary of the next, and so on up to the
+widest level, which covers the middle, then back down towards `range.end`.
+
+Pieces at either end that aren't whole buckets of even the finest level
+are given at level 0, clipped to `range`. An empty range yields nothing.
+
+```
+# use range_split::wheel::{split_cascade, Bucket};
+// Seconds, minutes and hours
+let widths = [1, 60, 3600];
+let pieces: Vec<_> = split_cascade(&(3598..7322), &widths).collect();
+assert_eq!(pieces, vec![
+    Bucket { level: 0, range: 3598..3599 },
+    Bucket { level: 0, range: 3599..3600 },
+    Bucket { level: 2, range: 3600..7200 },
+    Bucket { level: 1, range: 7200..7260 },
+    Bucket { level: 1, range: 7260..7320 },
+    Bucket { level: 0, range: 7320..7321 },
+    Bucket { level: 0, range: 7321..7322 },
+]);
+```
+Panics unless `widths` is non-empty, and each width is positive and divides the next.
+**/
+pub fn split_cascade<'a>(
+    range: &Range<u64>,
+    widths: &'a [u64],
+) -> impl Iterator<Item = Bucket> + 'a {
+    assert!(
+        widths.first().is_some_and(|&w| w > 0)
+            && widths
+                .windows(2)
+                .all(|w| w[1] > w[0] && w[1].is_multiple_of(w[0])),
+        "widths must be positive, each dividing the next"
+    );
+    let mut t = range.start;
+    let end = range.end;
+    core::iter::from_fn(move || {
+        if t >= end {
+            return None;
+        }
+        // The widest bucket starting at `t` that ends within `range`
+        let whole = widths
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|&(_, &w)| t.is_multiple_of(w) && t.checked_add(w).is_some_and(|e| e <= end));
+        let (level, next) = match whole {
+            Some((level, &w)) => (level, t + w),
+            // Up to the next boundary of the finest level, within `range`
+            None => (0, (t - t % widths[0]).saturating_add(widths[0]).min(end)),
+        };
+        let bucket = Bucket {
+            level,
+            range: t..next,
+        };
+        t = next;
+        Some(bucket)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unaligned_edges() {
+        let pieces: Vec<_> = split_cascade(&(5..47), &[10, 20]).collect();
+        let levels: Vec<_> = pieces.iter().map(|b| (b.level, b.range.clone())).collect();
+        assert_eq!(
+            levels,
+            vec![(0, 5..10), (0, 10..20), (1, 20..40), (0, 40..47)]
+        )
+    }
+
+    #[test]
+    fn covers_exactly() {
+        let widths = [1, 60, 3600, 86_400];
+        for range in [0..1, 59..61, 1_000..1_000_000, u64::MAX - 5_000..u64::MAX] {
+            let pieces: Vec<_> = split_cascade(&range, &widths).collect();
+            assert_eq!(pieces[0].range.start, range.start);
+            assert_eq!(pieces[pieces.len() - 1].range.end, range.end);
+            assert!(pieces
+                .windows(2)
+                .all(|w| w[0].range.end == w[1].range.start));
+            assert!(pieces
+                .iter()
+                .all(|b| b.range.start.is_multiple_of(widths[b.level])
+                    && b.range.end - b.range.start == widths[b.level]));
+        }
+        let (p, q) = (5, 5);
+        assert_eq!(split_cascade(&(p..q), &widths).count(), 0)
+    }
+
+    #[test]
+    #[should_panic]
+    fn widths_must_nest() {
+        let _ = split_cascade(&(0..10), &[2, 3]);
+    }
+}